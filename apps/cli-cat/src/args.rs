@@ -0,0 +1,369 @@
+use std::{ffi::OsString, fmt, path::PathBuf};
+
+pub(crate) const USAGE: &str = "\
+Usage:
+    cli-cat [options] [check] <log>...   Analyze logs, `-` reads from stdin. Logs can be
+                                         gzipped, or in a zip file with other logs
+    cli-cat [options] fix <instance dir> [--yes]
+    cli-cat [options] instance <instance dir>
+    cli-cat [options] mods <instance or mods dir>
+    cli-cat [options] scan <MultiMC dir>
+    cli-cat [options] report <log> [-o <file>]   Show the log with the findings highlighted,
+                                                 or write it as HTML to <file>
+    cli-cat [options] watch <instance dir or log>   Print findings while the game is running
+    cli-cat [options] fetch <url>   Analyze a log shared on paste.ee, mclo.gs, pastebin,
+                                    hastebin, GitHub Gist or Discord
+
+Options:
+    --format <format>             How `check` and `fetch` print their results: text (the
+                                  default), json, markdown or sarif
+    --color <auto|always|never>   Color the output, by default when writing to a terminal
+    -y, --yes                     Apply fixes without asking
+    -o, --output <file>           Where `report` writes its HTML page, `-` for stdout
+    -h, --help                    Show this help
+    -V, --version                 Show the version
+
+Exit codes:
+    0  nothing was found
+    1  problems were found
+    2  problems with the launcher's origin were found that have to be fixed first, like a
+       pirated launcher
+    3  something went wrong, like a file that couldn't be read
+    4  the arguments were wrong";
+
+/// Where to read a log from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Input {
+    Stdin,
+    File(PathBuf),
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Input::Stdin => f.write_str("stdin"),
+            Input::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Command {
+    Check(Vec<Input>),
+    Fix { dir: PathBuf, yes: bool },
+    Instance(PathBuf),
+    Mods(PathBuf),
+    Scan(PathBuf),
+    Report { input: Input, output: Option<PathBuf> },
+    Watch(PathBuf),
+    Fetch(String),
+    Help,
+    Version,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Color {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format {
+    Text,
+    Json,
+    Markdown,
+    Sarif,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Args {
+    pub(crate) command: Command,
+    pub(crate) color: Color,
+    pub(crate) format: Format,
+}
+
+/// Wrong arguments, with what was wrong about them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct UsageError(String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\n\n{}", self.0, USAGE)
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+fn usage_error(message: impl Into<String>) -> UsageError {
+    UsageError(message.into())
+}
+
+const SUBCOMMANDS: &[&str] = &["check", "fix", "instance", "mods", "scan", "report", "watch", "fetch"];
+
+/// Parses the arguments, without the program name. Options can go anywhere, and everything
+/// after `--` is taken as a file name. File names don't have to be valid UTF-8.
+pub(crate) fn parse(args: impl IntoIterator<Item = OsString>) -> Result<Args, UsageError> {
+    let mut color = Color::Auto;
+    let mut format = Format::Text;
+    let mut yes = false;
+    let mut output = None;
+    let mut positional: Vec<OsString> = Vec::new();
+    // Whether a subcommand came before `--`, after which it would be a file name
+    let mut dashes_first = false;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let Some(text) = arg.to_str() else {
+            positional.push(arg);
+            continue;
+        };
+        match text {
+            "--" => {
+                dashes_first = positional.is_empty();
+                positional.extend(args.by_ref());
+                break;
+            }
+            "-h" | "--help" => return Ok(Args { command: Command::Help, color, format }),
+            "-V" | "--version" => return Ok(Args { command: Command::Version, color, format }),
+            "-y" | "--yes" => yes = true,
+            "--format" => {
+                let value = args.next().ok_or_else(|| usage_error("`--format` needs a value"))?;
+                format = parse_format(&value.to_string_lossy())?;
+            }
+            "-o" | "--output" => {
+                let value = args.next().ok_or_else(|| usage_error("`--output` needs a file"))?;
+                output = Some(PathBuf::from(value));
+            }
+            "--color" => {
+                let value = args.next().ok_or_else(|| usage_error("`--color` needs a value"))?;
+                color = parse_color(&value.to_string_lossy())?;
+            }
+            _ => {
+                if let Some(value) = text.strip_prefix("--color=") {
+                    color = parse_color(value)?;
+                } else if let Some(value) = text.strip_prefix("--format=") {
+                    format = parse_format(value)?;
+                } else if text.starts_with('-') && text != "-" {
+                    return Err(usage_error(format!("Unknown option `{}`", text)));
+                } else {
+                    positional.push(arg);
+                }
+            }
+        }
+    }
+
+    let (subcommand, operands) = match positional.first().and_then(|p| p.to_str()) {
+        Some(name) if SUBCOMMANDS.contains(&name) && !dashes_first => (name, &positional[1..]),
+        _ => ("check", &positional[..]),
+    };
+    if yes && subcommand != "fix" {
+        return Err(usage_error("`--yes` only works with `fix`"));
+    }
+    if output.is_some() && subcommand != "report" {
+        return Err(usage_error("`--output` only works with `report`"));
+    }
+    if format != Format::Text && subcommand != "check" && subcommand != "fetch" {
+        return Err(usage_error("`--format` only works with `check` and `fetch`"));
+    }
+    let one_dir = |what: &str| match operands {
+        [dir] => Ok(PathBuf::from(dir)),
+        [] => Err(usage_error(format!("`{}` needs a directory", what))),
+        _ => Err(usage_error(format!("`{}` takes a single directory", what))),
+    };
+    let command = match subcommand {
+        "fix" => Command::Fix {
+            dir: one_dir("fix")?,
+            yes,
+        },
+        "instance" => Command::Instance(one_dir("instance")?),
+        "mods" => Command::Mods(one_dir("mods")?),
+        "scan" => Command::Scan(one_dir("scan")?),
+        "report" => match operands {
+            [log] => Command::Report {
+                input: input(log),
+                output,
+            },
+            _ => return Err(usage_error("`report` takes a single log")),
+        },
+        "watch" => match operands {
+            [path] => Command::Watch(PathBuf::from(path)),
+            _ => return Err(usage_error("`watch` takes a single instance folder or log")),
+        },
+        "fetch" => match operands {
+            [url] => Command::Fetch(url.to_string_lossy().into_owned()),
+            _ => return Err(usage_error("`fetch` takes a single link")),
+        },
+        _ => {
+            if operands.is_empty() {
+                return Err(usage_error("No logs given"));
+            }
+            Command::Check(operands.iter().map(input).collect())
+        }
+    };
+    Ok(Args { command, color, format })
+}
+
+fn parse_format(value: &str) -> Result<Format, UsageError> {
+    match value {
+        "text" => Ok(Format::Text),
+        "json" => Ok(Format::Json),
+        "markdown" | "md" => Ok(Format::Markdown),
+        "sarif" => Ok(Format::Sarif),
+        _ => Err(usage_error(format!("Unknown format `{}`", value))),
+    }
+}
+
+fn input(operand: &OsString) -> Input {
+    if operand == "-" {
+        Input::Stdin
+    } else {
+        Input::File(operand.into())
+    }
+}
+
+fn parse_color(value: &str) -> Result<Color, UsageError> {
+    match value {
+        "auto" => Ok(Color::Auto),
+        "always" => Ok(Color::Always),
+        "never" => Ok(Color::Never),
+        _ => Err(usage_error(format!("Unknown color setting `{}`", value))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<Args, UsageError> {
+        parse(args.iter().map(OsString::from))
+    }
+
+    fn command(args: &[&str]) -> Command {
+        parse_args(args).unwrap().command
+    }
+
+    fn error(args: &[&str]) -> String {
+        parse_args(args).unwrap_err().0
+    }
+
+    fn files(names: &[&str]) -> Command {
+        Command::Check(names.iter().map(|name| Input::File(name.into())).collect())
+    }
+
+    #[test]
+    fn checks_logs_without_a_subcommand() {
+        assert_eq!(command(&["latest.log"]), files(&["latest.log"]));
+        assert_eq!(command(&["check", "a.log", "b.log"]), files(&["a.log", "b.log"]));
+        assert_eq!(command(&["-"]), Command::Check(vec![Input::Stdin]));
+        assert_eq!(error(&[]), "No logs given");
+        assert_eq!(error(&["check"]), "No logs given");
+    }
+
+    #[test]
+    fn takes_options_anywhere() {
+        let expected = Args {
+            command: Command::Fix {
+                dir: "instances/Test".into(),
+                yes: true,
+            },
+            color: Color::Never,
+            format: Format::Text,
+        };
+        let placements: [&[&str]; 3] = [
+            &["--yes", "--color", "never", "fix", "instances/Test"],
+            &["fix", "--yes", "instances/Test", "--color=never"],
+            &["fix", "instances/Test", "--color", "never", "-y"],
+        ];
+        for args in placements {
+            assert_eq!(parse_args(args).unwrap(), expected, "{:?}", args);
+        }
+        assert_eq!(parse_args(&["latest.log", "--format=json"]).unwrap().format, Format::Json);
+        assert_eq!(parse_args(&["--format", "md", "latest.log"]).unwrap().format, Format::Markdown);
+        // Help wins over everything else
+        assert_eq!(command(&["fix", "--help", "--yes"]), Command::Help);
+    }
+
+    #[test]
+    fn takes_everything_after_dashes_as_files() {
+        // `-` is still stdin, like for other tools
+        assert_eq!(
+            command(&["--", "--yes", "-"]),
+            Command::Check(vec![Input::File("--yes".into()), Input::Stdin])
+        );
+        assert_eq!(command(&["--", "fix"]), files(&["fix"]));
+        assert_eq!(command(&["check", "--", "-h"]), files(&["-h"]));
+        assert_eq!(
+            command(&["report", "--", "-o"]),
+            Command::Report {
+                input: Input::File("-o".into()),
+                output: None
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_options_and_values() {
+        assert_eq!(error(&["--verbose", "latest.log"]), "Unknown option `--verbose`");
+        assert_eq!(error(&["--format", "xml", "latest.log"]), "Unknown format `xml`");
+        assert_eq!(error(&["--color=sometimes", "latest.log"]), "Unknown color setting `sometimes`");
+        assert_eq!(error(&["latest.log", "--format"]), "`--format` needs a value");
+        assert_eq!(error(&["report", "latest.log", "-o"]), "`--output` needs a file");
+    }
+
+    #[test]
+    fn only_takes_options_for_the_subcommands_they_work_with() {
+        assert_eq!(error(&["--yes", "latest.log"]), "`--yes` only works with `fix`");
+        assert_eq!(error(&["scan", "MultiMC", "-y"]), "`--yes` only works with `fix`");
+        assert_eq!(error(&["-o", "out.html", "latest.log"]), "`--output` only works with `report`");
+        assert_eq!(error(&["fix", "Test", "-o", "out.html"]), "`--output` only works with `report`");
+        assert_eq!(error(&["--format", "json", "report", "latest.log"]), "`--format` only works with `check` and `fetch`");
+        assert_eq!(error(&["mods", "Test", "--format=sarif"]), "`--format` only works with `check` and `fetch`");
+
+        assert_eq!(
+            command(&["report", "latest.log", "-o", "-"]),
+            Command::Report {
+                input: Input::File("latest.log".into()),
+                output: Some("-".into())
+            }
+        );
+        assert_eq!(
+            parse_args(&["fetch", "https://mclo.gs/aBc1234", "--format", "sarif"]).unwrap(),
+            Args {
+                command: Command::Fetch("https://mclo.gs/aBc1234".to_string()),
+                color: Color::Auto,
+                format: Format::Sarif,
+            }
+        );
+        // `--format text` is the default, so it's fine anywhere
+        assert!(parse_args(&["scan", "MultiMC", "--format", "text"]).is_ok());
+    }
+
+    #[test]
+    fn checks_the_number_of_operands() {
+        assert_eq!(error(&["fix"]), "`fix` needs a directory");
+        assert_eq!(error(&["scan", "a", "b"]), "`scan` takes a single directory");
+        assert_eq!(error(&["report", "a.log", "b.log"]), "`report` takes a single log");
+        assert_eq!(error(&["fetch"]), "`fetch` takes a single link");
+        assert_eq!(command(&["watch", "instances/Test"]), Command::Watch("instances/Test".into()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn takes_file_names_that_arent_utf8() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let name = OsString::from_vec(b"cr\xe4sh.log".to_vec());
+        let args = parse([OsString::from("--color=never"), name.clone()]).unwrap();
+        assert_eq!(args.command, Command::Check(vec![Input::File(name.clone().into())]));
+        assert_eq!(args.color, Color::Never);
+
+        let args = parse([OsString::from("fix"), name.clone(), OsString::from("-y")]).unwrap();
+        assert_eq!(
+            args.command,
+            Command::Fix {
+                dir: name.into(),
+                yes: true
+            }
+        );
+    }
+}
@@ -0,0 +1,39 @@
+use std::error::Error;
+
+use background_cat::{
+    input::{self, Limits},
+    render::Terminal,
+    Analyzer,
+};
+use paste_cat::{FetchLimits, Fetcher, Providers, Url};
+
+use crate::{
+    args::Format,
+    output::{self, Analyzed},
+    Outcome,
+};
+
+/// Downloads the paste `url` links to and prints what's found in the logs in it, like `check`.
+pub(crate) fn run(url: &str, format: Format, renderer: &Terminal) -> Result<Outcome, Box<dyn Error>> {
+    let parsed: Url = url.parse().map_err(|why| format!("`{}` isn't a link: {}", url, why))?;
+    let link = Providers::default()
+        .resolve(&parsed)
+        .ok_or_else(|| format!("`{}` isn't a link to a paste site cli-cat knows", url))?;
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    let fetcher = Fetcher::new(FetchLimits::default())?;
+    let fetched = runtime
+        .block_on(fetcher.fetch(&link.raw))
+        .map_err(|why| format!("Couldn't fetch {}: {}", link.raw, why))?;
+    let logs = input::extract(url, &fetched.bytes, &Limits::default()).map_err(|why| format!("Couldn't read {}: {}", url, why))?;
+
+    let analyzer = Analyzer::new();
+    let results: Vec<Analyzed> = logs
+        .into_iter()
+        .map(|log| Analyzed {
+            report: analyzer.analyze(&log.text),
+            input: log.name,
+        })
+        .collect();
+    print!("{}", output::write(format, &results, renderer));
+    Ok(crate::outcome(&results))
+}
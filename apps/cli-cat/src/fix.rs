@@ -0,0 +1,78 @@
+use std::{
+    error::Error,
+    io::{self, BufRead, Write},
+    path::Path,
+};
+
+use background_cat::{
+    fix::{apply, plan},
+    instance::Instance,
+    Analyzer, Renderer,
+};
+
+use crate::Outcome;
+
+/// Analyzes the latest log of the instance in `dir` and, after asking unless `yes` is set,
+/// applies the fixes that can be made to the instance.
+pub(crate) fn run(dir: &Path, yes: bool, renderer: &impl Renderer) -> Result<Outcome, Box<dyn Error>> {
+    let mut instance = Instance::open(dir)
+        .map_err(|why| format!("Couldn't open instance {}: {}", dir.display(), why))?;
+    let log = instance
+        .latest_log()
+        .map_err(|why| format!("Couldn't read the latest log of {}: {}", dir.display(), why))?;
+
+    let report = Analyzer::new().analyze(&log);
+    if report.is_blocked() {
+        for d in &report.diagnoses {
+            println!("{} {}", d.severity, renderer.render(&d.message));
+        }
+        eprintln!("Not fixing an instance of an unsupported launcher.");
+        return Ok(Outcome::of(&report.diagnoses));
+    }
+    if report.diagnoses.is_empty() {
+        println!("Didn't find anything to fix.");
+        return Ok(Outcome::Clean);
+    }
+    for d in &report.diagnoses {
+        println!("{} {}", d.severity, renderer.render(&d.message));
+    }
+
+    let outcome = Outcome::of(&report.diagnoses);
+    let plan = plan(&instance, &report.diagnoses)?;
+    if !plan.manual.is_empty() {
+        println!("\nThese have to be done by hand:");
+        for action in &plan.manual {
+            println!("  - {}", renderer.render(&action.to_string()));
+        }
+    }
+    if plan.changes.is_empty() {
+        println!("\nThere is nothing that can be fixed automatically.");
+        return Ok(outcome);
+    }
+    println!("\nThese changes can be made to the instance:");
+    for change in &plan.changes {
+        println!("  - {}", renderer.render(&change.to_string()));
+    }
+    if !yes && !confirm("Apply them? [y/N] ")? {
+        println!("Nothing was changed.");
+        return Ok(outcome);
+    }
+
+    let applied = apply(&mut instance, &plan)?;
+    if let Some(backup) = &applied.backup {
+        println!("Backed up the old instance.cfg to {}", backup.display());
+    }
+    for (from, to) in &applied.moved {
+        println!("Moved {} to {}", from.display(), to.display());
+    }
+    println!("Done.");
+    Ok(outcome)
+}
+
+fn confirm(prompt: &str) -> io::Result<bool> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
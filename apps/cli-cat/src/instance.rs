@@ -0,0 +1,39 @@
+use std::{error::Error, path::Path};
+
+use background_cat::{instance::Instance, Renderer};
+
+use crate::Outcome;
+
+/// Prints the components of the instance in `dir` and the problems found in its files.
+pub(crate) fn run(dir: &Path, renderer: &impl Renderer) -> Result<Outcome, Box<dyn Error>> {
+    let instance = Instance::open(dir)
+        .map_err(|why| format!("Couldn't open instance {}: {}", dir.display(), why))?;
+
+    if let Some(pack) = &instance.pack {
+        for component in &pack.components {
+            println!(
+                "{} {}",
+                component.cached_name.as_deref().unwrap_or(&component.uid),
+                component.version.as_deref().unwrap_or("?")
+            );
+        }
+        println!();
+    }
+
+    let diagnoses = instance.inspect()?;
+    if diagnoses.is_empty() {
+        println!("Didn't find any problems in the instance.");
+    }
+    for d in &diagnoses {
+        println!("{} {}", d.severity, renderer.render(&d.message));
+        for evidence in &d.evidence {
+            println!(
+                "    {} line {}: {}",
+                evidence.source.as_deref().unwrap_or("input"),
+                evidence.line,
+                evidence.text
+            );
+        }
+    }
+    Ok(Outcome::of(&diagnoses))
+}
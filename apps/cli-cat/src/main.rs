@@ -1,11 +1,172 @@
-use std::path::Path;
+use std::{
+    error::Error,
+    fs,
+    io::{self, IsTerminal, Read},
+    process,
+};
 
-use background_cat::common_mistakes;
+use args::{Args, Color, Command, Format, Input};
+use background_cat::{
+    input::{self, Limits},
+    render::Terminal,
+    Analyzer, Diagnosis, RuleSet,
+};
+use output::Analyzed;
+
+mod args;
+mod fetch;
+mod fix;
+mod instance;
+mod mods;
+mod output;
+mod report;
+mod scan;
+mod watch;
+
+/// How a command turned out, which is the exit code. Errors exit with [`EXIT_ERROR`] or, for
+/// wrong arguments, [`EXIT_USAGE`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Outcome {
+    Clean = 0,
+    Findings = 1,
+    OriginProblems = 2,
+}
+
+impl Outcome {
+    /// Only blocking findings about the launcher's origin, like a pirated launcher, are
+    /// [`Outcome::OriginProblems`]. Less certain ones, like a default offline name, are findings.
+    pub(crate) fn of(diagnoses: &[Diagnosis]) -> Outcome {
+        if diagnoses.iter().any(|d| d.rule_set == RuleSet::Origins && d.blocking) {
+            Outcome::OriginProblems
+        } else if diagnoses.is_empty() {
+            Outcome::Clean
+        } else {
+            Outcome::Findings
+        }
+    }
+}
+
+const EXIT_ERROR: i32 = 3;
+const EXIT_USAGE: i32 = 4;
 
 fn main() {
-    let arg: String = std::env::args().nth(1).unwrap();
+    let Args { command, color, format } = match args::parse(std::env::args_os().skip(1)) {
+        Ok(args) => args,
+        Err(why) => {
+            eprintln!("{}", why);
+            process::exit(EXIT_USAGE);
+        }
+    };
+    let renderer = Terminal {
+        color: match color {
+            Color::Always => true,
+            Color::Never => false,
+            Color::Auto => io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+        },
+    };
+
+    let result = match command {
+        Command::Check(inputs) => check(&inputs, format, &renderer),
+        Command::Fix { dir, yes } => fix::run(&dir, yes, &renderer),
+        Command::Instance(dir) => instance::run(&dir, &renderer),
+        Command::Mods(dir) => mods::run(&dir, &renderer),
+        Command::Scan(dir) => scan::run(&dir, &renderer),
+        Command::Report { input, output } => report::run(&input, output.as_deref(), &renderer),
+        Command::Watch(path) => watch::run(&path, &renderer),
+        Command::Fetch(url) => fetch::run(&url, format, &renderer),
+        Command::Help => {
+            println!("{}", args::USAGE);
+            Ok(Outcome::Clean)
+        }
+        Command::Version => {
+            println!("cli-cat {}", env!("CARGO_PKG_VERSION"));
+            Ok(Outcome::Clean)
+        }
+    };
+    match result {
+        Ok(outcome) => process::exit(outcome as i32),
+        Err(why) => {
+            eprintln!("{}", why);
+            process::exit(EXIT_ERROR);
+        }
+    }
+}
+
+/// Analyzes each of `inputs`, or each log in them for archives, and prints the results in
+/// `format`. Inputs that can't be read are reported and skipped, and make the whole check fail.
+fn check(inputs: &[Input], format: Format, renderer: &Terminal) -> Result<Outcome, Box<dyn Error>> {
+    let analyzer = Analyzer::new();
+    let mut results = Vec::new();
+    let mut failed = 0;
+    for input in inputs {
+        let logs = match read(input) {
+            Ok(bytes) => input::extract(&input.to_string(), &bytes, &Limits::default()).map_err(|why| why.to_string()),
+            Err(why) => Err(why.to_string()),
+        };
+        match logs {
+            Ok(logs) => results.extend(logs.into_iter().map(|log| Analyzed {
+                report: analyzer.analyze(&log.text),
+                input: log.name,
+            })),
+            Err(why) => {
+                eprintln!("Couldn't read {}: {}", input, why);
+                failed += 1;
+            }
+        }
+    }
+    print!("{}", output::write(format, &results, renderer));
+
+    match failed {
+        0 => Ok(outcome(&results)),
+        1 => Err("1 input couldn't be read".into()),
+        n => Err(format!("{} inputs couldn't be read", n).into()),
+    }
+}
+
+/// The worst outcome of `results`.
+pub(crate) fn outcome(results: &[Analyzed]) -> Outcome {
+    results
+        .iter()
+        .map(|r| Outcome::of(&r.report.diagnoses))
+        .max()
+        .unwrap_or(Outcome::Clean)
+}
+
+pub(crate) fn read(input: &Input) -> io::Result<Vec<u8>> {
+    match input {
+        Input::Stdin => {
+            let mut bytes = Vec::new();
+            io::stdin().lock().read_to_end(&mut bytes)?;
+            Ok(bytes)
+        }
+        Input::File(path) => fs::read(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn launcher_log(version: &str, username: &str) -> String {
+        format!(
+            "MultiMC version: {}\n\n\
+             Minecraft folder is:\n/home/user/MultiMC/instances/1.16.5/.minecraft\n\n\n\
+             Main Class:\n  net.minecraft.client.main.Main\n\n\
+             Params:\n  --username {} --version 1.16.5 --uuid <PROFILE ID> --accessToken <ACCESS TOKEN>\n",
+            version, username
+        )
+    }
 
-    let log = std::fs::read_to_string(Path::new(&arg)).unwrap();
+    fn outcome_of(log: &str) -> Outcome {
+        Outcome::of(&Analyzer::new().analyze(log).diagnoses)
+    }
 
-    println!("{:?}", common_mistakes(&log));
+    #[test]
+    fn only_blocking_origin_problems_exit_with_2() {
+        assert_eq!(outcome_of(""), Outcome::Clean);
+        assert_eq!(outcome_of(&launcher_log("0.7.0-3275", "<PROFILE NAME>")), Outcome::Clean);
+        // A default offline name only adds to the other findings
+        assert_eq!(outcome_of(&launcher_log("0.7.0-3275", "Player482")), Outcome::Findings);
+        assert_eq!(outcome_of(&launcher_log("0.6.16-custom", "<PROFILE NAME>")), Outcome::OriginProblems);
+    }
 }
@@ -0,0 +1,65 @@
+use std::{error::Error, path::Path};
+
+use background_cat::{instance::Instance, mods, Renderer};
+
+use crate::Outcome;
+
+/// Lists the mods in `dir` with their versions and dependencies, and the problems between
+/// them. `dir` is either an instance, whose loader and Minecraft version are checked too, or a
+/// mods folder.
+pub(crate) fn run(dir: &Path, renderer: &impl Renderer) -> Result<Outcome, Box<dyn Error>> {
+    let instance = match Instance::open(dir) {
+        Ok(instance) => Some(instance),
+        Err(_) if !dir.join("instance.cfg").exists() => None,
+        Err(why) => return Err(format!("Couldn't open instance {}: {}", dir.display(), why).into()),
+    };
+    let mods_dir = instance.as_ref().map_or_else(|| dir.to_path_buf(), Instance::mods_dir);
+    let mods::ModsDir { jars, unreadable } = mods::read_mods_dir(&mods_dir)
+        .map_err(|why| format!("Couldn't read {}: {}", mods_dir.display(), why))?;
+    let pack = instance.as_ref().and_then(|i| i.pack.as_ref());
+    let loader = pack.and_then(|p| p.loader());
+
+    for jar in &jars {
+        println!("{}", jar.file_name());
+        // Show what a jar for another loader is, too
+        let mut mods = jar.mods_for(loader);
+        if mods.is_empty() {
+            mods = jar.mods_for(None);
+        }
+        for m in mods {
+            println!(
+                "    {}{} {} ({}{})",
+                if m.bundled { "bundled " } else { "" },
+                m.id,
+                m.version.as_deref().unwrap_or("?"),
+                m.loader.name(),
+                m.minecraft
+                    .as_ref()
+                    .map(|v| format!(", Minecraft {}", v))
+                    .unwrap_or_default()
+            );
+            for dependency in &m.dependencies {
+                println!(
+                    "        {} {} {}",
+                    if dependency.required { "requires" } else { "recommends" },
+                    dependency.id,
+                    dependency.versions
+                );
+            }
+        }
+    }
+    for (path, why) in &unreadable {
+        println!("{}: {}", path.file_name().unwrap_or_default().to_string_lossy(), why);
+    }
+    println!();
+
+    let minecraft = pack.and_then(|p| p.minecraft_version());
+    let diagnoses = mods::check(&jars, loader, minecraft);
+    if diagnoses.is_empty() {
+        println!("Didn't find any problems with the mods.");
+    }
+    for d in &diagnoses {
+        println!("{} {}", d.severity, renderer.render(&d.message));
+    }
+    Ok(Outcome::of(&diagnoses))
+}
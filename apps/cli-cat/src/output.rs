@@ -0,0 +1,260 @@
+use background_cat::{
+    render::{Markdown, Plain, Terminal},
+    Diagnosis, Evidence, Renderer, Report,
+};
+use serde_json::{json, Value};
+
+use crate::{args::Format, report::strip_control};
+
+/// The report of one input of `check`.
+pub(crate) struct Analyzed {
+    pub(crate) input: String,
+    pub(crate) report: Report,
+}
+
+/// Writes `results` in `format`.
+pub(crate) fn write(format: Format, results: &[Analyzed], renderer: &Terminal) -> String {
+    match format {
+        Format::Text => text(results, renderer),
+        Format::Json => {
+            let results: Vec<Value> = results
+                .iter()
+                .map(|r| json!({ "input": r.input, "report": r.report }))
+                .collect();
+            pretty(&Value::Array(results))
+        }
+        Format::Markdown => markdown(results),
+        Format::Sarif => pretty(&sarif(results)),
+    }
+}
+
+fn pretty(value: &Value) -> String {
+    // Serializing a `Value` can't fail
+    serde_json::to_string_pretty(value).unwrap_or_default() + "\n"
+}
+
+fn text(results: &[Analyzed], renderer: &Terminal) -> String {
+    let mut out = String::new();
+    for (i, result) in results.iter().enumerate() {
+        if results.len() > 1 {
+            if i > 0 {
+                out.push('\n');
+            }
+            out.push_str(&format!("==> {} <==\n", result.input));
+        }
+        for d in &result.report.diagnoses {
+            out.push_str(&format!("{} {}\n", d.severity, renderer.render(&d.message)));
+            for evidence in &d.evidence {
+                out.push_str(&format!("    {}: {}\n", location(evidence), strip_control(&evidence.text)));
+            }
+        }
+    }
+    out
+}
+
+/// "line 12", with the input it's from when several were analyzed together.
+fn location(evidence: &Evidence) -> String {
+    match &evidence.source {
+        Some(source) => format!("{} line {}", source, evidence.line),
+        None => format!("line {}", evidence.line),
+    }
+}
+
+/// A section per input, to paste into an issue.
+fn markdown(results: &[Analyzed]) -> String {
+    let mut out = String::new();
+    for result in results {
+        out.push_str("### ");
+        Markdown.code(&mut out, &result.input);
+        out.push_str("\n\n");
+        if result.report.diagnoses.is_empty() {
+            out.push_str("No problems found.\n\n");
+            continue;
+        }
+        for d in &result.report.diagnoses {
+            let message = Markdown.render(&d.message).replace('\n', "\n  ");
+            out.push_str(&format!("- {} {}\n", d.severity, message));
+            for evidence in &d.evidence {
+                out.push_str(&format!("  - {}: ", location(evidence)));
+                Markdown.code(&mut out, evidence.text.trim());
+                out.push('\n');
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// A SARIF 2.1.0 log, with the lines that triggered each finding as its locations.
+fn sarif(results: &[Analyzed]) -> Value {
+    let mut rule_ids: Vec<&str> = results
+        .iter()
+        .flat_map(|r| r.report.diagnoses.iter().map(|d| d.id))
+        .collect();
+    rule_ids.sort_unstable();
+    rule_ids.dedup();
+    let rules: Vec<Value> = rule_ids.iter().map(|id| json!({ "id": id })).collect();
+
+    let findings: Vec<Value> = results
+        .iter()
+        .flat_map(|r| r.report.diagnoses.iter().map(move |d| sarif_result(&r.input, d)))
+        .collect();
+    json!({
+        "version": "2.1.0",
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "background-cat",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/MultiMC/background-cat",
+                    "rules": rules,
+                }
+            },
+            "results": findings,
+        }]
+    })
+}
+
+fn sarif_result(input: &str, d: &Diagnosis) -> Value {
+    let level = match d.severity {
+        "‼" => "error",
+        "❗" => "warning",
+        _ => "note",
+    };
+    let artifact = |source: Option<&str>| json!({ "uri": source.unwrap_or(input) });
+    let mut locations: Vec<Value> = d
+        .evidence
+        .iter()
+        .map(|e| {
+            json!({
+                "physicalLocation": {
+                    "artifactLocation": artifact(e.source.as_deref()),
+                    "region": { "startLine": e.line, "snippet": { "text": e.text } },
+                }
+            })
+        })
+        .collect();
+    if locations.is_empty() {
+        locations.push(json!({ "physicalLocation": { "artifactLocation": artifact(None) } }));
+    }
+    json!({
+        "ruleId": d.id,
+        "level": level,
+        "message": { "text": Plain.render(&d.message) },
+        "locations": locations,
+        "properties": { "ruleSet": d.rule_set, "confidence": d.confidence },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use background_cat::{analyze, RuleSet};
+
+    use super::*;
+
+    const LOG: &str = "MultiMC version: 0.7.0-3275\n\n\
+        Minecraft folder is:\nC:/Program Files/MultiMC/instances/1.12.2/.minecraft\n\n\n\
+        Java is version 1.8.0_372, using 64-bit architecture.\n\n\
+        [12:00:05] [main/FATAL]: java.lang.OutOfMemoryError: Java heap space\n";
+
+    /// The analyzed log, and a finding without evidence added to it.
+    fn results() -> Vec<Analyzed> {
+        let mut report = analyze(LOG);
+        report.diagnoses.push(Diagnosis {
+            id: "exit-code",
+            rule_set: RuleSet::Mistakes,
+            severity: "ℹ",
+            message: "The game exited with code 1.".to_string(),
+            confidence: 0.3,
+            blocking: false,
+            evidence: Vec::new(),
+            actions: Vec::new(),
+        });
+        vec![Analyzed {
+            input: "MultiMC.log".to_string(),
+            report,
+        }]
+    }
+
+    fn parse(format: Format) -> Value {
+        serde_json::from_str(&write(format, &results(), &Terminal { color: false })).unwrap()
+    }
+
+    #[test]
+    fn writes_json_with_stable_field_names() {
+        let json = parse(Format::Json);
+        let result = &json[0];
+        assert_eq!(result["input"], "MultiMC.log");
+        let report = &result["report"];
+        for field in ["classification", "launcher", "support", "facts", "diagnoses", "elapsed"] {
+            assert!(report.get(field).is_some(), "no `{}` in the report", field);
+        }
+        assert_eq!(report["classification"]["kind"], "launcher-log");
+        assert_eq!(report["launcher"]["name"], "MultiMC");
+        assert_eq!(report["facts"]["java_version"], "1.8.0_372");
+
+        let diagnosis = &report["diagnoses"][0];
+        assert_eq!(diagnosis["id"], "program-files");
+        for field in ["rule_set", "severity", "message", "confidence", "blocking", "evidence", "actions"] {
+            assert!(diagnosis.get(field).is_some(), "no `{}` in the diagnosis", field);
+        }
+        assert_eq!(diagnosis["rule_set"], "mistakes");
+        assert_eq!(
+            diagnosis["evidence"][0],
+            json!({ "source": null, "line": 4, "text": "C:/Program Files/MultiMC/instances/1.12.2/.minecraft" })
+        );
+        assert_eq!(diagnosis["actions"][0], json!({ "action": "move-install" }));
+    }
+
+    #[test]
+    fn writes_sarif_2_1_0() {
+        let sarif = parse(Format::Sarif);
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(sarif["$schema"], "https://json.schemastore.org/sarif-2.1.0.json");
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "background-cat");
+        let rules: Vec<&str> = run["tool"]["driver"]["rules"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(rules, ["exit-code", "out-of-memory", "program-files"]);
+
+        let results = run["results"].as_array().unwrap();
+        let ids: Vec<&str> = results.iter().map(|r| r["ruleId"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["program-files", "out-of-memory", "exit-code"]);
+
+        let out_of_memory = &results[1];
+        assert_eq!(out_of_memory["level"], "error");
+        assert!(out_of_memory["message"]["text"].as_str().unwrap().contains("out of memory"));
+        let location = &out_of_memory["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "MultiMC.log");
+        assert_eq!(location["region"]["startLine"], 9);
+        assert_eq!(
+            location["region"]["snippet"]["text"],
+            "[12:00:05] [main/FATAL]: java.lang.OutOfMemoryError: Java heap space"
+        );
+
+        // Findings without evidence are about the whole input
+        let exit_code = &results[2];
+        assert_eq!(exit_code["level"], "note");
+        assert_eq!(
+            exit_code["locations"],
+            json!([{ "physicalLocation": { "artifactLocation": { "uri": "MultiMC.log" } } }])
+        );
+    }
+
+    #[test]
+    fn writes_text_and_markdown() {
+        let results = results();
+        let text = write(Format::Text, &results, &Terminal { color: false });
+        assert!(text.contains("\n    line 9: [12:00:05] [main/FATAL]: java.lang.OutOfMemoryError: Java heap space\n"));
+        assert!(!text.contains("==>"));
+
+        let markdown = write(Format::Markdown, &results, &Terminal { color: false });
+        assert!(markdown.starts_with("### `MultiMC.log`\n\n- ‼ "));
+        assert!(markdown.contains("\n  - line 4: `C:/Program Files/MultiMC/instances/1.12.2/.minecraft`\n"));
+    }
+}
@@ -0,0 +1,355 @@
+use std::{borrow::Cow, collections::BTreeSet, error::Error, fs, path::Path};
+
+use background_cat::{
+    input,
+    render::{escape_html, Html, Terminal},
+    Analyzer, Diagnosis, Renderer,
+};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{args::Input, Outcome};
+
+/// Shows the log of `input` with the lines that triggered a finding highlighted and the
+/// findings on top. Writes a self-contained HTML page to `output` if given, and prints the log
+/// to the terminal otherwise.
+pub(crate) fn run(input: &Input, output: Option<&Path>, renderer: &Terminal) -> Result<Outcome, Box<dyn Error>> {
+    let bytes = crate::read(input).map_err(|why| format!("Couldn't read {}: {}", input, why))?;
+    let log = input::decode(&bytes);
+    let report = Analyzer::new().analyze(&log);
+    let lines: Vec<&str> = log.lines().collect();
+    let hits: BTreeSet<usize> = report
+        .diagnoses
+        .iter()
+        .flat_map(|d| &d.evidence)
+        .filter(|e| e.source.is_none())
+        .map(|e| e.line)
+        .collect();
+    let sections = sections(&lines);
+
+    match output {
+        Some(path) => {
+            let page = html(&input.to_string(), &lines, &report.diagnoses, &hits, &sections);
+            if path == Path::new("-") {
+                print!("{}", page);
+            } else {
+                fs::write(path, page).map_err(|why| format!("Couldn't write {}: {}", path.display(), why))?;
+            }
+        }
+        None => print!("{}", terminal(&lines, &report.diagnoses, &hits, &sections, renderer)),
+    }
+    Ok(Outcome::of(&report.diagnoses))
+}
+
+/// A list in the log, like the libraries or mods, that can be folded away. Lines are 1-based
+/// and `header` is the line introducing the list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Section {
+    header: usize,
+    last: usize,
+}
+
+impl Section {
+    fn contains(&self, line: usize) -> bool {
+        self.header < line && line <= self.last
+    }
+}
+
+/// Lists shorter than this aren't worth folding.
+const MIN_SECTION_LINES: usize = 3;
+
+/// Lists of the launcher log ("Libraries:", "Mods:", ...), Fabric ("Loading 75 mods:") and
+/// crash reports ("Mod List:"), whose items are indented.
+fn sections(lines: &[&str]) -> Vec<Section> {
+    lazy_static! {
+        static ref HEADER: Regex = Regex::new(
+            r"^(Libraries|Native libraries|Mods|Core Mods|Jar Mods|Params|Class Path):\s*$|Loading \d+ mods:\s*$|^\s*Mod List:\s*$"
+        )
+        .unwrap();
+    }
+    let mut sections = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if !HEADER.is_match(lines[i]) {
+            i += 1;
+            continue;
+        }
+        let items = lines[i + 1..]
+            .iter()
+            .take_while(|l| l.starts_with([' ', '\t']) && !l.trim().is_empty())
+            .count();
+        if items >= MIN_SECTION_LINES {
+            sections.push(Section {
+                header: i + 1,
+                last: i + 1 + items,
+            });
+        }
+        i += items + 1;
+    }
+    sections
+}
+
+const STYLE: &str = "
+body { margin: 0; font-family: sans-serif; background: #fafafa; color: #222; }
+header { position: sticky; top: 0; max-height: 40vh; overflow-y: auto; padding: 0.5em 1em;
+    background: #fff; border-bottom: 1px solid #ccc; box-shadow: 0 2px 4px rgba(0, 0, 0, 0.1); }
+header h1 { font-size: 1.1em; margin: 0.3em 0; }
+header ul { margin: 0; padding-left: 1.2em; }
+header li { margin: 0.3em 0; }
+.lines a { color: #0366d6; }
+.log { font-family: monospace; font-size: 0.85em; padding: 0.5em 0; }
+.line { white-space: pre-wrap; word-break: break-all; padding: 0 1em 0 0; }
+.line .n { display: inline-block; width: 5em; padding-right: 1em; text-align: right; color: #999;
+    text-decoration: none; user-select: none; }
+.line.hit { background: #fff3b0; }
+.line:target { background: #ffd966; }
+details > summary { cursor: pointer; list-style: none; }
+details > summary::before { content: '\\25b8 '; color: #999; }
+details[open] > summary::before { content: '\\25be '; }
+";
+
+fn html(name: &str, lines: &[&str], diagnoses: &[Diagnosis], hits: &BTreeSet<usize>, sections: &[Section]) -> String {
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str(&format!("<title>{}</title>\n", escape_html(name)));
+    out.push_str(&format!("<style>{}</style>\n</head>\n<body>\n<header>\n", STYLE));
+    out.push_str(&format!("<h1>{}</h1>\n", escape_html(name)));
+    if diagnoses.is_empty() {
+        out.push_str("<p>No problems found.</p>\n");
+    } else {
+        out.push_str("<ul>\n");
+        for d in diagnoses {
+            out.push_str(&format!("<li>{} {}", escape_html(d.severity), Html.render(&d.message)));
+            let links: Vec<String> = d
+                .evidence
+                .iter()
+                .filter(|e| e.source.is_none())
+                .map(|e| format!("<a href=\"#L{0}\">line {0}</a>", e.line))
+                .collect();
+            if !links.is_empty() {
+                out.push_str(&format!("<div class=\"lines\">{}</div>", links.join(", ")));
+            }
+            out.push_str("</li>\n");
+        }
+        out.push_str("</ul>\n");
+    }
+    out.push_str("</header>\n<div class=\"log\">\n");
+
+    let line = |out: &mut String, n: usize| {
+        let class = if hits.contains(&n) { "line hit" } else { "line" };
+        out.push_str(&format!(
+            "<div class=\"{}\" id=\"L{1}\"><a class=\"n\" href=\"#L{1}\">{1}</a>{2}</div>\n",
+            class,
+            n,
+            escape_html(lines[n - 1])
+        ));
+    };
+    let mut n = 1;
+    while n <= lines.len() {
+        match sections.iter().find(|s| s.header == n) {
+            Some(section) => {
+                // Lists with a highlighted line start out unfolded
+                let open = if hits.range(section.header + 1..=section.last).next().is_some() {
+                    " open"
+                } else {
+                    ""
+                };
+                out.push_str(&format!("<details{}>\n<summary>", open));
+                line(&mut out, n);
+                out.push_str("</summary>\n");
+                for item in section.header + 1..=section.last {
+                    line(&mut out, item);
+                }
+                out.push_str("</details>\n");
+                n = section.last + 1;
+            }
+            None => {
+                line(&mut out, n);
+                n += 1;
+            }
+        }
+    }
+    out.push_str("</div>\n</body>\n</html>\n");
+    out
+}
+
+/// `text` without ANSI escape sequences and other control characters except tabs, so a log
+/// can't change colors, move the cursor or set the title of the terminal it's printed to.
+pub(crate) fn strip_control(text: &str) -> Cow<'_, str> {
+    lazy_static! {
+        // CSI sequences like colors, and OSC sequences like titles and links
+        static ref ESCAPES: Regex = Regex::new(r"\x1b\[[0-?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)?").unwrap();
+    }
+    if !text.chars().any(|c| c.is_control() && c != '\t') {
+        return Cow::Borrowed(text);
+    }
+    let text = ESCAPES.replace_all(text, "");
+    Cow::Owned(text.chars().filter(|&c| !c.is_control() || c == '\t').collect())
+}
+
+/// The log for a terminal: the findings first, then the log with line numbers, highlighted
+/// lines and the lists folded to one line unless they have a highlighted line.
+fn terminal(
+    lines: &[&str],
+    diagnoses: &[Diagnosis],
+    hits: &BTreeSet<usize>,
+    sections: &[Section],
+    renderer: &Terminal,
+) -> String {
+    let mut out = String::new();
+    for d in diagnoses {
+        out.push_str(&format!("{} {}\n", d.severity, renderer.render(&d.message)));
+        let at: Vec<String> = d
+            .evidence
+            .iter()
+            .filter(|e| e.source.is_none())
+            .map(|e| e.line.to_string())
+            .collect();
+        if !at.is_empty() {
+            out.push_str(&format!("    see line {}\n", at.join(", ")));
+        }
+    }
+    if !diagnoses.is_empty() {
+        out.push('\n');
+    }
+
+    let width = lines.len().to_string().len();
+    let mut n = 1;
+    while n <= lines.len() {
+        let hit = hits.contains(&n);
+        let number = format!("{:>width$}", n, width = width);
+        let text = strip_control(lines[n - 1]);
+        match (renderer.color, hit) {
+            (true, true) => out.push_str(&format!("\x1b[1;33m{} ▌\x1b[0m \x1b[43;30m{}\x1b[0m\n", number, text)),
+            (true, false) => out.push_str(&format!("\x1b[2m{} │\x1b[0m {}\n", number, text)),
+            (false, true) => out.push_str(&format!("{} > {}\n", number, text)),
+            (false, false) => out.push_str(&format!("{} | {}\n", number, text)),
+        }
+
+        let folded = sections
+            .iter()
+            .find(|s| s.header == n && !hits.iter().any(|&h| s.contains(h)));
+        match folded {
+            Some(section) => {
+                let hidden = section.last - section.header;
+                let note = format!("{:>width$} … {} lines folded", "", hidden, width = width);
+                if renderer.color {
+                    out.push_str(&format!("\x1b[2m{}\x1b[0m\n", note));
+                } else {
+                    out.push_str(&format!("{}\n", note));
+                }
+                n = section.last + 1;
+            }
+            None => n += 1,
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use background_cat::{Evidence, RuleSet};
+
+    use super::*;
+
+    const LOG: &str = "\
+MultiMC version: 0.7.0-3275
+
+Libraries:
+  /MultiMC/libraries/com/mojang/patchy/1.3.9/patchy-1.3.9.jar
+  /MultiMC/libraries/oshi-project/oshi-core/1.1/oshi-core-1.1.jar
+  /MultiMC/libraries/net/java/jinput/jinput/2.0.5/jinput-2.0.5.jar
+  /MultiMC/libraries/net/java/jutils/jutils/1.0.0/jutils-1.0.0.jar
+
+Mods:
+  [✔️]OptiFine_1.12.2_HD_U_G5.jar
+  [✔️]jei_1.12.2-4.16.1.301.jar
+
+Params:
+  --username <PROFILE NAME> --version 1.12.2
+
+[12:00:00] [main/INFO]: Loading 3 mods:
+\t- fabric-api 0.86.1
+\t- fabricloader 0.14.21
+\t- minecraft 1.20.1
+[12:00:05] [main/ERROR]: Crashed
+";
+
+    fn lines() -> Vec<&'static str> {
+        LOG.lines().collect()
+    }
+
+    fn diagnosis(line: usize) -> Diagnosis {
+        Diagnosis {
+            id: "test",
+            rule_set: RuleSet::Mistakes,
+            severity: "❗",
+            message: "Something's wrong.".to_string(),
+            confidence: 1.0,
+            blocking: false,
+            evidence: vec![Evidence {
+                source: None,
+                line,
+                text: lines()[line - 1].to_string(),
+            }],
+            actions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn finds_lists_long_enough_to_fold() {
+        assert_eq!(
+            sections(&lines()),
+            [Section { header: 3, last: 7 }, Section { header: 16, last: 19 }]
+        );
+        // Crash reports indent the header of their mod list
+        let crash_report = ["-- System Details --", "\tMod List: ", "\t\ta.jar", "\t\tb.jar", "\t\tc.jar"];
+        assert_eq!(sections(&crash_report), [Section { header: 2, last: 5 }]);
+        assert_eq!(sections(&["Libraries:", "", "  a.jar", "  b.jar", "  c.jar"]), []);
+    }
+
+    #[test]
+    fn folds_lists_without_highlighted_lines() {
+        let lines = lines();
+        let sections = sections(&lines);
+        let renderer = Terminal { color: false };
+        let diagnoses = [diagnosis(20)];
+        let hits = BTreeSet::from([20]);
+        let out = terminal(&lines, &diagnoses, &hits, &sections, &renderer);
+        assert!(out.starts_with("❗ Something's wrong.\n    see line 20\n\n"));
+        assert!(out.contains("\n 3 | Libraries:\n   … 4 lines folded\n 8 | \n"));
+        assert!(out.contains("\n16 | [12:00:00] [main/INFO]: Loading 3 mods:\n   … 3 lines folded\n20 > "));
+        // Too short to fold
+        assert!(out.contains("\n10 |   [✔️]OptiFine_1.12.2_HD_U_G5.jar\n"));
+
+        let hits = BTreeSet::from([5]);
+        let out = terminal(&lines, &[diagnosis(5)], &hits, &sections, &renderer);
+        assert!(out.contains("\n 3 | Libraries:\n 4 |   /MultiMC"));
+        assert!(out.contains("\n 5 >   /MultiMC/libraries/oshi-project"));
+        assert!(out.contains("… 3 lines folded"));
+    }
+
+    #[test]
+    fn opens_lists_with_highlighted_lines_in_html() {
+        let lines = lines();
+        let sections = sections(&lines);
+        let page = html("latest.log", &lines, &[diagnosis(5)], &BTreeSet::from([5]), &sections);
+        assert_eq!(page.matches("<details open>").count(), 1);
+        assert_eq!(page.matches("<details>").count(), 1);
+        assert!(page.contains("<div class=\"line hit\" id=\"L5\">"));
+        assert!(page.contains("<a href=\"#L5\">line 5</a>"));
+    }
+
+    #[test]
+    fn strips_control_characters_from_log_lines() {
+        assert_eq!(strip_control("[12:00:00] \tplain"), "[12:00:00] \tplain");
+        assert_eq!(strip_control("\x1b[31mred\x1b[0m text"), "red text");
+        assert_eq!(strip_control("\x1b]0;new title\x07after"), "after");
+        assert_eq!(strip_control("\x1b]8;;https://x.org\x1b\\link\x1b]8;;\x1b\\"), "link");
+        assert_eq!(strip_control("bell\x07 and\r back\x08space"), "bell and backspace");
+
+        let lines = ["\x1b[2J\x1b[Hcleared", "fine"];
+        let out = terminal(&lines, &[], &BTreeSet::new(), &[], &Terminal { color: false });
+        assert_eq!(out, "1 | cleared\n2 | fine\n");
+    }
+}
@@ -0,0 +1,155 @@
+use std::{error::Error, fs, path::Path};
+
+use background_cat::{input, instance::Instance, root::LauncherRoot, Analyzer, Diagnosis, Renderer};
+
+use crate::{report::strip_control, Outcome};
+
+/// What was found in one instance.
+struct Summary {
+    name: String,
+    minecraft: String,
+    loader: String,
+    logs: Vec<String>,
+    diagnoses: Vec<Diagnosis>,
+}
+
+/// Analyzes every instance of the MultiMC folder `dir`: the files of the instance and its
+/// newest log and crash report. Prints a table of the instances, the problems found in each,
+/// and the problems with the launcher itself.
+pub(crate) fn run(dir: &Path, renderer: &impl Renderer) -> Result<Outcome, Box<dyn Error>> {
+    let root = LauncherRoot::open(dir)?;
+    let analyzer = Analyzer::new();
+
+    let mut summaries = Vec::new();
+    for instance_dir in root.instances()? {
+        // One broken instance shouldn't stop the others from being scanned
+        match summarize(&instance_dir, &analyzer) {
+            Ok(summary) => summaries.push(summary),
+            Err(why) => eprintln!("Skipping {}: {}", instance_dir.display(), why),
+        }
+    }
+
+    let mut rows = vec![["Instance", "Minecraft", "Loader", "Logs", "Problems"].map(str::to_string)];
+    rows.extend(summaries.iter().map(|s| {
+        [
+            s.name.clone(),
+            s.minecraft.clone(),
+            s.loader.clone(),
+            s.logs.join(", "),
+            s.diagnoses.len().to_string(),
+        ]
+    }));
+    print_table(&rows);
+
+    for summary in summaries.iter().filter(|s| !s.diagnoses.is_empty()) {
+        println!();
+        println!("{}:", summary.name);
+        for d in &summary.diagnoses {
+            println!("{} {}", d.severity, renderer.render(&d.message));
+        }
+    }
+
+    let launcher = root.inspect()?;
+    println!();
+    if launcher.is_empty() {
+        println!("Didn't find any problems with the launcher.");
+    } else {
+        println!("Launcher:");
+    }
+    for d in &launcher {
+        println!("{} {}", d.severity, renderer.render(&d.message));
+        for evidence in &d.evidence {
+            println!(
+                "    {} line {}: {}",
+                evidence.source.as_deref().unwrap_or("input"),
+                evidence.line,
+                strip_control(&evidence.text)
+            );
+        }
+    }
+    let outcome = summaries.iter().map(|s| Outcome::of(&s.diagnoses)).max().unwrap_or(Outcome::Clean);
+    Ok(outcome.max(Outcome::of(&launcher)))
+}
+
+/// Prints `rows` with the columns lined up.
+fn print_table(rows: &[[String; 5]]) {
+    let mut widths = [0; 5];
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        println!("{}", cells.join("  ").trim_end());
+    }
+}
+
+fn summarize(dir: &Path, analyzer: &Analyzer) -> Result<Summary, Box<dyn Error>> {
+    let instance = Instance::open(dir)?;
+    let mut diagnoses = instance.inspect()?;
+    let mut logs = Vec::new();
+    for path in [instance.newest_log()?, instance.newest_crash_report()?].into_iter().flatten() {
+        let bytes = fs::read(&path)?;
+        let report = analyzer.analyze(&input::decode(&bytes));
+        for d in report.diagnoses {
+            if !diagnoses.iter().any(|known| known.id == d.id) {
+                diagnoses.push(d);
+            }
+        }
+        logs.push(path.file_name().unwrap_or_default().to_string_lossy().into_owned());
+    }
+
+    let pack = instance.pack.as_ref();
+    Ok(Summary {
+        name: instance.name(),
+        minecraft: pack.and_then(|p| p.minecraft_version()).unwrap_or("?").to_string(),
+        loader: pack.and_then(|p| p.loader()).map_or("-", |l| l.name()).to_string(),
+        logs,
+        diagnoses,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn summarizes_an_instance_with_its_newest_log() {
+        let dir = TempDir::new().unwrap();
+        let instance = dir.path().join("instances/Modded");
+        fs::create_dir_all(instance.join(".minecraft/logs")).unwrap();
+        fs::write(instance.join("instance.cfg"), "name=Modded Pack\n").unwrap();
+        fs::write(
+            instance.join("mmc-pack.json"),
+            r#"{"components": [
+                {"uid": "net.minecraft", "version": "1.12.2"},
+                {"uid": "net.minecraftforge", "version": "14.23.5.2859"}
+            ]}"#,
+        )
+        .unwrap();
+        fs::write(
+            instance.join(".minecraft/logs/latest.log"),
+            "[12:00:00] [main/INFO]: Loading\n\
+             [12:00:05] [main/FATAL]: java.lang.OutOfMemoryError: Java heap space\n",
+        )
+        .unwrap();
+
+        let root = LauncherRoot::open(dir.path()).unwrap();
+        let instances = root.instances().unwrap();
+        assert_eq!(instances, [instance]);
+        let summary = summarize(&instances[0], &Analyzer::new()).unwrap();
+        assert_eq!(summary.name, "Modded Pack");
+        assert_eq!(summary.minecraft, "1.12.2");
+        assert_eq!(summary.loader, "Forge");
+        assert_eq!(summary.logs, ["latest.log"]);
+        let ids: Vec<&str> = summary.diagnoses.iter().map(|d| d.id).collect();
+        assert_eq!(ids, ["out-of-memory"]);
+    }
+}
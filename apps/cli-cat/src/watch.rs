@@ -0,0 +1,230 @@
+use std::{
+    error::Error,
+    fs::{self, File, Metadata},
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use background_cat::{instance::Instance, render::Terminal, Diagnosis, IncrementalAnalyzer, Renderer};
+
+use crate::{report::strip_control, Outcome};
+
+/// How often the log is checked for new lines.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Follows the log at `path`, or the `latest.log` of the instance at `path`, and prints what
+/// the rules find as soon as the lines triggering them are written. Runs until interrupted.
+///
+/// `latest.log` only has what the game writes, so the checks for what the launcher prints
+/// before starting it, like "Using system GLFW", only run when following the launcher's output.
+pub(crate) fn run(path: &Path, renderer: &Terminal) -> Result<Outcome, Box<dyn Error>> {
+    let log = log_path(path)?;
+    eprintln!("Watching {}, press Ctrl+C to stop", log.display());
+
+    let mut analyzer = IncrementalAnalyzer::default();
+    let mut follower = Follower::new(log.clone());
+    loop {
+        match follower.poll().map_err(|why| format!("Couldn't read {}: {}", log.display(), why))? {
+            Poll::Lines(lines) => print(&analyzer.push_lines(lines.lines()), renderer),
+            Poll::Restarted => {
+                // What only shows up when looking at the whole log
+                print(&std::mem::take(&mut analyzer).finish(), renderer);
+                eprintln!("{} was started over, watching the new log", log.display());
+            }
+            Poll::Waiting => thread::sleep(POLL_INTERVAL),
+        }
+    }
+}
+
+fn print(diagnoses: &[Diagnosis], renderer: &Terminal) {
+    for d in diagnoses {
+        println!("{} {}", d.severity, renderer.render(&d.message));
+        for evidence in &d.evidence {
+            println!("    line {}: {}", evidence.line, strip_control(&evidence.text));
+        }
+    }
+}
+
+/// The log to follow: `path` itself, or the `latest.log` of the instance in it.
+fn log_path(path: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    if !path.is_dir() {
+        return Ok(path.to_path_buf());
+    }
+    let instance = Instance::open(path)
+        .map_err(|why| format!("Couldn't open instance {}: {}", path.display(), why))?;
+    Ok(instance.latest_log_path())
+}
+
+#[derive(Debug, PartialEq)]
+enum Poll {
+    /// Complete lines written since the last poll.
+    Lines(String),
+    /// The file was truncated or replaced, e.g. when the game rotated `latest.log` on launch.
+    Restarted,
+    /// Nothing new, or the file doesn't exist (yet).
+    Waiting,
+}
+
+/// Reads what's appended to a file, a line at a time.
+struct Follower {
+    path: PathBuf,
+    position: u64,
+    identity: Option<Identity>,
+    /// The start of a line that hasn't been written completely yet.
+    partial: Vec<u8>,
+}
+
+impl Follower {
+    fn new(path: PathBuf) -> Self {
+        Follower {
+            path,
+            position: 0,
+            identity: None,
+            partial: Vec::new(),
+        }
+    }
+
+    fn poll(&mut self) -> io::Result<Poll> {
+        let metadata = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            // Between the game moving the old log away and creating the new one
+            Err(why) if why.kind() == io::ErrorKind::NotFound => return Ok(Poll::Waiting),
+            Err(why) => return Err(why),
+        };
+        let identity = identity(&metadata);
+        let replaced = self.identity.is_some() && identity != self.identity;
+        self.identity = identity;
+        if replaced || metadata.len() < self.position {
+            self.position = 0;
+            self.partial.clear();
+            return Ok(Poll::Restarted);
+        }
+        if metadata.len() == self.position {
+            return Ok(Poll::Waiting);
+        }
+
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.position))?;
+        let read = file.read_to_end(&mut self.partial)?;
+        self.position += read as u64;
+        match self.partial.iter().rposition(|&b| b == b'\n') {
+            Some(end) => {
+                let lines: Vec<u8> = self.partial.drain(..=end).collect();
+                Ok(Poll::Lines(String::from_utf8_lossy(&lines).into_owned()))
+            }
+            None => Ok(Poll::Waiting),
+        }
+    }
+}
+
+/// Tells files apart, to notice when the log was replaced by a new file with the same name.
+#[cfg(unix)]
+type Identity = (u64, u64);
+#[cfg(not(unix))]
+type Identity = std::time::SystemTime;
+
+#[cfg(unix)]
+fn identity(metadata: &Metadata) -> Option<Identity> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn identity(metadata: &Metadata) -> Option<Identity> {
+    metadata.created().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use background_cat::ResponseId;
+    use std::io::Write;
+
+    fn append(path: &Path, text: &str) {
+        let mut file = fs::OpenOptions::new().append(true).create(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    fn found(lines: &[&str]) -> Vec<&'static str> {
+        let mut analyzer = IncrementalAnalyzer::default();
+        analyzer.push_lines(lines.iter().copied()).into_iter().map(|d| d.id).collect()
+    }
+
+    #[test]
+    fn waits_for_lines_to_be_complete() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("latest.log");
+        let mut follower = Follower::new(log.clone());
+        assert_eq!(follower.poll().unwrap(), Poll::Waiting);
+
+        append(&log, "[12:00:00] first\n[12:00:01] sec");
+        assert_eq!(follower.poll().unwrap(), Poll::Lines("[12:00:00] first\n".to_string()));
+        assert_eq!(follower.poll().unwrap(), Poll::Waiting);
+        append(&log, "ond");
+        assert_eq!(follower.poll().unwrap(), Poll::Waiting);
+        append(&log, "\n");
+        assert_eq!(follower.poll().unwrap(), Poll::Lines("[12:00:01] second\n".to_string()));
+    }
+
+    #[test]
+    fn starts_over_when_the_log_is_truncated() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("latest.log");
+        append(&log, "[12:00:00] an old session\n[12:00:01] that ran a while\n");
+        let mut follower = Follower::new(log.clone());
+        assert!(matches!(follower.poll().unwrap(), Poll::Lines(_)));
+
+        fs::write(&log, "[13:00:00] new\n").unwrap();
+        assert_eq!(follower.poll().unwrap(), Poll::Restarted);
+        assert_eq!(follower.poll().unwrap(), Poll::Lines("[13:00:00] new\n".to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn starts_over_when_the_log_is_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("latest.log");
+        append(&log, "[12:00:00] old\n");
+        let mut follower = Follower::new(log.clone());
+        assert!(matches!(follower.poll().unwrap(), Poll::Lines(_)));
+
+        // Like the game moving the old log away on launch, the new one being longer already
+        fs::rename(&log, dir.path().join("2021-06-01-1.log")).unwrap();
+        assert_eq!(follower.poll().unwrap(), Poll::Waiting);
+        append(&log, "[13:00:00] a new session\n");
+        assert_eq!(follower.poll().unwrap(), Poll::Restarted);
+        assert_eq!(follower.poll().unwrap(), Poll::Lines("[13:00:00] a new session\n".to_string()));
+    }
+
+    #[test]
+    fn finds_problems_as_lines_come_in() {
+        let game = [
+            "[12:00:00] [main/INFO]: Setting user: Steve",
+            "[12:00:01] [Render thread/INFO]: Backend library: LWJGL version 3.2.2 build 10",
+            "java.lang.OutOfMemoryError: Java heap space",
+        ];
+        assert_eq!(found(&game), [ResponseId::OutOfMemory.as_str()]);
+
+        let launcher = [
+            "MultiMC version: 0.7.0-3275",
+            "",
+            "Minecraft folder is:",
+            "/home/steve/MultiMC/instances/1.16.5/.minecraft",
+            "",
+            "Java path is:",
+            "/usr/lib/jvm/java-8-openjdk/jre/bin/java",
+            "",
+            "Java is version 1.8.0_292, using 64-bit architecture.",
+            "",
+            "Main Class:",
+            "  net.minecraft.client.main.Main",
+            "",
+            "Using system GLFW.",
+        ];
+        assert!(found(&launcher).contains(&ResponseId::UsingSystemGlfw.as_str()));
+        // Only the launcher prints it, before the game's own log starts
+        assert!(!found(&[game[0], "Using system GLFW."]).contains(&ResponseId::UsingSystemGlfw.as_str()));
+    }
+}
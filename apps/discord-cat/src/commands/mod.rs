@@ -11,6 +11,8 @@ use serenity::{
 use log::{debug, error};
 use serde::Deserialize;
 
+mod policy;
+use policy::POLICY_COMMAND;
 mod xkcd;
 use xkcd::XKCD_COMMAND;
 
@@ -141,6 +143,10 @@ static_image_command! {
 #[commands(info)]
 struct Other;
 
+#[group]
+#[commands(policy)]
+struct Config;
+
 #[command]
 async fn info(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
     let creator_name = match UserId::from(185_461_862_878_543_872).to_user(ctx).await {
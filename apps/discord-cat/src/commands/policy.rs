@@ -0,0 +1,144 @@
+use serenity::{
+    framework::standard::{macros::command, Args, CommandResult},
+    model::channel::Message,
+    prelude::*,
+    utils::Colour,
+};
+
+use background_cat::{LauncherPolicy, Support, VersionRange};
+use log::error;
+
+use crate::policy::GuildPolicies;
+
+async fn reply(ctx: &Context, msg: &Message, title: &str, description: String) {
+    if let Err(why) = msg
+        .channel_id
+        .send_message(&ctx, |m| {
+            m.embed(|e| {
+                e.title(title);
+                e.colour(Colour::DARK_TEAL);
+                e.description(description)
+            })
+        })
+        .await
+    {
+        error!("Couldn't send policy message: {}", why);
+    }
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions("MANAGE_GUILD")]
+#[sub_commands(set, remove, reset)]
+#[description = "Show how logs from MultiMC forks and versions are treated on this server."]
+async fn policy(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
+    let policy = {
+        let data = ctx.data.read().await;
+        let policies = data.get::<GuildPolicies>().expect("guild policies are set up in main");
+        let policies = policies.read().await;
+        policies.policy_for(msg.guild_id)
+    };
+    let mut description = format!("Other launchers: **{}**\n", policy.default_support());
+    for entry in policy.entries() {
+        description += &format!(
+            "{} `{}`: **{}**\n",
+            entry.launcher, entry.versions, entry.support
+        );
+    }
+    description += "\nLater entries take precedence.";
+    reply(ctx, msg, "Launcher policy", description).await;
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions("MANAGE_GUILD")]
+#[description = "Set how logs from a launcher are treated: `supported`, `warn` or `refuse`."]
+#[usage = "\"<launcher>\" <supported|warn|refuse> [versions]"]
+#[example = "\"Prism Launcher\" warn >=7.0"]
+async fn set(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let launcher = args.quoted().single::<String>()?;
+    let support = args.single::<Support>()?;
+    let versions = if args.is_empty() {
+        VersionRange::any()
+    } else {
+        args.rest().parse::<VersionRange>()?
+    };
+    let entry = LauncherPolicy {
+        launcher,
+        versions,
+        support,
+    };
+
+    {
+        let data = ctx.data.read().await;
+        let policies = data.get::<GuildPolicies>().expect("guild policies are set up in main");
+        let mut policies = policies.write().await;
+        policies.add(msg.guild_id.expect("only in guilds"), &entry)?;
+    }
+
+    reply(
+        ctx,
+        msg,
+        "Launcher policy updated",
+        format!(
+            "{} `{}`: **{}**",
+            entry.launcher, entry.versions, entry.support
+        ),
+    )
+    .await;
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions("MANAGE_GUILD")]
+#[description = "Remove the entries for a launcher, or only the one for some of its versions."]
+#[usage = "\"<launcher>\" [versions]"]
+#[example = "\"Prism Launcher\" >=7.0"]
+async fn remove(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let launcher = args.quoted().single::<String>()?;
+    let versions = if args.is_empty() {
+        None
+    } else {
+        Some(args.rest().parse::<VersionRange>()?)
+    };
+
+    let removed = {
+        let data = ctx.data.read().await;
+        let policies = data.get::<GuildPolicies>().expect("guild policies are set up in main");
+        let mut policies = policies.write().await;
+        policies.remove(msg.guild_id.expect("only in guilds"), &launcher, versions.as_ref())?
+    };
+
+    let description = match (removed, &versions) {
+        (0, _) => format!("There was no entry for {} to remove.", launcher),
+        (_, Some(versions)) => format!("Removed the entry for {} `{}`.", launcher, versions),
+        (1, None) => format!("Removed the entry for {}.", launcher),
+        (n, None) => format!("Removed {} entries for {}.", n, launcher),
+    };
+    reply(ctx, msg, "Launcher policy updated", description).await;
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions("MANAGE_GUILD")]
+#[aliases("clear")]
+#[description = "Go back to the default launcher policy."]
+async fn reset(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
+    {
+        let data = ctx.data.read().await;
+        let policies = data.get::<GuildPolicies>().expect("guild policies are set up in main");
+        let mut policies = policies.write().await;
+        policies.reset(msg.guild_id.expect("only in guilds"))?;
+    }
+    reply(
+        ctx,
+        msg,
+        "Launcher policy reset",
+        "Only MultiMC is supported again.".to_string(),
+    )
+    .await;
+    Ok(())
+}
@@ -1,9 +1,6 @@
 use lazy_static::lazy_static;
 use log::{debug, error, info};
-use regex::Regex;
-use reqwest::get;
-use std::{collections::HashSet, env, time::Duration};
-use futures::StreamExt;
+use std::{collections::HashSet, env, sync::Arc};
 
 use serenity::{
     async_trait,
@@ -15,23 +12,32 @@ use serenity::{
         channel::Message,
         gateway::Ready,
         id::{ChannelId, UserId},
-        user::User,
-        interactions::InteractionResponseType,
     },
     prelude::*,
     utils::Colour,
-    builder::CreateButton,
 };
 
-use background_cat::common_mistakes;
-use background_cat::common_origins;
+use paste_cat::{FetchLimits, Fetcher, Providers};
+
+use background_cat::{
+    classify,
+    input::{self, Limits},
+    render::Discord,
+    Diagnosis, LogKind, OriginPolicy, Renderer, Session,
+};
 
 mod commands;
-use commands::{FUN_GROUP, OTHER_GROUP, STATICIMAGE_GROUP, STATICTEXT_GROUP};
+use commands::{CONFIG_GROUP, FUN_GROUP, OTHER_GROUP, STATICIMAGE_GROUP, STATICTEXT_GROUP};
+
+mod policy;
+use policy::GuildPolicies;
 
 mod hook;
 use hook::after_hook;
 
+mod upload;
+use upload::LogUploader;
+
 #[tokio::main]
 async fn main() {
     kankyo::load(false).expect("expected a .env file");
@@ -49,6 +55,7 @@ async fn main() {
         .group(&STATICIMAGE_GROUP)
         .group(&FUN_GROUP)
         .group(&OTHER_GROUP)
+        .group(&CONFIG_GROUP)
         .help(&MY_HELP)
         .after(after_hook);
 
@@ -62,6 +69,17 @@ async fn main() {
         .await
         .expect("error creating client");
 
+    let policy_file = env::var("BACKGROUND_CAT_POLICY_FILE").unwrap_or_else(|_| "policies.json".to_string());
+    client
+        .data
+        .write()
+        .await
+        .insert::<GuildPolicies>(Arc::new(RwLock::new(GuildPolicies::load(policy_file))));
+    let fetcher = Fetcher::new(FetchLimits::default()).expect("error creating the HTTP client");
+    client.data.write().await.insert::<LogFetcher>(fetcher);
+    let uploader = upload::from_env().await;
+    client.data.write().await.insert::<LogUploader>(uploader);
+
     if let Err(why) = client.start().await {
         error!("Client error: {:?}", why);
     }
@@ -85,11 +103,34 @@ async fn my_help(
     Ok(())
 }
 
-/// Takes a string of an URL, returns the content.
-/// Helper for Error Handling.
-async fn get_log(link: &str) -> Result<String, Box<dyn std::error::Error>> {
-    let link: reqwest::Url = link.parse()?;
-    Ok(get(link).await?.text().await?)
+/// How sure the classifier has to be before we ask for a different log.
+const KIND_CONFIDENCE_THRESHOLD: f32 = 0.6;
+
+/// Returns the kind of `log` if it is confidently not a launcher log.
+fn wrong_log_kind(log: &str) -> Option<LogKind> {
+    let classification = classify(log);
+    if classification.kind != LogKind::LauncherLog
+        && classification.kind != LogKind::Unknown
+        && classification.confidence >= KIND_CONFIDENCE_THRESHOLD
+    {
+        Some(classification.kind)
+    } else {
+        None
+    }
+}
+
+/// Links in a message that are looked at, so a wall of links can't keep the bot busy.
+const MAX_LINKS: usize = 3;
+
+lazy_static! {
+    static ref PASTE_PROVIDERS: Providers = Providers::default();
+}
+
+/// Downloads linked and attached logs.
+struct LogFetcher;
+
+impl TypeMapKey for LogFetcher {
+    type Value = Fetcher;
 }
 
 struct Handler;
@@ -101,82 +142,77 @@ impl EventHandler for Handler {
             return;
         }
 
-        lazy_static! {
-            static ref PASTEE_REGEX: Regex = Regex::new(r"https:/{2}(?:api\.)?(paste\.ee)|(pastee\.dev)/p/[^\s/]+").unwrap();
-        }
-
-        if let Some(link) = PASTEE_REGEX.find(&msg.content) {
-            info!(
-                "Found paste.ee link: {} in message {}",
-                link.as_str(),
-                msg.id
-            );
-
-            let link_raw = link.as_str().replacen("/p/", "/r/", 1);
-            let log = match get_log(&link_raw).await {
-                Ok(o) => o,
-                Err(_) => return,
-            };
-            debug!("Content of log: {}", log);
-
-            let origins = common_origins(&log);
+        let policy = {
+            let data = ctx.data.read().await;
+            let policies = data.get::<GuildPolicies>().expect("guild policies are set up in main");
+            let policies = policies.read().await;
+            policies.policy_for(msg.guild_id)
+        };
 
-            if origins.is_empty() {
-                let mistakes = common_mistakes(&log);
-                if ! mistakes.is_empty() {
-                    send_help_reply(msg.channel_id, mistakes, &ctx).await;
-                    return;
-                } else {
-                    info!("Didn't find any mistakes in log ({})", link.as_str());
+        let fetcher = {
+            let data = ctx.data.read().await;
+            data.get::<LogFetcher>().expect("the log fetcher is set up in main").clone()
+        };
+        let limits = Limits::default();
+        let links = PASTE_PROVIDERS.find_links(&msg.content);
+        if !links.is_empty() {
+            let mut logs = Vec::new();
+            for link in links.iter().take(MAX_LINKS) {
+                info!("Found {} link: {} in message {}", link.provider, link.url, msg.id);
+                match fetcher.fetch(&link.raw).await {
+                    Ok(fetched) => match input::extract(link.url.as_str(), &fetched.bytes, &limits) {
+                        Ok(extracted) => logs.extend(extracted.into_iter().map(|log| (log.name, log.text))),
+                        Err(why) => info!("Couldn't unpack {}: {}", link.url, why),
+                    },
+                    Err(why) => error!("Couldn't fetch {}: {}", link.raw, why),
                 }
-            } else {
-                info!("Detected pirated, custom or forked launcher ({})", link.as_str());
-                send_origins_reply(msg.channel_id, origins, &ctx).await;
+            }
+            debug!("Content of logs: {:?}", logs);
+
+            if !logs.is_empty() && respond(&ctx, msg.channel_id, &logs, policy.clone()).await {
                 return;
             }
-            
-        };
+        }
 
+        let mut logs = Vec::new();
         for attachment in msg.attachments {
-            let content = match attachment.download().await {
-                Ok(content) => content,
-                Err(_) => return,
+            let content_type = attachment.content_type.as_deref().unwrap_or_default();
+            if !content_type.starts_with("text/plain") && !is_archive(content_type, &attachment.filename) {
+                continue;
+            }
+            if attachment.size > fetcher.limits().max_size {
+                info!("Skipping attachment {} of {} bytes", attachment.filename, attachment.size);
+                continue;
+            }
+            let Ok(url) = attachment.url.parse() else {
+                error!("Attachment {} has an invalid URL: {}", attachment.filename, attachment.url);
+                continue;
             };
-            let content_type = attachment.content_type;
-            if content_type.is_some() && str::starts_with(&content_type.unwrap(), "text/plain") {
-                let log = String::from_utf8_lossy(&content).into_owned();
-
-                let paste_ee_future = upload_paste_ee(msg.channel_id, &log, &ctx, &msg.author);
-
-                let log_parse_future = async {
-                    let origins = common_origins(&log);
-
-                    if origins.is_empty() {
-                        let mistakes = common_mistakes(&log);
-
-                        if !mistakes.is_empty() {
-                            debug!("Mistakes found: {:?}", mistakes);
-                            send_help_reply(msg.channel_id, mistakes, &ctx).await;
-                            return;
-                        } else {
-                            info!(
-                                "Didn't find any mistakes in attachment ({})",
-                                attachment.filename
-                            );
-                        }
-                    } else {
-                        info!(
-                            "Detected pirated, custom or forked launcher ({})",
-                            attachment.filename
-                        );
-                        send_origins_reply(msg.channel_id, origins, &ctx).await;
-                        return;
-                    }
-                };
-
-                futures::join!(paste_ee_future, log_parse_future);
+            match fetcher.fetch(&url).await {
+                Ok(fetched) => match input::extract(&attachment.filename, &fetched.bytes, &limits) {
+                    Ok(extracted) => logs.extend(extracted.into_iter().map(|log| (log.name, log.text))),
+                    Err(why) => info!("Couldn't unpack attachment {}: {}", attachment.filename, why),
+                },
+                Err(why) => error!("Couldn't download attachment {}: {}", attachment.filename, why),
             }
         }
+        if logs.is_empty() {
+            return;
+        }
+
+        let uploader = {
+            let data = ctx.data.read().await;
+            data.get::<LogUploader>().expect("the log uploader is set up in main").clone()
+        };
+        let upload_future = async {
+            if let Some(uploader) = &uploader {
+                upload::offer_upload(uploader.as_ref(), msg.channel_id, &logs, &ctx, &msg.author).await;
+            }
+        };
+
+        let log_parse_future = respond(&ctx, msg.channel_id, &logs, policy);
+
+        futures::join!(upload_future, log_parse_future);
         return;
     }
 
@@ -194,128 +230,56 @@ impl EventHandler for Handler {
     }
 }
 
-async fn upload_paste_ee(channel_id: ChannelId, log: &String, ctx: &Context, user: &User) {
-    let mut button = CreateButton::default();
-    button.custom_id("upload-log");
-    button.label("Upload Log");
-
-    match channel_id.send_message(&ctx, |m| {
-        m.embed(|e| {
-            e.title("Upload log to paste.ee?");
-            e.colour(Colour::DARK_TEAL);
-            e.description("This will make it easier for people to read your log.\nThis button only works for the user who sent the log.");
-            debug!("Embed: {:?}", e);
-            e
-        });
-        m.components(|c| {
-            c.create_action_row(|r| {
-                r.add_button(button);
-                r
-            });
-            debug!("Components: {:?}", c);
-            c
-        });
-        debug!("Embed: {:?}", m);
-        m
-    }).await {
-        Err(why) => error!("Couldn't send message: {}", why),
-        Ok(mut msg) => {
-            let mut interaction_stream = msg.await_component_interactions(&ctx).timeout(Duration::from_secs(180)).build();
-            let mut uploaded = false;
-
-            while let Some(interaction) = interaction_stream.next().await {
-                if interaction.user.id == user.id {
-                    let paste_ee_token = env::var("PASTE_EE_TOKEN").expect("Expected paste.ee API token in $PASTE_EE_TOKEN");
-                    let client = reqwest::Client::new();
-
-                    let request_body = json::object! {
-                        description: "MultiMC Background Cat Log Upload",
-                        sections: [{ contents: log.as_str() }]
-                    }.dump();
-
-                    let response = json::parse(
-                        client.post("https://api.paste.ee/v1/pastes")
-                            .header("Content-Type", "application/json")
-                            .header("X-Auth-Token", paste_ee_token)
-                            .body(request_body)
-                            .send()
-                            .await.unwrap()
-                            .text()
-                            .await.unwrap()
-                            .as_str()
-                    ).unwrap();
-
-                    if !&response["success"].as_bool().unwrap_or_default() {
-                        error!("paste.ee upload failed");
-                    } else {
-                        let link = &response["link"];
-
-                        interaction.create_interaction_response(&ctx, |r| {
-                            r.kind(InteractionResponseType::UpdateMessage).interaction_response_data(|d| {
-                                d.embed(|e| {
-                                    e.title("Uploaded log");
-                                    e.colour(Colour::DARK_TEAL);
-                                    e.field("Log uploaded to paste.ee", link, true);
-                                    debug!("Embed: {:?}", e);
-                                    e
-                                });
-                                d.components(|c| c);
-                                debug!("Interaction response data: {:?}", d);
-                                d
-                            });
-                            debug!("Interaction response: {:?}", r);
-                            r
-                        }).await.unwrap();
-
-                        uploaded = true;
-                        info!("Uploaded attachment log to paste.ee: {}", link);
-                    }
-                } else {
-                    interaction.create_interaction_response(&ctx, |r| {
-                        r.kind(InteractionResponseType::ChannelMessageWithSource).interaction_response_data(|d| {
-                            d.ephemeral(true);
-                            d.embed(|e| {
-                                e.title("You are unauthorized to do this!");
-                                e.colour(Colour::DARK_TEAL);
-                                e.description("Only the user who sent the log can upload it to paste.ee.");
-                                debug!("Embed: {:?}", e);
-                                e
-                            });
-                            debug!("Interaction response data: {:?}", d);
-                            d
-                        });
-                        debug!("Interaction response: {:?}", r);
-                        r
-                    }).await.unwrap();
-                }
-            }
+/// Whether an attachment is a gzipped log or a zip file that may contain logs.
+fn is_archive(content_type: &str, filename: &str) -> bool {
+    const TYPES: &[&str] = &["application/gzip", "application/x-gzip", "application/zip", "application/x-zip-compressed"];
+    let filename = filename.to_ascii_lowercase();
+    TYPES.iter().any(|t| content_type.starts_with(t)) || filename.ends_with(".gz") || filename.ends_with(".zip")
+}
 
-            if !uploaded {
-                msg.edit(&ctx, |m| {
-                    m.embed(|e| {
-                        e.title("Timed out");
-                        e.colour(Colour::DARK_TEAL);
-                        e.description("Log has not been uploaded");
-                        debug!("Embed: {:?}", e);
-                        e
-                    });
-                    m.components(|c| c);
-                    debug!("Embed: {:?}", m);
-                    m
-                }).await.unwrap();
-            }
-        }
+/// Analyzes `logs` together and replies with what was found. Returns whether there was
+/// anything to reply.
+async fn respond(ctx: &Context, channel_id: ChannelId, logs: &[(String, String)], policy: OriginPolicy) -> bool {
+    let mut session = Session::new();
+    session.policy(policy);
+    for (name, log) in logs {
+        session.add(name.as_str(), log.as_str());
+    }
+    let report = session.analyze();
+    let names = logs.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(", ");
+
+    if report.is_blocked() {
+        info!("Detected pirated, custom or forked launcher ({})", names);
+        send_origins_reply(channel_id, &report.diagnoses, ctx).await;
+    } else if !report.diagnoses.is_empty() {
+        debug!("Mistakes found: {:?}", report.diagnoses);
+        send_help_reply(channel_id, &report.diagnoses, &report.notes, ctx).await;
+    } else if let Some(kind) = logs
+        .iter()
+        .find_map(|(_, log)| wrong_log_kind(log))
+        .filter(|_| !report.has_launcher_log())
+    {
+        info!("Logs are {} instead of a launcher log ({})", kind.description(), names);
+        send_kind_reply(channel_id, kind, ctx).await;
+    } else {
+        info!("Didn't find any mistakes in logs ({})", names);
+        return false;
     }
+    true
 }
 
-async fn send_help_reply(channel_id: ChannelId, mistakes: Vec<(&str, String)>, ctx: &Context) {
+/// `notes` are remarks about the logs themselves, shown after the diagnoses.
+async fn send_help_reply(channel_id: ChannelId, diagnoses: &[Diagnosis], notes: &[String], ctx: &Context) {
     if let Err(why) = channel_id
         .send_message(&ctx, |m| {
             m.embed(|e| {
                 e.title("Automated Response: (Warning: Experimental)");
                 e.colour(Colour::DARK_TEAL);
-                for i in mistakes.iter() {
-                    e.field(i.0, &i.1, true);
+                for d in diagnoses {
+                    e.field(d.severity, with_checklist(d), true);
+                }
+                for note in notes {
+                    e.field("ℹ", Discord.render(note), true);
                 }
                 e.footer(|f| {
                     f.icon_url("https://cdn.discordapp.com/emojis/280120125284417536.png?v=1");
@@ -330,17 +294,45 @@ async fn send_help_reply(channel_id: ChannelId, mistakes: Vec<(&str, String)>, c
         .await {
             error!("Couldn't send message: {}", why)
         }
-    return;
 }
 
-async fn send_origins_reply(channel_id: ChannelId, origins: Vec<(&str, String)>, ctx: &Context) {
+/// The message of a diagnosis, followed by the steps that fix it.
+fn with_checklist(diagnosis: &Diagnosis) -> String {
+    let mut text = Discord.render(&diagnosis.message);
+    for action in &diagnosis.actions {
+        text += &format!("\n☐ {}", Discord.render(&action.to_string()));
+    }
+    text
+}
+
+/// How many evidence lines to quote per diagnosis.
+const MAX_EVIDENCE_LINES: usize = 3;
+/// Evidence lines are cut off after this many characters.
+const MAX_EVIDENCE_CHARS: usize = 120;
+
+/// The message of a diagnosis, followed by the lines that triggered it so moderators can
+/// verify it before acting.
+fn with_evidence(diagnosis: &Diagnosis) -> String {
+    let mut text = Discord.render(&diagnosis.message);
+    for evidence in diagnosis.evidence.iter().take(MAX_EVIDENCE_LINES) {
+        let line: String = evidence.text.trim().chars().take(MAX_EVIDENCE_CHARS).collect();
+        let line = line.replace('`', "'");
+        match &evidence.source {
+            Some(source) => text += &format!("\n{} line {}: `{}`", source, evidence.line, line),
+            None => text += &format!("\nLine {}: `{}`", evidence.line, line),
+        }
+    }
+    text
+}
+
+async fn send_origins_reply(channel_id: ChannelId, origins: &[Diagnosis], ctx: &Context) {
     if let Err(why) = channel_id
         .send_message(&ctx, |m| {
             m.embed(|e| {
                 e.title("Automated Response: (Warning: Experimental)");
                 e.colour(Colour::DARK_TEAL);
-                for i in origins.iter() {
-                    e.field(i.0, &i.1, true);
+                for d in origins {
+                    e.field(d.severity, with_evidence(d), true);
                 }
                 debug!("Embed: {:?}", e);
                 e
@@ -351,5 +343,26 @@ async fn send_origins_reply(channel_id: ChannelId, origins: Vec<(&str, String)>,
         .await {
             error!("Couldn't send message: {}", why)
         }
-    return;
+}
+
+async fn send_kind_reply(channel_id: ChannelId, kind: LogKind, ctx: &Context) {
+    if let Err(why) = channel_id
+        .send_message(&ctx, |m| {
+            m.embed(|e| {
+                e.title("Automated Response: (Warning: Experimental)");
+                e.colour(Colour::DARK_TEAL);
+                e.description(format!(
+                    "This looks like {}, which usually doesn't show the whole problem.\n\
+                    Please send the full log from MultiMC instead. (Type `-log` for help)",
+                    kind.description()
+                ));
+                debug!("Embed: {:?}", e);
+                e
+            });
+            debug!("Embed: {:?}", m);
+            m
+        })
+        .await {
+            error!("Couldn't send message: {}", why)
+        }
 }
@@ -0,0 +1,224 @@
+use std::{collections::HashMap, fs, io, path::PathBuf, sync::Arc};
+
+use log::error;
+use serde::{Deserialize, Serialize};
+use serenity::{model::id::GuildId, prelude::*};
+
+use background_cat::{LauncherPolicy, OriginPolicy, VersionRange};
+
+/// A launcher policy entry as stored on disk, see [`LauncherPolicy`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct StoredEntry {
+    launcher: String,
+    versions: String,
+    support: String,
+}
+
+/// Launcher policies configured per guild, applied on top of the default [`OriginPolicy`]
+/// and saved as JSON.
+pub(crate) struct GuildPolicies {
+    path: PathBuf,
+    guilds: HashMap<u64, Vec<StoredEntry>>,
+}
+
+impl TypeMapKey for GuildPolicies {
+    type Value = Arc<RwLock<GuildPolicies>>;
+}
+
+impl GuildPolicies {
+    /// Loads the policies from `path`. A missing or broken file starts out empty.
+    pub(crate) fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let guilds = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|why| {
+                error!("Couldn't parse guild policies in {}: {}", path.display(), why);
+                HashMap::new()
+            }),
+            Err(why) if why.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(why) => {
+                error!("Couldn't read guild policies from {}: {}", path.display(), why);
+                HashMap::new()
+            }
+        };
+        GuildPolicies { path, guilds }
+    }
+
+    fn save(&self) -> io::Result<()> {
+        fs::write(&self.path, serde_json::to_string_pretty(&self.guilds)?)
+    }
+
+    /// The policy to use for messages in `guild`, or the default one in DMs.
+    pub(crate) fn policy_for(&self, guild: Option<GuildId>) -> OriginPolicy {
+        let mut policy = OriginPolicy::default();
+        let entries = guild.and_then(|g| self.guilds.get(&g.0));
+        for entry in entries.into_iter().flatten() {
+            // Entries are validated before they are stored, but the file might have been edited.
+            match (entry.versions.parse(), entry.support.parse()) {
+                (Ok(versions), Ok(support)) => {
+                    policy.set(entry.launcher.clone(), versions, support);
+                }
+                _ => error!("Ignoring invalid launcher policy {:?}", entry),
+            }
+        }
+        policy
+    }
+
+    pub(crate) fn add(&mut self, guild: GuildId, entry: &LauncherPolicy) -> io::Result<()> {
+        self.guilds.entry(guild.0).or_default().push(StoredEntry {
+            launcher: entry.launcher.clone(),
+            versions: entry.versions.to_string(),
+            support: entry.support.to_string(),
+        });
+        self.save()
+    }
+
+    /// Removes the entries for `launcher`, or only the one for `versions` of it. Returns how
+    /// many were removed.
+    pub(crate) fn remove(
+        &mut self,
+        guild: GuildId,
+        launcher: &str,
+        versions: Option<&VersionRange>,
+    ) -> io::Result<usize> {
+        let Some(entries) = self.guilds.get_mut(&guild.0) else {
+            return Ok(0);
+        };
+        let before = entries.len();
+        entries.retain(|e| {
+            !(e.launcher.eq_ignore_ascii_case(launcher)
+                && versions.is_none_or(|v| e.versions == v.to_string()))
+        });
+        let removed = before - entries.len();
+        if entries.is_empty() {
+            self.guilds.remove(&guild.0);
+        }
+        if removed > 0 {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    pub(crate) fn reset(&mut self, guild: GuildId) -> io::Result<()> {
+        self.guilds.remove(&guild.0);
+        self.save()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use background_cat::{LauncherInfo, Support};
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const GUILD: GuildId = GuildId(1);
+    const OTHER_GUILD: GuildId = GuildId(2);
+
+    fn entry(launcher: &str, versions: &str, support: Support) -> LauncherPolicy {
+        LauncherPolicy {
+            launcher: launcher.to_string(),
+            versions: versions.parse().unwrap(),
+            support,
+        }
+    }
+
+    /// The entries added for `guild`, after the default ones.
+    fn added(policies: &GuildPolicies, guild: GuildId) -> Vec<LauncherPolicy> {
+        let defaults = OriginPolicy::default().entries().len();
+        policies.policy_for(Some(guild)).entries()[defaults..].to_vec()
+    }
+
+    fn launcher(name: &str, version: &str) -> LauncherInfo {
+        LauncherInfo {
+            name: name.to_string(),
+            version: version.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn starts_empty_without_a_file_or_with_a_broken_one() {
+        let dir = TempDir::new().unwrap();
+        let missing = GuildPolicies::load(dir.path().join("policies.json"));
+        assert!(missing.guilds.is_empty());
+
+        fs::write(dir.path().join("broken.json"), "{not json").unwrap();
+        let broken = GuildPolicies::load(dir.path().join("broken.json"));
+        assert!(broken.guilds.is_empty());
+    }
+
+    #[test]
+    fn saves_and_loads_entries() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("policies.json");
+        let mut policies = GuildPolicies::load(&path);
+        policies.add(GUILD, &entry("Prism Launcher", ">=7.0", Support::Warn)).unwrap();
+        policies.add(GUILD, &entry("UltimMC", "*", Support::Refuse)).unwrap();
+
+        let loaded = GuildPolicies::load(&path);
+        assert_eq!(
+            added(&loaded, GUILD),
+            [entry("Prism Launcher", ">=7.0", Support::Warn), entry("UltimMC", "*", Support::Refuse)]
+        );
+        assert!(added(&loaded, OTHER_GUILD).is_empty());
+        assert_eq!(loaded.policy_for(None).entries(), OriginPolicy::default().entries());
+    }
+
+    #[test]
+    fn matches_launchers_by_name_and_version() {
+        let dir = TempDir::new().unwrap();
+        let mut policies = GuildPolicies::load(dir.path().join("policies.json"));
+        policies.add(GUILD, &entry("Prism Launcher", "*", Support::Warn)).unwrap();
+        policies.add(GUILD, &entry("prism launcher", ">=8.0", Support::Supported)).unwrap();
+
+        let policy = policies.policy_for(Some(GUILD));
+        assert_eq!(policy.support_for(&launcher("Prism Launcher", "7.2")), Support::Warn);
+        // Later entries take precedence
+        assert_eq!(policy.support_for(&launcher("Prism Launcher", "8.2")), Support::Supported);
+        // Other launchers are treated as by default, in the guild and in DMs
+        let default = OriginPolicy::default();
+        let multimc = launcher("MultiMC", "0.7.0-3275");
+        assert_eq!(policy.support_for(&multimc), default.support_for(&multimc));
+        let prism = launcher("Prism Launcher", "8.2");
+        assert_eq!(policies.policy_for(None).support_for(&prism), default.support_for(&prism));
+    }
+
+    #[test]
+    fn ignores_invalid_entries_in_the_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("policies.json");
+        fs::write(
+            &path,
+            r#"{"1": [
+                {"launcher": "PolyMC", "versions": "*", "support": "sometimes"},
+                {"launcher": "PolyMC", "versions": ">=5.0", "support": "refuse"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(added(&GuildPolicies::load(&path), GUILD), [entry("PolyMC", ">=5.0", Support::Refuse)]);
+    }
+
+    #[test]
+    fn removes_entries_and_resets() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("policies.json");
+        let mut policies = GuildPolicies::load(&path);
+        policies.add(GUILD, &entry("Prism Launcher", "*", Support::Warn)).unwrap();
+        policies.add(GUILD, &entry("Prism Launcher", ">=8.0", Support::Supported)).unwrap();
+        policies.add(GUILD, &entry("UltimMC", "*", Support::Refuse)).unwrap();
+        policies.add(OTHER_GUILD, &entry("Prism Launcher", "*", Support::Refuse)).unwrap();
+
+        let versions = ">=8.0".parse().unwrap();
+        assert_eq!(policies.remove(GUILD, "prism launcher", Some(&versions)).unwrap(), 1);
+        assert_eq!(policies.remove(GUILD, "Prism Launcher", Some(&versions)).unwrap(), 0);
+        assert_eq!(policies.remove(GUILD, "PolyMC", None).unwrap(), 0);
+        assert_eq!(policies.remove(GUILD, "Prism Launcher", None).unwrap(), 1);
+        let loaded = GuildPolicies::load(&path);
+        assert_eq!(added(&loaded, GUILD), [entry("UltimMC", "*", Support::Refuse)]);
+        assert_eq!(added(&loaded, OTHER_GUILD).len(), 1);
+
+        policies.reset(GUILD).unwrap();
+        let loaded = GuildPolicies::load(&path);
+        assert!(added(&loaded, GUILD).is_empty());
+        assert_eq!(added(&loaded, OTHER_GUILD).len(), 1);
+    }
+}
@@ -0,0 +1,251 @@
+use std::{env, sync::Arc, time::Duration};
+
+use futures::StreamExt;
+use log::{debug, error, info, warn};
+use serenity::{
+    builder::CreateButton,
+    model::{
+        id::ChannelId,
+        interactions::{message_component::MessageComponentInteraction, InteractionResponseType},
+        user::User,
+    },
+    prelude::*,
+    utils::Colour,
+};
+
+use paste_cat::{
+    upload::{McLogs, PasteEe, SelfHosted},
+    UploadError, Uploader,
+};
+
+/// Where logs are uploaded when their sender asks for it, or `None` if uploading is off.
+pub(crate) struct LogUploader;
+
+impl TypeMapKey for LogUploader {
+    type Value = Option<Arc<dyn Uploader>>;
+}
+
+/// Sets up the uploader configured with `BACKGROUND_CAT_UPLOADER`, which is `paste.ee` if
+/// `PASTE_EE_TOKEN` is set and `none` otherwise, and checks that it works. Panics on wrong
+/// settings or credentials, like the rest of the configuration.
+pub(crate) async fn from_env() -> Option<Arc<dyn Uploader>> {
+    let var = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+    let expiry = var("BACKGROUND_CAT_UPLOAD_EXPIRY").and_then(|expiry| {
+        parse_expiry(&expiry).unwrap_or_else(|| panic!("invalid expiry in $BACKGROUND_CAT_UPLOAD_EXPIRY: {}", expiry))
+    });
+    let kind = var("BACKGROUND_CAT_UPLOADER")
+        .unwrap_or_else(|| if var("PASTE_EE_TOKEN").is_some() { "paste.ee" } else { "none" }.to_string());
+
+    let uploader: Result<Arc<dyn Uploader>, UploadError> = match kind.as_str() {
+        "paste.ee" => {
+            let token = var("PASTE_EE_TOKEN").expect("expected a paste.ee API token in $PASTE_EE_TOKEN");
+            PasteEe::new(token, expiry).map(|uploader| Arc::new(uploader) as _)
+        }
+        "mclo.gs" => {
+            if expiry.is_some() {
+                warn!("mclo.gs doesn't support $BACKGROUND_CAT_UPLOAD_EXPIRY, logs are kept for 90 days");
+            }
+            McLogs::new().map(|uploader| Arc::new(uploader) as _)
+        }
+        "self-hosted" => {
+            let url = var("BACKGROUND_CAT_UPLOAD_URL").expect("expected the paste server's URL in $BACKGROUND_CAT_UPLOAD_URL");
+            let url = url
+                .parse()
+                .unwrap_or_else(|why| panic!("invalid URL in $BACKGROUND_CAT_UPLOAD_URL: {}", why));
+            if expiry.is_some() {
+                warn!("$BACKGROUND_CAT_UPLOAD_EXPIRY is ignored, the paste server decides how long logs are kept");
+            }
+            SelfHosted::new(url, var("BACKGROUND_CAT_UPLOAD_TOKEN")).map(|uploader| Arc::new(uploader) as _)
+        }
+        "none" => {
+            info!("Log uploads are turned off");
+            return None;
+        }
+        other => panic!(
+            "unknown uploader in $BACKGROUND_CAT_UPLOADER: {} (expected paste.ee, mclo.gs, self-hosted or none)",
+            other
+        ),
+    };
+    let uploader = uploader.unwrap_or_else(|why| panic!("error creating the HTTP client for uploads: {}", why));
+
+    match uploader.validate().await {
+        Ok(()) => info!("Uploading logs to {}", uploader.name()),
+        Err(UploadError::Unauthorized) => panic!("{} refused the upload token", uploader.name()),
+        // It may only be down for now
+        Err(why) => warn!("Couldn't check the upload settings for {}: {}", uploader.name(), why),
+    }
+    Some(uploader)
+}
+
+/// Parses an expiry like `3600`, `90m`, `12h` or `30d`, or `never`. Returns `None` if it's
+/// neither, or if it's zero, which would delete logs right away.
+fn parse_expiry(expiry: &str) -> Option<Option<Duration>> {
+    if expiry.eq_ignore_ascii_case("never") {
+        return Some(None);
+    }
+    let (number, unit) = match expiry.find(|c: char| !c.is_ascii_digit()) {
+        Some(at) => expiry.split_at(at),
+        None => (expiry, "s"),
+    };
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return None,
+    };
+    let number: u64 = number.parse().ok().filter(|&number| number != 0)?;
+    Some(Some(Duration::from_secs(number.checked_mul(seconds)?)))
+}
+
+/// Offers to upload `logs`, pairs of file name and content, with `uploader` as one paste.
+/// Only `user`, who sent the logs, can accept.
+pub(crate) async fn offer_upload(
+    uploader: &dyn Uploader,
+    channel_id: ChannelId,
+    logs: &[(String, String)],
+    ctx: &Context,
+    user: &User,
+) {
+    let mut button = CreateButton::default();
+    button.custom_id("upload-log");
+    button.label("Upload Log");
+
+    let mut msg = match channel_id.send_message(&ctx, |m| {
+        m.embed(|e| {
+            e.title(format!("Upload log to {}?", uploader.name()));
+            e.colour(Colour::DARK_TEAL);
+            e.description("This will make it easier for people to read your log.\nThis button only works for the user who sent the log.");
+            debug!("Embed: {:?}", e);
+            e
+        });
+        m.components(|c| {
+            c.create_action_row(|r| {
+                r.add_button(button);
+                r
+            });
+            debug!("Components: {:?}", c);
+            c
+        });
+        debug!("Embed: {:?}", m);
+        m
+    }).await {
+        Ok(msg) => msg,
+        Err(why) => {
+            error!("Couldn't send message: {}", why);
+            return;
+        }
+    };
+
+    let mut interaction_stream = msg.await_component_interactions(ctx).timeout(Duration::from_secs(180)).build();
+    while let Some(interaction) = interaction_stream.next().await {
+        if interaction.user.id != user.id {
+            reply_ephemeral(
+                ctx,
+                &interaction,
+                "You are unauthorized to do this!",
+                &format!("Only the user who sent the log can upload it to {}.", uploader.name()),
+            )
+            .await;
+            continue;
+        }
+
+        match uploader.upload("MultiMC Background Cat Log Upload", logs).await {
+            Ok(link) => {
+                info!("Uploaded attachment log to {}: {}", uploader.name(), link);
+                if let Err(why) = interaction.create_interaction_response(&ctx, |r| {
+                    r.kind(InteractionResponseType::UpdateMessage).interaction_response_data(|d| {
+                        d.embed(|e| {
+                            e.title("Uploaded log");
+                            e.colour(Colour::DARK_TEAL);
+                            e.field(format!("Log uploaded to {}", uploader.name()), link, true);
+                            debug!("Embed: {:?}", e);
+                            e
+                        });
+                        d.components(|c| c);
+                        debug!("Interaction response data: {:?}", d);
+                        d
+                    });
+                    debug!("Interaction response: {:?}", r);
+                    r
+                }).await {
+                    error!("Couldn't send the uploaded log's link: {}", why);
+                }
+                return;
+            }
+            Err(why) => {
+                error!("Couldn't upload log to {}: {}", uploader.name(), why);
+                reply_ephemeral(
+                    ctx,
+                    &interaction,
+                    "Couldn't upload the log",
+                    &format!("{} didn't take the log, try again in a bit.", uploader.name()),
+                )
+                .await;
+            }
+        }
+    }
+
+    if let Err(why) = msg.edit(&ctx, |m| {
+        m.embed(|e| {
+            e.title("Timed out");
+            e.colour(Colour::DARK_TEAL);
+            e.description("Log has not been uploaded");
+            debug!("Embed: {:?}", e);
+            e
+        });
+        m.components(|c| c);
+        debug!("Embed: {:?}", m);
+        m
+    }).await {
+        error!("Couldn't edit message: {}", why);
+    }
+}
+
+/// Answers `interaction` with a message only its user sees.
+async fn reply_ephemeral(ctx: &Context, interaction: &MessageComponentInteraction, title: &str, description: &str) {
+    if let Err(why) = interaction.create_interaction_response(&ctx, |r| {
+        r.kind(InteractionResponseType::ChannelMessageWithSource).interaction_response_data(|d| {
+            d.ephemeral(true);
+            d.embed(|e| {
+                e.title(title);
+                e.colour(Colour::DARK_TEAL);
+                e.description(description);
+                debug!("Embed: {:?}", e);
+                e
+            });
+            debug!("Interaction response data: {:?}", d);
+            d
+        });
+        debug!("Interaction response: {:?}", r);
+        r
+    }).await {
+        error!("Couldn't answer interaction: {}", why);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_expiries() {
+        assert_eq!(parse_expiry("never"), Some(None));
+        assert_eq!(parse_expiry("Never"), Some(None));
+        assert_eq!(parse_expiry("3600"), Some(Some(Duration::from_secs(3600))));
+        assert_eq!(parse_expiry("45s"), Some(Some(Duration::from_secs(45))));
+        assert_eq!(parse_expiry("90m"), Some(Some(Duration::from_secs(90 * 60))));
+        assert_eq!(parse_expiry("12h"), Some(Some(Duration::from_secs(12 * 60 * 60))));
+        assert_eq!(parse_expiry("30d"), Some(Some(Duration::from_secs(30 * 24 * 60 * 60))));
+    }
+
+    #[test]
+    fn rejects_invalid_expiries() {
+        for expiry in ["0", "0d", "", "d", "soon", "12w", "1.5h", "-1", "12 h", "1h30m"] {
+            assert_eq!(parse_expiry(expiry), None, "{:?}", expiry);
+        }
+        // Overflows when turned into seconds
+        assert_eq!(parse_expiry(&format!("{}d", u64::MAX / 60)), None);
+        assert_eq!(parse_expiry("99999999999999999999"), None);
+    }
+}
@@ -0,0 +1,12 @@
+fn main() {
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let config = cbindgen::Config::from_file("cbindgen.toml").expect("cbindgen.toml is invalid");
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src("src/lib.rs")
+        .generate()
+        .expect("Couldn't generate the C header")
+        .write_to_file("include/background_cat.h");
+}
@@ -0,0 +1,88 @@
+#ifndef BACKGROUND_CAT_H
+#define BACKGROUND_CAT_H
+
+/* Generated from src/lib.rs when the crate is built, don't edit it by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Changes whenever the API changes in a way that breaks programs built against an older
+// header. Compare it with `bcat_abi_version()` when loading the library at runtime.
+#define BCAT_ABI_VERSION 1
+
+// A finding of a rule.
+typedef struct BcatDiagnosis {
+  // Id of the response, e.g. "out-of-memory".
+  const char *id;
+  // "mistakes" or "origins".
+  const char *rule_set;
+  // An emoji telling how bad the problem is.
+  const char *severity;
+  // The response as plain text.
+  const char *message;
+  // The response as HTML, with links and code marked up, e.g. for a `QLabel`.
+  const char *message_html;
+  // How likely the finding is right, between 0 and 1.
+  float confidence;
+  // Whether the finding is about the launcher itself, in which case nothing else was
+  // reported.
+  bool blocking;
+  // 1-based number of the first line that triggered the finding, or 0 if the rule
+  // doesn't tell.
+  size_t line;
+} BcatDiagnosis;
+
+// The findings for a log, in the order they should be shown.
+typedef struct BcatDiagnoses {
+  const struct BcatDiagnosis *items;
+  size_t len;
+} BcatDiagnoses;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// The version of background-cat, e.g. "0.1.0". The string is static and must not be freed.
+const char *bcat_version(void);
+
+// The `BCAT_ABI_VERSION` the library was built with.
+uint32_t bcat_abi_version(void);
+
+// Runs all rules on the `len` bytes at `log` and returns the findings, or NULL if `log` is
+// NULL while `len` isn't 0. Free the result with `bcat_diagnoses_free`.
+//
+// # Safety
+//
+// `log` must point to `len` readable bytes.
+struct BcatDiagnoses *bcat_analyze(const uint8_t *log, size_t len);
+
+// Frees what `bcat_analyze` returned. Does nothing for NULL.
+//
+// # Safety
+//
+// `diagnoses` must come from `bcat_analyze` and not have been freed before.
+void bcat_diagnoses_free(struct BcatDiagnoses *diagnoses);
+
+// Like `bcat_analyze`, but returns the whole report as JSON, in the format of
+// `cli-cat --format json`'s reports. Free the result with `bcat_string_free`.
+//
+// # Safety
+//
+// `log` must point to `len` readable bytes.
+char *bcat_analyze_json(const uint8_t *log, size_t len);
+
+// Frees a string returned by the library. Does nothing for NULL.
+//
+// # Safety
+//
+// `string` must come from `bcat_analyze_json` and not have been freed before.
+void bcat_string_free(char *string);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* BACKGROUND_CAT_H */
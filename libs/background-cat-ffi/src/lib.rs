@@ -0,0 +1,171 @@
+//! C API of background-cat, for launchers that want to show diagnoses next to their log.
+//!
+//! The header, `include/background_cat.h`, is generated from this file when the crate is
+//! built. Strings going in are UTF-8 (or UTF-16 with a byte order mark), strings coming out
+//! are NUL-terminated UTF-8 and owned by the library until they're freed with the matching
+//! `bcat_*_free` function.
+
+use std::{
+    ffi::{c_char, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use background_cat::{
+    input,
+    render::{Html, Plain},
+    Analyzer, Diagnosis, Renderer, RuleSet,
+};
+
+/// Changes whenever the API changes in a way that breaks programs built against an older
+/// header. Compare it with `bcat_abi_version()` when loading the library at runtime.
+pub const BCAT_ABI_VERSION: u32 = 1;
+
+/// A finding of a rule.
+#[repr(C)]
+pub struct BcatDiagnosis {
+    /// Id of the response, e.g. "out-of-memory".
+    pub id: *const c_char,
+    /// "mistakes" or "origins".
+    pub rule_set: *const c_char,
+    /// An emoji telling how bad the problem is.
+    pub severity: *const c_char,
+    /// The response as plain text.
+    pub message: *const c_char,
+    /// The response as HTML, with links and code marked up, e.g. for a `QLabel`.
+    pub message_html: *const c_char,
+    /// How likely the finding is right, between 0 and 1.
+    pub confidence: f32,
+    /// Whether the finding is about the launcher itself, in which case nothing else was
+    /// reported.
+    pub blocking: bool,
+    /// 1-based number of the first line that triggered the finding, or 0 if the rule
+    /// doesn't tell.
+    pub line: usize,
+}
+
+/// The findings for a log, in the order they should be shown.
+#[repr(C)]
+pub struct BcatDiagnoses {
+    pub items: *const BcatDiagnosis,
+    pub len: usize,
+}
+
+/// The version of background-cat, e.g. "0.1.0". The string is static and must not be freed.
+#[no_mangle]
+pub extern "C" fn bcat_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// The `BCAT_ABI_VERSION` the library was built with.
+#[no_mangle]
+pub extern "C" fn bcat_abi_version() -> u32 {
+    BCAT_ABI_VERSION
+}
+
+/// Runs all rules on the `len` bytes at `log` and returns the findings, or NULL if `log` is
+/// NULL while `len` isn't 0. Free the result with `bcat_diagnoses_free`.
+///
+/// # Safety
+///
+/// `log` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bcat_analyze(log: *const u8, len: usize) -> *mut BcatDiagnoses {
+    let Some(bytes) = bytes(log, len) else {
+        return ptr::null_mut();
+    };
+    guarded(|| {
+        let items: Box<[BcatDiagnosis]> = analyze(bytes).iter().map(diagnosis).collect();
+        let len = items.len();
+        let items = Box::into_raw(items).cast::<BcatDiagnosis>().cast_const();
+        Box::into_raw(Box::new(BcatDiagnoses { items, len }))
+    })
+}
+
+/// Frees what `bcat_analyze` returned. Does nothing for NULL.
+///
+/// # Safety
+///
+/// `diagnoses` must come from `bcat_analyze` and not have been freed before.
+#[no_mangle]
+pub unsafe extern "C" fn bcat_diagnoses_free(diagnoses: *mut BcatDiagnoses) {
+    if diagnoses.is_null() {
+        return;
+    }
+    let diagnoses = Box::from_raw(diagnoses);
+    let items = Box::from_raw(ptr::slice_from_raw_parts_mut(diagnoses.items.cast_mut(), diagnoses.len));
+    for item in items.iter() {
+        for string in [item.id, item.rule_set, item.severity, item.message, item.message_html] {
+            drop(CString::from_raw(string.cast_mut()));
+        }
+    }
+}
+
+/// Like `bcat_analyze`, but returns the whole report as JSON, in the format of
+/// `cli-cat --format json`'s reports. Free the result with `bcat_string_free`.
+///
+/// # Safety
+///
+/// `log` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bcat_analyze_json(log: *const u8, len: usize) -> *mut c_char {
+    let Some(bytes) = bytes(log, len) else {
+        return ptr::null_mut();
+    };
+    guarded(|| {
+        let report = Analyzer::new().analyze(&input::decode(bytes));
+        // Serializing a report can't fail
+        c_string(&serde_json::to_string(&report).unwrap_or_default())
+    })
+}
+
+/// Frees a string returned by the library. Does nothing for NULL.
+///
+/// # Safety
+///
+/// `string` must come from `bcat_analyze_json` and not have been freed before.
+#[no_mangle]
+pub unsafe extern "C" fn bcat_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+unsafe fn bytes<'a>(log: *const u8, len: usize) -> Option<&'a [u8]> {
+    match (log.is_null(), len) {
+        (true, 0) => Some(&[]),
+        (true, _) => None,
+        (false, _) => Some(slice::from_raw_parts(log, len)),
+    }
+}
+
+fn analyze(bytes: &[u8]) -> Vec<Diagnosis> {
+    Analyzer::new().analyze(&input::decode(bytes)).diagnoses
+}
+
+/// Runs `f`, returning NULL instead of unwinding into C if it panics.
+fn guarded<T>(f: impl FnOnce() -> *mut T) -> *mut T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(ptr::null_mut())
+}
+
+fn diagnosis(d: &Diagnosis) -> BcatDiagnosis {
+    let rule_set = match d.rule_set {
+        RuleSet::Mistakes => "mistakes",
+        RuleSet::Origins => "origins",
+    };
+    BcatDiagnosis {
+        id: c_string(d.id),
+        rule_set: c_string(rule_set),
+        severity: c_string(d.severity),
+        message: c_string(&Plain.render(&d.message)),
+        message_html: c_string(&Html.render(&d.message)),
+        confidence: d.confidence,
+        blocking: d.blocking,
+        line: d.evidence.first().map_or(0, |e| e.line),
+    }
+}
+
+/// A string for C, which can't contain NUL characters, so they're dropped.
+fn c_string(s: &str) -> *mut c_char {
+    CString::new(s.replace('\0', "")).unwrap_or_default().into_raw()
+}
@@ -0,0 +1,59 @@
+/* Uses the C API the way a launcher would. Built and run by tests/c_api.rs. */
+
+#include <assert.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "background_cat.h"
+
+static const char LOG[] =
+    "MultiMC version: 0.6.16\n"
+    "Minecraft folder is:\n"
+    "C:/Program Files/MultiMC/instances/1.18/.minecraft\n"
+    "[12:00:00] [main/INFO]: Loading\n"
+    "java.lang.OutOfMemoryError: Java heap space\n";
+
+static const BcatDiagnosis *find(const BcatDiagnoses *diagnoses, const char *id) {
+    for (size_t i = 0; i < diagnoses->len; i++) {
+        if (strcmp(diagnoses->items[i].id, id) == 0) {
+            return &diagnoses->items[i];
+        }
+    }
+    return NULL;
+}
+
+int main(void) {
+    assert(bcat_abi_version() == BCAT_ABI_VERSION);
+    assert(strlen(bcat_version()) > 0);
+
+    BcatDiagnoses *diagnoses = bcat_analyze((const uint8_t *)LOG, strlen(LOG));
+    assert(diagnoses != NULL);
+    const BcatDiagnosis *memory = find(diagnoses, "out-of-memory");
+    assert(memory != NULL);
+    assert(memory->line == 5);
+    assert(strcmp(memory->rule_set, "mistakes") == 0);
+    assert(strstr(memory->message_html, "<a href=") != NULL);
+    const BcatDiagnosis *program_files = find(diagnoses, "program-files");
+    assert(program_files != NULL);
+    assert(program_files->line == 3);
+    for (size_t i = 0; i < diagnoses->len; i++) {
+        printf("%s %s (line %zu)\n", diagnoses->items[i].severity, diagnoses->items[i].message,
+               diagnoses->items[i].line);
+    }
+    bcat_diagnoses_free(diagnoses);
+
+    BcatDiagnoses *empty = bcat_analyze(NULL, 0);
+    assert(empty != NULL && empty->len == 0);
+    bcat_diagnoses_free(empty);
+    assert(bcat_analyze(NULL, 1) == NULL);
+
+    char *json = bcat_analyze_json((const uint8_t *)LOG, strlen(LOG));
+    assert(json != NULL);
+    assert(strstr(json, "\"diagnoses\"") != NULL);
+    assert(strstr(json, "\"out-of-memory\"") != NULL);
+    bcat_string_free(json);
+
+    bcat_diagnoses_free(NULL);
+    bcat_string_free(NULL);
+    return 0;
+}
@@ -0,0 +1,34 @@
+//! Builds tests/c/analyze.c against the library and the generated header, and runs it.
+#![cfg(unix)]
+
+use std::{env, path::PathBuf, process::Command};
+
+#[test]
+fn c_program_uses_the_api() {
+    // Test binaries are in target/<profile>/deps, next to which cargo puts the library
+    let exe = env::current_exe().unwrap();
+    let libs = exe.parent().and_then(|deps| deps.parent()).unwrap();
+    let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let program = env::temp_dir().join(format!("background-cat-ffi-test-{}", std::process::id()));
+
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(&compiler)
+        .args(["-std=c99", "-Wall", "-Werror", "-o"])
+        .arg(&program)
+        .arg(manifest.join("tests/c/analyze.c"))
+        .arg("-I")
+        .arg(manifest.join("include"))
+        .arg("-L")
+        .arg(libs)
+        .arg(format!("-Wl,-rpath,{}", libs.display()))
+        .arg("-lbackground_cat_ffi")
+        .status()
+        .unwrap_or_else(|why| panic!("Couldn't run {}: {}", compiler, why));
+    assert!(status.success(), "Couldn't build the C test program");
+
+    let output = Command::new(&program).output().unwrap();
+    std::fs::remove_file(&program).ok();
+    print!("{}", String::from_utf8_lossy(&output.stdout));
+    eprint!("{}", String::from_utf8_lossy(&output.stderr));
+    assert!(output.status.success(), "The C test program failed: {}", output.status);
+}
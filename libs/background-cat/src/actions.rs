@@ -0,0 +1,67 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::ResponseId;
+
+/// A step that fixes what a diagnosis is about, for frontends that can apply fixes themselves
+/// or show them as a checklist.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", content = "value", rename_all = "kebab-case")]
+pub enum Action {
+    /// Add an argument to the instance's JVM arguments.
+    SetJvmArg(String),
+    /// Run the game with this major Java version.
+    RequireJava(u32),
+    /// Remove the mod with this id, or the jar with this file name if its metadata can't be
+    /// read.
+    RemoveMod(String),
+    /// Install the mod with this id.
+    InstallMod(String),
+    /// Move the launcher out of the folder it's in.
+    MoveInstall,
+    /// Set the maximum memory of the instance, in MiB.
+    SetMaxMemory(u64),
+    Reboot,
+}
+
+/// Displays the action as a checklist item, in the markup described in [`crate::render`].
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::SetJvmArg(arg) => write!(f, "Add `{}` to the JVM arguments", arg),
+            Action::RequireJava(version) => write!(f, "Use Java {}", version),
+            Action::RemoveMod(id) => write!(f, "Remove the mod `{}`", id),
+            Action::InstallMod(id) => write!(f, "Install the mod `{}`", id),
+            Action::MoveInstall => f.write_str("Move MultiMC to a folder of its own, like `C:/MultiMC`"),
+            Action::SetMaxMemory(mib) => write!(f, "Set the maximum memory to {} MiB", mib),
+            Action::Reboot => f.write_str("Reboot your computer"),
+        }
+    }
+}
+
+/// Memory to suggest when a log runs out of memory without saying how much it had.
+pub(crate) const DEFAULT_MAX_MEMORY: u64 = 4096;
+
+/// What fixes the problem a response is about, unless the rule knows better.
+pub(crate) fn default_actions(id: ResponseId) -> Vec<Action> {
+    match id {
+        ResponseId::ProgramFiles
+        | ResponseId::MultimcInOnedrive
+        | ResponseId::TempFolder
+        | ResponseId::WinrarTemp => vec![Action::MoveInstall],
+        ResponseId::MacosJavaTooNew | ResponseId::UseJava8 => vec![Action::RequireJava(8)],
+        ResponseId::UseJava16 => vec![Action::RequireJava(16)],
+        ResponseId::UseJava17 => vec![Action::RequireJava(17)],
+        ResponseId::UseJava21 => vec![Action::RequireJava(21)],
+        ResponseId::IdLimit => vec![Action::InstallMod("jeid".to_string())],
+        ResponseId::OutOfMemory => vec![Action::SetMaxMemory(DEFAULT_MAX_MEMORY)],
+        ResponseId::OptifineAndShadermod => vec![Action::RemoveMod("shadersmod".to_string())],
+        ResponseId::MissingFabricApi => vec![Action::InstallMod("fabric-api".to_string())],
+        ResponseId::AppleSiliconIncompatibleForge => {
+            vec![Action::SetJvmArg("-Dfml.earlyprogresswindow=false".to_string())]
+        }
+        ResponseId::RebootRequired => vec![Action::Reboot],
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,646 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use log::error;
+use serde::Serialize;
+
+use crate::{
+    actions::default_actions, classify, detect_launcher, exit_codes, facts, Action,
+    Classification, Diagnosis, Evidence, Facts, LauncherInfo, Locale, LogKind, OriginPolicy,
+    ResponseId, Support, UnknownResponse, ORIGINS, PARSERS,
+};
+
+/// What a check found in a log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    severity: &'static str,
+    /// Id of the response, see [`ResponseId`].
+    id: &'static str,
+    /// Text to show instead of the response, for checks with their own wording.
+    message: Option<String>,
+    evidence: Vec<Evidence>,
+    /// Replace the response's default actions.
+    actions: Option<Vec<Action>>,
+}
+
+impl Hit {
+    pub fn new(severity: &'static str, id: ResponseId) -> Self {
+        Hit::named(severity, id.as_str())
+    }
+
+    /// A hit for the response with the id `id`, for rules that aren't compiled in, like ones
+    /// loaded from a file. Unless the hit gets a message of its own, an unknown id is reported
+    /// as a [`RuleError`] when the rule runs.
+    pub fn named(severity: &'static str, id: &'static str) -> Self {
+        Hit {
+            severity,
+            id,
+            message: None,
+            evidence: Vec::new(),
+            actions: None,
+        }
+    }
+
+    /// Shows `message` instead of the catalogued response for the hit's id.
+    pub fn with_message(self, message: impl Into<String>) -> Self {
+        Hit {
+            message: Some(message.into()),
+            ..self
+        }
+    }
+
+    pub fn with_evidence(self, evidence: impl IntoIterator<Item = Evidence>) -> Self {
+        Hit {
+            evidence: evidence.into_iter().collect(),
+            ..self
+        }
+    }
+
+    /// Suggests `actions` instead of the ones the response usually comes with.
+    pub fn with_actions(self, actions: impl IntoIterator<Item = Action>) -> Self {
+        Hit {
+            actions: Some(actions.into_iter().collect()),
+            ..self
+        }
+    }
+}
+
+/// Something that looks for a problem in a log. Implemented for closures, so most custom
+/// rules don't need a type of their own.
+pub trait Detector: Send + Sync {
+    fn detect(&self, log: &str) -> Option<Hit>;
+}
+
+impl<F> Detector for F
+where
+    F: Fn(&str) -> Option<Hit> + Send + Sync,
+{
+    fn detect(&self, log: &str) -> Option<Hit> {
+        self(log)
+    }
+}
+
+#[derive(Clone)]
+enum Check {
+    Builtin(fn(&str) -> Option<Hit>),
+    Custom(Arc<dyn Detector>),
+}
+
+impl Check {
+    fn run(&self, log: &str) -> Option<Hit> {
+        match self {
+            Check::Builtin(check) => check(log),
+            Check::Custom(detector) => detector.detect(log),
+        }
+    }
+}
+
+/// A check together with the kinds of logs it can match in and how it relates to other rules.
+#[derive(Clone)]
+pub struct Rule {
+    id: &'static str,
+    check: Check,
+    kinds: &'static [LogKind],
+    confidence: f32,
+    /// Responses made redundant by this rule's finding.
+    supersedes: &'static [ResponseId],
+    /// Responses contradicting this rule's finding. Only the more confident one is kept.
+    conflicts: &'static [ResponseId],
+    /// See [`Diagnosis::blocking`].
+    blocking: bool,
+}
+
+impl fmt::Debug for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Rule")
+            .field("id", &self.id)
+            .field("kinds", &self.kinds)
+            .field("confidence", &self.confidence)
+            .field("supersedes", &self.supersedes)
+            .field("conflicts", &self.conflicts)
+            .field("blocking", &self.blocking)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Rule {
+    /// A rule named `id` that runs `check` on logs of `kinds`, with full confidence.
+    pub const fn new(id: &'static str, check: fn(&str) -> Option<Hit>, kinds: &'static [LogKind]) -> Self {
+        Rule {
+            id,
+            check: Check::Builtin(check),
+            kinds,
+            confidence: 1.0,
+            supersedes: &[],
+            conflicts: &[],
+            blocking: false,
+        }
+    }
+
+    /// Like [`Rule::new`], for checks that need state, like closures capturing a regex.
+    pub fn detector(id: &'static str, detector: impl Detector + 'static, kinds: &'static [LogKind]) -> Self {
+        Rule {
+            check: Check::Custom(Arc::new(detector)),
+            ..Rule::new(id, |_| None, kinds)
+        }
+    }
+
+    pub const fn confidence(mut self, confidence: f32) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    pub const fn supersedes(mut self, supersedes: &'static [ResponseId]) -> Self {
+        self.supersedes = supersedes;
+        self
+    }
+
+    pub const fn conflicts(mut self, conflicts: &'static [ResponseId]) -> Self {
+        self.conflicts = conflicts;
+        self
+    }
+
+    /// Makes findings of the rule hide all others, if the rule is at least as confident as
+    /// [`BLOCKING_CONFIDENCE`].
+    pub const fn blocking(mut self) -> Self {
+        self.blocking = true;
+        self
+    }
+
+    pub fn id(&self) -> &'static str {
+        self.id
+    }
+
+    /// Rules run on every log that couldn't be classified, so a bad guess never hides a mistake.
+    pub fn applies_to(&self, kinds: &[LogKind]) -> bool {
+        kinds
+            .iter()
+            .any(|kind| *kind == LogKind::Unknown || self.kinds.contains(kind))
+    }
+}
+
+/// Groups of rules an [`Analyzer`] can run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleSet {
+    /// Problems with the setup or the game that we can help with.
+    Mistakes,
+    /// Where the launcher comes from, like custom, forked or pirated builds.
+    Origins,
+}
+
+/// Id of the rule pointing out launchers that the [`OriginPolicy`] doesn't fully support.
+const LAUNCHER_SUPPORT: &str = "launcher-support";
+/// Id of the rule explaining the exit code when nothing else was found.
+const EXIT_CODE: &str = "exit-code";
+
+/// A rule that made a finding the analyzer couldn't turn into a diagnosis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleError {
+    /// Id of the rule.
+    pub rule: &'static str,
+    pub error: UnknownResponse,
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rule `{}` refers to an {}", self.rule, self.error)
+    }
+}
+
+impl std::error::Error for RuleError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Runs rules on logs. [`Analyzer::new`] starts out with every built-in rule, which can be
+/// disabled by id, and custom rules can be added to either [`RuleSet`].
+#[derive(Debug, Clone)]
+pub struct Analyzer {
+    rules: Vec<(RuleSet, Rule)>,
+    sets: Vec<RuleSet>,
+    disabled: HashSet<String>,
+    policy: OriginPolicy,
+    locale: Locale,
+}
+
+impl Default for Analyzer {
+    fn default() -> Self {
+        Analyzer::new()
+    }
+}
+
+/// What an [`Analyzer`] found in a log.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub classification: Classification,
+    /// The launcher that wrote the log, if it has a launcher log header.
+    pub launcher: Option<LauncherInfo>,
+    /// How the [`OriginPolicy`] treats `launcher`.
+    pub support: Option<Support>,
+    pub facts: Facts,
+    /// Findings of all rule sets, in the order of the rules. Origins come first.
+    pub diagnoses: Vec<Diagnosis>,
+    /// How long the analysis took.
+    pub elapsed: Duration,
+}
+
+impl Report {
+    /// Whether a blocking finding, like a pirated launcher, was made.
+    pub fn is_blocked(&self) -> bool {
+        self.diagnoses.iter().any(|d| d.blocking)
+    }
+
+    /// Findings about where the launcher comes from.
+    pub fn origins(&self) -> impl Iterator<Item = &Diagnosis> {
+        self.diagnoses.iter().filter(|d| d.rule_set == RuleSet::Origins)
+    }
+
+    /// Findings about the setup or the game.
+    pub fn mistakes(&self) -> impl Iterator<Item = &Diagnosis> {
+        self.diagnoses.iter().filter(|d| d.rule_set == RuleSet::Mistakes)
+    }
+}
+
+impl Analyzer {
+    /// An analyzer running all built-in rules, treating launchers as the default
+    /// [`OriginPolicy`] says.
+    pub fn new() -> Self {
+        let mut analyzer = Analyzer::empty();
+        analyzer
+            .rules
+            .extend(ORIGINS.iter().map(|r| (RuleSet::Origins, r.clone())));
+        analyzer
+            .rules
+            .extend(PARSERS.iter().map(|r| (RuleSet::Mistakes, r.clone())));
+        analyzer
+    }
+
+    /// An analyzer without any built-in rules, for running only custom ones. The launcher
+    /// policy and exit code explanations still apply unless disabled.
+    pub fn empty() -> Self {
+        Analyzer {
+            rules: Vec::new(),
+            sets: vec![RuleSet::Origins, RuleSet::Mistakes],
+            disabled: HashSet::new(),
+            policy: OriginPolicy::default(),
+            locale: Locale::default(),
+        }
+    }
+
+    /// Only runs rules from `sets`.
+    pub fn rule_sets(&mut self, sets: &[RuleSet]) -> &mut Self {
+        self.sets = sets.to_vec();
+        self
+    }
+
+    /// Adds a rule to `set`, after the ones already there.
+    pub fn add_rule(&mut self, set: RuleSet, rule: Rule) -> &mut Self {
+        self.rules.push((set, rule));
+        self
+    }
+
+    /// Stops running the rule named `id`. The launcher policy check is named
+    /// `launcher-support` and the exit code explanation `exit-code`.
+    pub fn disable(&mut self, id: &str) -> &mut Self {
+        self.disabled.insert(id.to_string());
+        self
+    }
+
+    /// Undoes [`Analyzer::disable`].
+    pub fn enable(&mut self, id: &str) -> &mut Self {
+        self.disabled.remove(id);
+        self
+    }
+
+    /// Sets how launchers are treated, instead of the default [`OriginPolicy`].
+    pub fn policy(&mut self, policy: OriginPolicy) -> &mut Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Sets the language of the responses.
+    pub fn locale(&mut self, locale: Locale) -> &mut Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Ids of the rules that will run.
+    pub fn rule_ids(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.rules
+            .iter()
+            .filter(|(set, rule)| self.runs(*set, rule.id))
+            .map(|(_, rule)| rule.id)
+    }
+
+    /// Runs the rules on `input`. Weaker findings are suppressed in favour of the ones that
+    /// supersede or conflict with them, and confident blocking findings suppress everything
+    /// else, see [`BLOCKING_CONFIDENCE`].
+    ///
+    /// Findings of rules referring to unknown responses are logged and left out, see
+    /// [`Analyzer::try_analyze`].
+    pub fn analyze(&self, input: &str) -> Report {
+        let (report, errors) = self.report(input);
+        for why in errors {
+            error!("{}", why);
+        }
+        report
+    }
+
+    /// Like [`Analyzer::analyze`], but fails if a rule refers to an unknown response.
+    pub fn try_analyze(&self, input: &str) -> Result<Report, RuleError> {
+        match self.report(input) {
+            (report, errors) if errors.is_empty() => Ok(report),
+            (_, mut errors) => Err(errors.swap_remove(0)),
+        }
+    }
+
+    fn report(&self, input: &str) -> (Report, Vec<RuleError>) {
+        let start = Instant::now();
+        let classification = classify(input);
+        let launcher = detect_launcher(input);
+        let (diagnoses, errors) = self.diagnose_kinds(input, &[classification.kind]);
+        let report = Report {
+            diagnoses,
+            support: launcher.as_ref().map(|l| self.policy.support_for(l)),
+            launcher,
+            facts: facts(input),
+            classification,
+            elapsed: start.elapsed(),
+        };
+        (report, errors)
+    }
+
+    /// Like [`Analyzer::analyze`], for input made up of logs of all of `kinds`.
+    pub(crate) fn diagnose_kinds(&self, input: &str, kinds: &[LogKind]) -> (Vec<Diagnosis>, Vec<RuleError>) {
+        let (found, errors) = self.findings(input, kinds, |_| false);
+        let mut diagnoses = resolve(found);
+        if diagnoses.is_empty() && self.runs(RuleSet::Mistakes, EXIT_CODE) {
+            diagnoses.extend(exit_codes::exit_code_context(input, self.locale));
+        }
+        (diagnoses, errors)
+    }
+
+    /// What the rules for `kinds` and the launcher policy find in `input`, leaving out the
+    /// rules `skip` returns `true` for.
+    pub(crate) fn findings(&self, input: &str, kinds: &[LogKind], skip: impl Fn(&str) -> bool) -> (Vec<Finding>, Vec<RuleError>) {
+        let mut found = Vec::new();
+        let mut errors = Vec::new();
+        let mut run = |wanted: RuleSet, found: &mut Vec<Finding>| {
+            let rules = self.rules.iter().filter(|(set, rule)| {
+                *set == wanted && self.runs(*set, rule.id) && rule.applies_to(kinds) && !skip(rule.id)
+            });
+            for (_, rule) in rules {
+                match rule.run(input, wanted, self.locale) {
+                    Ok(finding) => found.extend(finding),
+                    Err(why) => errors.push(why),
+                }
+            }
+        };
+        run(RuleSet::Origins, &mut found);
+        if self.runs(RuleSet::Origins, LAUNCHER_SUPPORT) && !skip(LAUNCHER_SUPPORT) {
+            found.extend(launcher_support(input, &self.policy, self.locale));
+        }
+        run(RuleSet::Mistakes, &mut found);
+        (found, errors)
+    }
+
+    fn runs(&self, set: RuleSet, id: &str) -> bool {
+        self.sets.contains(&set) && !self.disabled.contains(id)
+    }
+}
+
+/// A diagnosis together with the rule that made it and its relations.
+#[derive(Debug, Clone)]
+pub(crate) struct Finding {
+    /// Id of the rule.
+    pub(crate) rule: &'static str,
+    pub(crate) diagnosis: Diagnosis,
+    supersedes: &'static [ResponseId],
+    conflicts: &'static [ResponseId],
+}
+
+impl Rule {
+    fn run(&self, input: &str, rule_set: RuleSet, locale: Locale) -> Result<Option<Finding>, RuleError> {
+        let hit = match self.check.run(input) {
+            Some(hit) => hit,
+            None => return Ok(None),
+        };
+        let response = hit.id.parse::<ResponseId>();
+        let message = match (hit.message, &response) {
+            (Some(message), _) => message,
+            (None, Ok(id)) => id.text(locale).to_string(),
+            (None, Err(error)) => {
+                return Err(RuleError {
+                    rule: self.id,
+                    error: error.clone(),
+                })
+            }
+        };
+        let actions = match (hit.actions, response) {
+            (Some(actions), _) => actions,
+            (None, Ok(id)) => default_actions(id),
+            (None, Err(_)) => Vec::new(),
+        };
+        Ok(Some(Finding {
+            rule: self.id,
+            diagnosis: Diagnosis {
+                id: hit.id,
+                rule_set,
+                severity: hit.severity,
+                message,
+                confidence: self.confidence,
+                blocking: self.blocking,
+                evidence: hit.evidence,
+                actions,
+            },
+            supersedes: self.supersedes,
+            conflicts: self.conflicts,
+        }))
+    }
+}
+
+/// Points out launchers that the policy doesn't fully support.
+fn launcher_support(input: &str, policy: &OriginPolicy, locale: Locale) -> Option<Finding> {
+    let launcher = detect_launcher(input)?;
+    let (severity, id, blocking) = match policy.support_for(&launcher) {
+        Support::Supported => return None,
+        Support::Warn => ("❗", ResponseId::UnsupportedLauncherWarning, false),
+        Support::Refuse if launcher.name == "MultiMC" => ("‼", ResponseId::UnsupportedLauncher, true),
+        Support::Refuse => ("‼", ResponseId::ForkedBuild, true),
+    };
+    Some(Finding {
+        rule: LAUNCHER_SUPPORT,
+        diagnosis: Diagnosis {
+            id: id.as_str(),
+            rule_set: RuleSet::Origins,
+            severity,
+            message: id.text(locale).to_string(),
+            confidence: 1.0,
+            blocking,
+            evidence: Vec::new(),
+            actions: default_actions(id),
+        },
+        supersedes: &[],
+        conflicts: &[],
+    })
+}
+
+/// How confident a blocking finding has to be to hide the other findings. Less confident
+/// ones, like guesses from a player's name, are reported like any other finding, since
+/// hiding the help a legitimate player needs costs more than missing a cracked launcher.
+pub const BLOCKING_CONFIDENCE: f32 = 0.8;
+
+/// Keeps only the strongest finding for each response (with the evidence of all of them),
+/// drops findings that conflict with a more confident one or that another finding
+/// supersedes, and drops everything else if a blocking finding at least as confident as
+/// [`BLOCKING_CONFIDENCE`] was made. The order of the findings is kept.
+pub(crate) fn resolve(mut found: Vec<Finding>) -> Vec<Diagnosis> {
+    for finding in &mut found {
+        if finding.diagnosis.confidence < BLOCKING_CONFIDENCE {
+            finding.diagnosis.blocking = false;
+        }
+    }
+    let blocked = found.iter().any(|f| f.diagnosis.blocking);
+    let mut by_confidence: Vec<(usize, Finding)> = found
+        .into_iter()
+        .enumerate()
+        .filter(|(_, f)| !blocked || f.diagnosis.blocking)
+        .collect();
+    // Stable, so the original order breaks ties.
+    by_confidence.sort_by(|a, b| b.1.diagnosis.confidence.total_cmp(&a.1.diagnosis.confidence));
+
+    let mut kept: Vec<(usize, Finding)> = Vec::new();
+    for (i, finding) in by_confidence {
+        let id = finding.diagnosis.id;
+        if let Some((_, same)) = kept.iter_mut().find(|(_, other)| other.diagnosis.id == id) {
+            for evidence in finding.diagnosis.evidence {
+                if !same.diagnosis.evidence.contains(&evidence) {
+                    same.diagnosis.evidence.push(evidence);
+                }
+            }
+            continue;
+        }
+        let overruled = kept.iter().any(|(_, other)| {
+            refers_to(other.conflicts, id) || refers_to(finding.conflicts, other.diagnosis.id)
+        });
+        if !overruled {
+            kept.push((i, finding));
+        }
+    }
+
+    let superseded: Vec<ResponseId> = kept
+        .iter()
+        .flat_map(|(_, f)| f.supersedes.iter().copied())
+        .collect();
+    kept.retain(|(_, f)| !refers_to(&superseded, f.diagnosis.id));
+    kept.sort_by_key(|(i, _)| *i);
+    kept.into_iter().map(|(_, f)| f.diagnosis).collect()
+}
+
+fn refers_to(responses: &[ResponseId], id: &str) -> bool {
+    responses.iter().any(|r| r.as_str() == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(id: ResponseId, confidence: f32, line: usize) -> Finding {
+        Finding {
+            rule: id.as_str(),
+            diagnosis: Diagnosis {
+                id: id.as_str(),
+                rule_set: RuleSet::Mistakes,
+                severity: "❗",
+                message: String::new(),
+                confidence,
+                blocking: false,
+                evidence: vec![Evidence {
+                    source: None,
+                    line,
+                    text: String::new(),
+                }],
+                actions: Vec::new(),
+            },
+            supersedes: &[],
+            conflicts: &[],
+        }
+    }
+
+    fn blocking(mut finding: Finding) -> Finding {
+        finding.diagnosis.blocking = true;
+        finding
+    }
+
+    fn ids(diagnoses: &[Diagnosis]) -> Vec<&str> {
+        diagnoses.iter().map(|d| d.id).collect()
+    }
+
+    #[test]
+    fn merges_findings_of_the_same_response() {
+        let resolved = resolve(vec![
+            finding(ResponseId::OutOfMemory, 0.5, 3),
+            finding(ResponseId::ProgramFiles, 1.0, 1),
+            finding(ResponseId::OutOfMemory, 1.0, 7),
+        ]);
+        assert_eq!(ids(&resolved), ["program-files", "out-of-memory"]);
+        assert_eq!(resolved[1].confidence, 1.0);
+        let lines: Vec<usize> = resolved[1].evidence.iter().map(|e| e.line).collect();
+        assert_eq!(lines, [7, 3]);
+    }
+
+    #[test]
+    fn keeps_the_more_confident_side_of_a_conflict() {
+        let mut id_limit = finding(ResponseId::IdLimit, 0.9, 1);
+        id_limit.conflicts = &[ResponseId::OutOfMemory];
+        let resolved = resolve(vec![finding(ResponseId::OutOfMemory, 0.5, 2), id_limit.clone()]);
+        assert_eq!(ids(&resolved), ["id-limit"]);
+
+        // Either side can declare the conflict
+        let mut out_of_memory = finding(ResponseId::OutOfMemory, 1.0, 2);
+        out_of_memory.conflicts = &[ResponseId::IdLimit];
+        id_limit.conflicts = &[];
+        assert_eq!(ids(&resolve(vec![id_limit, out_of_memory])), ["out-of-memory"]);
+    }
+
+    #[test]
+    fn drops_superseded_findings_whatever_their_confidence() {
+        let mut pirated = finding(ResponseId::PiratedBuild, 0.5, 5);
+        pirated.supersedes = &[ResponseId::OfflineAccount, ResponseId::ForkedBuild];
+        let resolved = resolve(vec![
+            finding(ResponseId::OfflineAccount, 1.0, 1),
+            finding(ResponseId::ProgramFiles, 1.0, 2),
+            pirated,
+            finding(ResponseId::ForkedBuild, 1.0, 9),
+        ]);
+        assert_eq!(ids(&resolved), ["program-files", "pirated-build"]);
+    }
+
+    #[test]
+    fn confident_blocking_findings_hide_the_others() {
+        let resolved = resolve(vec![
+            finding(ResponseId::ProgramFiles, 1.0, 1),
+            blocking(finding(ResponseId::M1PythonWrapper, BLOCKING_CONFIDENCE, 2)),
+            finding(ResponseId::OutOfMemory, 1.0, 3),
+            blocking(finding(ResponseId::PiratedBuild, 1.0, 4)),
+        ]);
+        assert_eq!(ids(&resolved), ["m1-python-wrapper", "pirated-build"]);
+        assert!(resolved.iter().all(|d| d.blocking));
+    }
+
+    #[test]
+    fn unconfident_blocking_findings_hide_nothing() {
+        let resolved = resolve(vec![
+            blocking(finding(ResponseId::OfflineAccount, 0.6, 1)),
+            finding(ResponseId::OutOfMemory, 1.0, 3),
+        ]);
+        assert_eq!(ids(&resolved), ["offline-account", "out-of-memory"]);
+        assert!(!resolved[0].blocking);
+    }
+}
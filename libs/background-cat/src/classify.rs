@@ -0,0 +1,295 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Serialize;
+
+/// The kind of text a user posted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogKind {
+    /// The log shown by the launcher, starting with the "MultiMC version: " header.
+    LauncherLog,
+    /// The game's own `logs/latest.log`.
+    LatestLog,
+    /// Forge's `logs/debug.log`.
+    DebugLog,
+    /// A `crash-reports/crash-*.txt` file.
+    CrashReport,
+    /// A Java fatal error log, `hs_err_pid*.log`.
+    HsErr,
+    Unknown,
+}
+
+impl LogKind {
+    /// Short description to use in a sentence, e.g. "This looks like a crash report".
+    pub fn description(&self) -> &'static str {
+        match self {
+            LogKind::LauncherLog => "a launcher log",
+            LogKind::LatestLog => "a `latest.log`",
+            LogKind::DebugLog => "a `debug.log`",
+            LogKind::CrashReport => "a crash report",
+            LogKind::HsErr => "a Java fatal error log (`hs_err_pid`)",
+            LogKind::Unknown => "an unknown file",
+        }
+    }
+}
+
+/// Result of [`classify`]. `confidence` is between 0 and 1, and always 0 for [`LogKind::Unknown`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Classification {
+    pub kind: LogKind,
+    pub confidence: f32,
+}
+
+/// Below this score a log is considered [`LogKind::Unknown`].
+const MIN_SCORE: f32 = 0.2;
+
+/// How many lines to look at when checking the line format of game logs.
+const SAMPLE_LINES: usize = 200;
+
+const LAUNCHER_MARKERS: [(&str, f32); 4] = [
+    ("Minecraft folder is:\n", 0.3),
+    ("Java path is:\n", 0.1),
+    ("Main Class:\n", 0.1),
+    ("Params:\n", 0.1),
+];
+
+const CRASH_REPORT_MARKERS: [(&str, f32); 4] = [
+    ("---- Minecraft Crash Report ----", 0.6),
+    ("A detailed walkthrough of the error, its code path and all known details is as follows:", 0.2),
+    ("\nDescription: ", 0.1),
+    ("-- System Details --", 0.1),
+];
+
+const HS_ERR_MARKERS: [(&str, f32); 4] = [
+    ("# A fatal error has been detected by the Java Runtime Environment:", 0.6),
+    ("---------------  S U M M A R Y ------------", 0.2),
+    ("---------------  T H R E A D  ---------------", 0.1),
+    ("# Problematic frame:", 0.1),
+];
+
+fn marker_score(log: &str, markers: &[(&str, f32)]) -> f32 {
+    markers
+        .iter()
+        .filter(|(marker, _)| log.contains(marker))
+        .map(|(_, weight)| weight)
+        .sum()
+}
+
+fn launcher_score(log: &str) -> f32 {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"(?m)^(MultiMC|PolyMC|ManyMC|Prism Launcher|UltimMC) version: ").unwrap();
+    }
+    let header = if RE.is_match(log) { 0.4 } else { 0.0 };
+    header + marker_score(log, &LAUNCHER_MARKERS)
+}
+
+/// Fraction of the first lines that look like `re`. Empty lines and indented stack
+/// trace lines are skipped.
+fn line_format_score(log: &str, re: &Regex) -> f32 {
+    let lines: Vec<&str> = log
+        .lines()
+        .filter(|l| !l.trim().is_empty() && !l.starts_with(char::is_whitespace))
+        .take(SAMPLE_LINES)
+        .collect();
+    if lines.is_empty() {
+        return 0.0;
+    }
+    lines.iter().filter(|l| re.is_match(l)).count() as f32 / lines.len() as f32
+}
+
+fn latest_log_score(log: &str) -> f32 {
+    lazy_static! {
+        // [12:34:56] [Render thread/INFO]: ...
+        static ref RE: Regex = Regex::new(r"^\[[0-9]{2}:[0-9]{2}:[0-9]{2}\] \[[^\]]+/[A-Z]+\]").unwrap();
+    }
+    line_format_score(log, &RE)
+}
+
+fn debug_log_score(log: &str) -> f32 {
+    lazy_static! {
+        // [14Mar2024 10:00:00.123] [main/DEBUG] [cpw.mods.modlauncher.Launcher/MODLAUNCHER]: ...
+        static ref RE: Regex =
+            Regex::new(r"^\[[0-9]{2}[A-Z][a-z]{2}[0-9]{4} [0-9]{2}:[0-9]{2}:[0-9]{2}\.[0-9]{3}\] \[[^\]]+/[A-Z]+\]").unwrap();
+    }
+    line_format_score(log, &RE)
+}
+
+/// Guesses what kind of log `log` is.
+///
+/// Launcher logs embed the game's output and often a crash report, so the launcher
+/// wins ties.
+pub fn classify(log: &str) -> Classification {
+    let scores = [
+        (LogKind::LauncherLog, launcher_score(log)),
+        (LogKind::CrashReport, marker_score(log, &CRASH_REPORT_MARKERS)),
+        (LogKind::HsErr, marker_score(log, &HS_ERR_MARKERS)),
+        (LogKind::DebugLog, debug_log_score(log)),
+        (LogKind::LatestLog, latest_log_score(log)),
+    ];
+    let (kind, score) = scores
+        .iter()
+        .fold((LogKind::Unknown, 0.0), |best, &(kind, score)| {
+            if score > best.1 {
+                (kind, score)
+            } else {
+                best
+            }
+        });
+    if score < MIN_SCORE {
+        Classification {
+            kind: LogKind::Unknown,
+            confidence: 0.0,
+        }
+    } else {
+        Classification {
+            kind,
+            confidence: score.min(1.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAUNCHER_LOG: &str = "\
+MultiMC version: 0.7.0-3275
+
+Minecraft folder is:
+C:/MultiMC/instances/1.20.1/.minecraft
+
+
+Java path is:
+C:/Program Files/Java/jdk-17/bin/javaw.exe
+
+
+Java is version 17.0.2, using 64-bit architecture.
+
+Main Class:
+  net.minecraft.client.main.Main
+
+Params:
+  --username <PROFILE NAME> --version 1.20.1 --accessToken <ACCESS TOKEN>
+
+Window size: 854 x 480
+
+[12:00:00] [main/INFO]: Loading Minecraft 1.20.1
+";
+
+    const LATEST_LOG: &str = "\
+[12:00:00] [main/INFO]: Loading Minecraft 1.20.1 with Fabric Loader 0.14.21
+[12:00:01] [main/INFO]: Loading 42 mods:
+\t- fabric-api 0.86.1+1.20.1
+[12:00:05] [Render thread/INFO]: Setting user: Steve
+[12:00:09] [Render thread/ERROR]: Failed to load texture
+java.io.FileNotFoundException: minecraft:textures/missing.png
+\tat net.minecraft.class_1060.method_4619(class_1060.java:75)
+[12:00:10] [Render thread/INFO]: Stopping!
+";
+
+    const DEBUG_LOG: &str = "\
+[14Mar2024 10:00:00.123] [main/INFO] [cpw.mods.modlauncher.Launcher/MODLAUNCHER]: ModLauncher running
+[14Mar2024 10:00:00.130] [main/DEBUG] [cpw.mods.modlauncher.LaunchServiceHandler/MODLAUNCHER]: Found launch services
+[14Mar2024 10:00:02.456] [main/INFO] [net.minecraftforge.fml.loading.FMLLoader/CORE]: Found mod file
+";
+
+    const CRASH_REPORT: &str = "\
+---- Minecraft Crash Report ----
+// Don't be sad, have a hug! <3
+
+Time: 2024-03-14 10:00:00
+Description: Initializing game
+
+java.lang.RuntimeException: Could not execute entrypoint stage 'client'
+\tat net.fabricmc.loader.impl.FabricLoaderImpl.invokeEntrypoints(FabricLoaderImpl.java:388)
+
+A detailed walkthrough of the error, its code path and all known details is as follows:
+---------------------------------------------------------------------------------------
+
+-- System Details --
+Details:
+\tMinecraft Version: 1.20.1
+";
+
+    const HS_ERR: &str = "\
+#
+# A fatal error has been detected by the Java Runtime Environment:
+#
+#  EXCEPTION_ACCESS_VIOLATION (0xc0000005) at pc=0x00007ffb1a2b3c4d, pid=1234, tid=5678
+#
+# Problematic frame:
+# C  [atio6axx.dll+0x1a2b3c]
+#
+
+---------------  S U M M A R Y ------------
+
+Command Line: -Xmx4G net.minecraft.client.main.Main
+
+---------------  T H R E A D  ---------------
+";
+
+    #[test]
+    fn classifies_each_kind_of_log() {
+        let cases = [
+            (LAUNCHER_LOG, LogKind::LauncherLog),
+            (LATEST_LOG, LogKind::LatestLog),
+            (DEBUG_LOG, LogKind::DebugLog),
+            (CRASH_REPORT, LogKind::CrashReport),
+            (HS_ERR, LogKind::HsErr),
+        ];
+        for (log, kind) in cases {
+            let classification = classify(log);
+            assert_eq!(classification.kind, kind);
+            // The exception message in the latest.log isn't a log line
+            assert!(classification.confidence >= 0.8, "{:?}: {}", kind, classification.confidence);
+        }
+    }
+
+    #[test]
+    fn knows_the_launcher_headers_of_forks() {
+        let prism = LAUNCHER_LOG.replace("MultiMC version: 0.7.0-3275", "Prism Launcher version: 8.2");
+        assert_eq!(classify(&prism).kind, LogKind::LauncherLog);
+    }
+
+    #[test]
+    fn doesnt_guess_at_other_text() {
+        for text in ["", "my game crashed, help", "Params:\n", "{\"components\": []}"] {
+            assert_eq!(
+                classify(text),
+                Classification {
+                    kind: LogKind::Unknown,
+                    confidence: 0.0
+                },
+                "{:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn prefers_the_launcher_log_when_it_contains_others() {
+        let mixed = format!("{}{}\n{}", LAUNCHER_LOG, LATEST_LOG, CRASH_REPORT);
+        assert_eq!(classify(&mixed).kind, LogKind::LauncherLog);
+    }
+
+    #[test]
+    fn prefers_the_crash_report_over_log_lines_pasted_with_it() {
+        let mixed = format!("{}\n{}", &LATEST_LOG[..LATEST_LOG.find("[12:00:05]").unwrap()], CRASH_REPORT);
+        assert_eq!(classify(&mixed).kind, LogKind::CrashReport);
+    }
+
+    #[test]
+    fn is_less_confident_about_partial_logs() {
+        // Only the game folder header of a launcher log
+        let header = classify("Minecraft folder is:\nC:/MultiMC/instances/1.20.1/.minecraft\n");
+        assert_eq!(header.kind, LogKind::LauncherLog);
+        assert!(header.confidence < 0.5);
+
+        // A latest.log with chat messages and other lines pasted in between
+        let interrupted = format!("{}<Steve> help\nit crashed again\nsee above\n", LATEST_LOG);
+        let classification = classify(&interrupted);
+        assert_eq!(classification.kind, LogKind::LatestLog);
+        assert!(classification.confidence < 1.0 && classification.confidence > 0.5);
+    }
+}
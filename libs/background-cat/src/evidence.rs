@@ -0,0 +1,35 @@
+use regex::Regex;
+use serde::Serialize;
+
+/// A line of the input that made a rule match, so people can verify the finding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Evidence {
+    /// Name of the input the line is from, when several inputs were analyzed together.
+    pub source: Option<String>,
+    /// 1-based line number.
+    pub line: usize,
+    pub text: String,
+}
+
+impl Evidence {
+    /// The line containing the byte at `offset`.
+    pub fn at(log: &str, offset: usize) -> Evidence {
+        let start = log[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let end = log[offset..].find('\n').map_or(log.len(), |i| offset + i);
+        Evidence {
+            source: None,
+            line: log[..start].matches('\n').count() + 1,
+            text: log[start..end].trim_end_matches('\r').to_string(),
+        }
+    }
+
+    /// The first line containing `needle`.
+    pub fn find(log: &str, needle: &str) -> Option<Evidence> {
+        Some(Evidence::at(log, log.find(needle)?))
+    }
+
+    /// The first line matching `re`.
+    pub fn find_re(log: &str, re: &Regex) -> Option<Evidence> {
+        Some(Evidence::at(log, re.find(log)?.start()))
+    }
+}
@@ -0,0 +1,282 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{Diagnosis, Evidence, Locale, ResponseId, RuleSet};
+
+/// How the game process ended, as reported by the last exit line of a MultiMC log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExitStatus {
+    pub code: i64,
+    /// MultiMC says "crashed" instead of "exited" when the process was killed by a signal
+    /// or terminated abnormally.
+    pub crashed: bool,
+}
+
+/// A known exit code together with a short name and the response explaining it to users.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExitCode {
+    pub code: i64,
+    pub name: &'static str,
+    pub explanation: ResponseId,
+}
+
+/// Windows NTSTATUS codes, as printed by MultiMC (signed 32-bit).
+const WINDOWS_CODES: [ExitCode; 12] = [
+    ExitCode {
+        code: -1073741819,
+        name: "STATUS_ACCESS_VIOLATION",
+        explanation: ResponseId::ExitAccessViolation,
+    },
+    ExitCode {
+        code: -1073741571,
+        name: "STATUS_STACK_OVERFLOW",
+        explanation: ResponseId::ExitStackOverflow,
+    },
+    ExitCode {
+        code: -1073741515,
+        name: "STATUS_DLL_NOT_FOUND",
+        explanation: ResponseId::ExitDllNotFound,
+    },
+    ExitCode {
+        code: -1073741511,
+        name: "STATUS_ENTRYPOINT_NOT_FOUND",
+        explanation: ResponseId::ExitEntrypointNotFound,
+    },
+    ExitCode {
+        code: -1073741502,
+        name: "STATUS_DLL_INIT_FAILED",
+        explanation: ResponseId::ExitDllInitFailed,
+    },
+    ExitCode {
+        code: -1073740791,
+        name: "STATUS_STACK_BUFFER_OVERRUN",
+        explanation: ResponseId::ExitStackBufferOverrun,
+    },
+    ExitCode {
+        code: -1073741795,
+        name: "STATUS_ILLEGAL_INSTRUCTION",
+        explanation: ResponseId::ExitIllegalInstruction,
+    },
+    ExitCode {
+        code: -1073740940,
+        name: "STATUS_HEAP_CORRUPTION",
+        explanation: ResponseId::ExitHeapCorruption,
+    },
+    ExitCode {
+        code: -1073741801,
+        name: "STATUS_NO_MEMORY",
+        explanation: ResponseId::ExitNoMemory,
+    },
+    ExitCode {
+        code: -1073741510,
+        name: "STATUS_CONTROL_C_EXIT",
+        explanation: ResponseId::ExitControlC,
+    },
+    ExitCode {
+        code: 1073807364,
+        name: "DBG_TERMINATE_PROCESS",
+        explanation: ResponseId::ExitKilledFromOutside,
+    },
+    ExitCode {
+        code: -805306369,
+        name: "Not responding",
+        explanation: ResponseId::ExitNotResponding,
+    },
+];
+
+/// POSIX signals, as reported by MultiMC when the process crashed.
+const SIGNALS: [ExitCode; 6] = [
+    ExitCode {
+        code: 4,
+        name: "SIGILL",
+        explanation: ResponseId::ExitIllegalInstruction,
+    },
+    ExitCode {
+        code: 6,
+        name: "SIGABRT",
+        explanation: ResponseId::ExitAborted,
+    },
+    ExitCode {
+        code: 7,
+        name: "SIGBUS",
+        explanation: ResponseId::ExitBusError,
+    },
+    ExitCode {
+        code: 9,
+        name: "SIGKILL",
+        explanation: ResponseId::ExitKilled,
+    },
+    ExitCode {
+        code: 11,
+        name: "SIGSEGV",
+        explanation: ResponseId::ExitNativeCrash,
+    },
+    ExitCode {
+        code: 15,
+        name: "SIGTERM",
+        explanation: ResponseId::ExitTerminated,
+    },
+];
+
+/// Exit codes of the Java process itself. Codes above 128 are how shells report signals.
+const JVM_CODES: [ExitCode; 6] = [
+    ExitCode {
+        code: 1,
+        name: "Generic error",
+        explanation: ResponseId::ExitJavaError,
+    },
+    ExitCode {
+        code: -1,
+        name: "Generic error",
+        explanation: ResponseId::ExitGameError,
+    },
+    ExitCode {
+        code: 134,
+        name: "SIGABRT",
+        explanation: ResponseId::ExitAborted,
+    },
+    ExitCode {
+        code: 137,
+        name: "SIGKILL",
+        explanation: ResponseId::ExitKilled,
+    },
+    ExitCode {
+        code: 139,
+        name: "SIGSEGV",
+        explanation: ResponseId::ExitNativeCrash,
+    },
+    ExitCode {
+        code: 143,
+        name: "SIGTERM",
+        explanation: ResponseId::ExitTerminated,
+    },
+];
+
+lazy_static! {
+    static ref RE_EXIT: Regex = Regex::new(r"Process (crashed with exit ?code|exited with code) (-?[0-9]+)").unwrap();
+}
+
+/// The last exit line MultiMC printed, and where it starts.
+fn last_exit(log: &str) -> Option<(ExitStatus, usize)> {
+    let captures = RE_EXIT.captures_iter(log).last()?;
+    let status = ExitStatus {
+        code: captures[2].parse().ok()?,
+        crashed: captures[1].starts_with("crashed"),
+    };
+    Some((status, captures.get(0)?.start()))
+}
+
+/// Finds the last exit line MultiMC printed, e.g. "Process crashed with exitcode -1073741819."
+pub fn exit_status(log: &str) -> Option<ExitStatus> {
+    last_exit(log).map(|(status, _)| status)
+}
+
+/// NTSTATUS codes are 32-bit, and launchers print them signed (-1073741819) or unsigned
+/// (3221225477). The table has them signed.
+fn as_ntstatus(code: i64) -> i64 {
+    match u32::try_from(code) {
+        Ok(code) => code as i32 as i64,
+        Err(_) => code,
+    }
+}
+
+/// Looks up an explanation for an exit status. Signals are only considered for crashes,
+/// since a normal exit with code 9 has nothing to do with SIGKILL.
+pub fn explain(status: ExitStatus) -> Option<&'static ExitCode> {
+    let signals: &[ExitCode] = if status.crashed { &SIGNALS } else { &[] };
+    WINDOWS_CODES
+        .iter()
+        .find(|c| c.code == as_ntstatus(status.code))
+        .or_else(|| signals.iter().chain(JVM_CODES.iter()).find(|c| c.code == status.code))
+}
+
+/// Detector that explains the exit code of the game, in `locale`. Only meant to add context
+/// when no other rule matched.
+pub(crate) fn exit_code_context(log: &str, locale: Locale) -> Option<Diagnosis> {
+    let (status, offset) = last_exit(log)?;
+    let known = explain(status)?;
+    let response = if status.crashed { ResponseId::CrashExitCode } else { ResponseId::ExitCode };
+    let message = response.format(
+        locale,
+        &[
+            ("code", &status.code.to_string()),
+            ("name", known.name),
+            ("explanation", known.explanation.text(locale)),
+        ],
+    );
+    Some(Diagnosis {
+        id: response.as_str(),
+        rule_set: RuleSet::Mistakes,
+        severity: "ℹ",
+        message,
+        confidence: 0.3,
+        blocking: false,
+        evidence: vec![Evidence::at(log, offset)],
+        actions: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exited(code: i64) -> ExitStatus {
+        ExitStatus { code, crashed: false }
+    }
+
+    fn crashed(code: i64) -> ExitStatus {
+        ExitStatus { code, crashed: true }
+    }
+
+    #[test]
+    fn finds_the_last_exit_line() {
+        let log = "Process exited with code 1.\nRestarting\nProcess crashed with exitcode -1073741819.\n";
+        assert_eq!(exit_status(log), Some(crashed(-1073741819)));
+        assert_eq!(exit_status("Process crashed with exit code 139"), Some(crashed(139)));
+        assert_eq!(exit_status("Process exited with code 0."), Some(exited(0)));
+        assert_eq!(exit_status("[main/INFO]: Stopping!"), None);
+    }
+
+    #[test]
+    fn looks_up_ntstatus_codes_signed_and_unsigned() {
+        let cases = [
+            (-1073741819, "STATUS_ACCESS_VIOLATION"),
+            (3221225477, "STATUS_ACCESS_VIOLATION"),
+            (-1073740791, "STATUS_STACK_BUFFER_OVERRUN"),
+            (3221226505, "STATUS_STACK_BUFFER_OVERRUN"),
+            (-805306369, "Not responding"),
+            (3489660927, "Not responding"),
+            (1073807364, "DBG_TERMINATE_PROCESS"),
+        ];
+        for (code, name) in cases {
+            assert_eq!(explain(exited(code)).map(|c| c.name), Some(name), "{}", code);
+            assert_eq!(explain(crashed(code)).map(|c| c.name), Some(name), "{}", code);
+        }
+        // Out of the 32-bit range, so not an NTSTATUS
+        assert_eq!(explain(exited(3221225477 + (1 << 32))), None);
+    }
+
+    #[test]
+    fn only_takes_signals_for_crashes() {
+        assert_eq!(explain(crashed(9)).map(|c| c.name), Some("SIGKILL"));
+        assert_eq!(explain(exited(9)), None);
+        assert_eq!(explain(exited(137)).map(|c| c.name), Some("SIGKILL"));
+        assert_eq!(explain(exited(1)).map(|c| c.explanation), Some(ResponseId::ExitJavaError));
+    }
+
+    #[test]
+    fn explains_the_exit_line() {
+        let log = "[12:00:00] [Render thread/INFO]: Stopping!\nProcess crashed with exitcode 3221225477.\n";
+        let diagnosis = exit_code_context(log, Locale::En).unwrap();
+        assert_eq!(diagnosis.id, "crash-exit-code");
+        assert!(diagnosis
+            .message
+            .starts_with("The game crashed with code 3221225477 (STATUS_ACCESS_VIOLATION). The game tried"));
+        assert_eq!(diagnosis.evidence.len(), 1);
+        assert_eq!(diagnosis.evidence[0].line, 2);
+        assert_eq!(diagnosis.evidence[0].text, "Process crashed with exitcode 3221225477.");
+
+        assert_eq!(exit_code_context("Process exited with code 1.", Locale::En).unwrap().id, "exit-code");
+        assert!(exit_code_context("Process exited with code 42.", Locale::En).is_none());
+    }
+}
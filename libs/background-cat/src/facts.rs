@@ -0,0 +1,151 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Serialize;
+
+/// What a log says about the setup it comes from, mostly from the launcher log header or the
+/// system details of a crash report. Facts a log doesn't mention are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Facts {
+    pub minecraft_version: Option<String>,
+    pub loader: Option<Loader>,
+    /// Without build suffixes like "+8" or "-b11".
+    pub java_version: Option<String>,
+    pub java_path: Option<String>,
+    pub os: Option<String>,
+    /// The `-Xmx` the game was started with, in MiB.
+    pub max_memory: Option<u64>,
+    pub minecraft_folder: Option<String>,
+}
+
+/// A mod loader and its version, e.g. Fabric Loader 0.15.7.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Loader {
+    pub name: &'static str,
+    pub version: String,
+}
+
+pub fn facts(log: &str) -> Facts {
+    Facts {
+        minecraft_version: minecraft_version(log),
+        loader: loader(log),
+        java_version: java_version(log),
+        java_path: line_after(log, "Java path is:\n"),
+        os: os(log),
+        max_memory: max_memory(log),
+        minecraft_folder: line_after(log, "Minecraft folder is:\n"),
+    }
+}
+
+/// The line following `header`, as in the launcher log's "Minecraft folder is:\n<folder>".
+fn line_after(log: &str, header: &str) -> Option<String> {
+    let start = log.find(header)? + header.len();
+    let line = log[start..].lines().next()?.trim();
+    if line.is_empty() {
+        None
+    } else {
+        Some(line.to_string())
+    }
+}
+
+/// The first capture of the first of `res` that matches.
+fn first_capture(log: &str, res: &[&Regex]) -> Option<String> {
+    res.iter()
+        .find_map(|re| re.captures(log))
+        .map(|c| c[1].trim().to_string())
+}
+
+fn minecraft_version(log: &str) -> Option<String> {
+    lazy_static! {
+        static ref CRASH_REPORT: Regex = Regex::new(r"(?m)^\s*Minecraft Version: (\S+)").unwrap();
+        static ref FABRIC: Regex = Regex::new(r"Loading Minecraft (\S+) with").unwrap();
+        static ref FORGE: Regex = Regex::new(r"--fml\.mcVersion,? ([^,\s]+)").unwrap();
+        static ref LIBRARIES: Regex =
+            Regex::new(r"com/mojang/minecraft/([^/\s]+)/minecraft-[^/\s]+\.jar").unwrap();
+    }
+    first_capture(log, &[&CRASH_REPORT, &FABRIC, &FORGE, &LIBRARIES])
+}
+
+fn loader(log: &str) -> Option<Loader> {
+    lazy_static! {
+        static ref LOADERS: [(&'static str, Regex); 4] = [
+            (
+                "NeoForge",
+                Regex::new(r"net/neoforged/neoforge/([^/\s]+)/|--fml\.neoForgeVersion,? ([^,\s]+)").unwrap(),
+            ),
+            (
+                "Forge",
+                Regex::new(r"net/minecraftforge/forge/[^-/\s]+-([^/\s]+)/|--fml\.forgeVersion,? ([^,\s]+)").unwrap(),
+            ),
+            (
+                "Quilt Loader",
+                Regex::new(r"org/quiltmc/quilt-loader/([^/\s]+)/|Loading Minecraft \S+ with Quilt Loader (\S+)").unwrap(),
+            ),
+            (
+                "Fabric Loader",
+                Regex::new(r"net/fabricmc/fabric-loader/([^/\s]+)/|Loading Minecraft \S+ with Fabric Loader (\S+)").unwrap(),
+            ),
+        ];
+    }
+    LOADERS.iter().find_map(|(name, re)| {
+        let c = re.captures(log)?;
+        let version = c.get(1).or_else(|| c.get(2))?;
+        Some(Loader {
+            name,
+            version: version.as_str().to_string(),
+        })
+    })
+}
+
+/// Extracts the Java version from a launcher log, crash report or `hs_err_pid` file.
+pub(crate) fn java_version(log: &str) -> Option<String> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"(?:Java is version |Java Version: |# JRE version: .*\(build )([0-9][0-9._]*)"
+        )
+        .unwrap();
+    }
+    Some(RE.captures(log)?[1].trim_end_matches('.').to_string())
+}
+
+fn os(log: &str) -> Option<String> {
+    lazy_static! {
+        static ref CRASH_REPORT: Regex = Regex::new(r"(?m)^\s*Operating System: (.+)$").unwrap();
+        static ref HS_ERR: Regex = Regex::new(r"(?m)^OS: *(\S.*)$").unwrap();
+    }
+    first_capture(log, &[&CRASH_REPORT, &HS_ERR])
+}
+
+pub(crate) fn max_memory(log: &str) -> Option<u64> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"-Xmx([0-9]+)([kKmMgG]?)\b").unwrap();
+    }
+    let c = RE.captures(log)?;
+    let amount: u64 = c[1].parse().ok()?;
+    Some(match &c[2] {
+        "k" | "K" => amount / 1024,
+        "g" | "G" => amount.checked_mul(1024)?,
+        // Without a unit it's bytes
+        "" => amount / (1024 * 1024),
+        _ => amount,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_max_memory_in_mib() {
+        assert_eq!(max_memory("-Xms512m -Xmx4G"), Some(4096));
+        assert_eq!(max_memory("-Xmx2048m"), Some(2048));
+        assert_eq!(max_memory("-Xmx1048576k"), Some(1024));
+        assert_eq!(max_memory("-Xmx1073741824"), Some(1024));
+        assert_eq!(max_memory("-Xms512m"), None);
+    }
+
+    #[test]
+    fn ignores_max_memory_too_large_to_count() {
+        assert_eq!(max_memory(&format!("-Xmx{}G", u64::MAX / 1000)), None);
+        assert_eq!(max_memory("-Xmx99999999999999999999999G"), None);
+    }
+}
@@ -0,0 +1,407 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    instance::{backup, java_major, Instance, InstanceConfig},
+    mods::ModJar,
+    Action, Diagnosis,
+};
+
+/// A change to an instance that carries out an [`Action`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// Set a key in `instance.cfg`.
+    SetConfig { key: &'static str, value: String },
+    /// Move a mod jar into the [`DISABLED_MODS_DIR`].
+    DisableMod(PathBuf),
+}
+
+/// Folder next to `mods` that disabled mods are moved to, so they can be put back by hand.
+pub const DISABLED_MODS_DIR: &str = "mods-disabled";
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::SetConfig { key, value } => write!(f, "Set `{}` to `{}`", key, value),
+            Change::DisableMod(path) => write!(
+                f,
+                "Move `{}` to `{}`",
+                path.file_name().unwrap_or_default().to_string_lossy(),
+                DISABLED_MODS_DIR
+            ),
+        }
+    }
+}
+
+/// What [`plan`] would do to an instance.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FixPlan {
+    pub changes: Vec<Change>,
+    /// Actions that have to be done by hand, like moving the launcher or installing a mod.
+    pub manual: Vec<Action>,
+}
+
+impl FixPlan {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.manual.is_empty()
+    }
+}
+
+/// Works out the changes to `instance` that carry out the actions of `diagnoses`.
+pub fn plan(instance: &Instance, diagnoses: &[Diagnosis]) -> io::Result<FixPlan> {
+    let mut plan = FixPlan::default();
+    // Overriding the arguments replaces the launcher's global ones, so those are kept. If
+    // they can't be found, the argument has to be added by hand.
+    let mut jvm_args = if instance.config.is_set("OverrideJavaArgs") {
+        Some(instance.config.get("JvmArgs").unwrap_or_default().to_string())
+    } else {
+        instance
+            .launcher_config()?
+            .map(|launcher| launcher.get("JvmArgs").unwrap_or_default().to_string())
+    };
+    let jars = match instance.mod_jars() {
+        Ok(jars) => jars,
+        Err(why) if why.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(why) => return Err(why),
+    };
+    // The ids of the mods in each jar, if its metadata can be read
+    let jars: Vec<(PathBuf, Option<Vec<String>>)> = jars
+        .into_iter()
+        .map(|path| {
+            let ids = ModJar::read(&path)
+                .ok()
+                .map(|jar| jar.mods.into_iter().filter(|m| !m.bundled).map(|m| m.id).collect());
+            (path, ids)
+        })
+        .collect();
+
+    for action in diagnoses.iter().flat_map(|d| &d.actions) {
+        match action {
+            Action::SetJvmArg(arg) => match &mut jvm_args {
+                Some(jvm_args) => {
+                    if !jvm_args.split_whitespace().any(|a| a == arg) {
+                        *jvm_args = format!("{} {}", jvm_args, arg).trim().to_string();
+                        set(&mut plan, "OverrideJavaArgs", "true");
+                        set(&mut plan, "JvmArgs", jvm_args);
+                    }
+                }
+                None => manual(&mut plan, action),
+            },
+            Action::SetMaxMemory(mib) => {
+                let current = instance.config.get("MaxMemAlloc").and_then(|m| m.parse().ok());
+                if !instance.config.is_set("OverrideMemory") || current.is_none_or(|c: u64| c < *mib) {
+                    set(&mut plan, "OverrideMemory", "true");
+                    set(&mut plan, "MaxMemAlloc", &mib.to_string());
+                }
+            }
+            Action::RequireJava(major) => match find_java(*major, &java_roots(instance)) {
+                Some(java) => {
+                    set(&mut plan, "OverrideJavaLocation", "true");
+                    set(&mut plan, "JavaPath", &java.to_string_lossy());
+                }
+                None => plan.manual.push(action.clone()),
+            },
+            Action::RemoveMod(id) => {
+                for (jar, _) in jars.iter().filter(|(jar, ids)| is_mod(id, jar, ids.as_deref())) {
+                    let change = Change::DisableMod(jar.clone());
+                    if !plan.changes.contains(&change) {
+                        plan.changes.push(change);
+                    }
+                }
+            }
+            Action::InstallMod(_) | Action::MoveInstall | Action::Reboot => manual(&mut plan, action),
+        }
+    }
+    Ok(plan)
+}
+
+fn manual(plan: &mut FixPlan, action: &Action) {
+    if !plan.manual.contains(action) {
+        plan.manual.push(action.clone());
+    }
+}
+
+/// Whether a jar is the mod `id`: one of the mods in its metadata has the id, or, for jars
+/// whose metadata can't be read, the file name starts with the id, like `shadersmod` in
+/// `ShadersMod-v2.4.12.jar` but not in `notshadersmod.jar` or an addon like
+/// `better-shadersmod-addon.jar`.
+fn is_mod(id: &str, jar: &Path, jar_ids: Option<&[String]>) -> bool {
+    match jar_ids {
+        Some(ids) => ids.iter().any(|i| i.eq_ignore_ascii_case(id)),
+        None => {
+            let name = name_tokens(&jar.file_name().unwrap_or_default().to_string_lossy());
+            let id = name_tokens(id);
+            !id.is_empty() && name.starts_with(&id)
+        }
+    }
+}
+
+fn name_tokens(name: &str) -> Vec<String> {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Sets `key`, replacing an earlier change of the same key.
+fn set(plan: &mut FixPlan, key: &'static str, value: &str) {
+    plan.changes
+        .retain(|c| !matches!(c, Change::SetConfig { key: k, .. } if *k == key));
+    plan.changes.push(Change::SetConfig {
+        key,
+        value: value.to_string(),
+    });
+}
+
+/// What [`apply`] did to an instance, so it can be undone with [`undo`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Applied {
+    /// The copy of `instance.cfg` from before, if the config was changed.
+    pub backup: Option<PathBuf>,
+    /// Mod jars that were moved, from where to where.
+    pub moved: Vec<(PathBuf, PathBuf)>,
+}
+
+/// Makes the changes of `plan`. `instance.cfg` is backed up before anything is changed. If
+/// a change fails, the ones made before it are undone, so the instance is either fixed or
+/// left as it was.
+pub fn apply(instance: &mut Instance, plan: &FixPlan) -> io::Result<Applied> {
+    let mut config = instance.config.clone();
+    for change in &plan.changes {
+        if let Change::SetConfig { key, value } = change {
+            config.set(key, value);
+        }
+    }
+    let mut applied = Applied::default();
+    if config != instance.config {
+        applied.backup = Some(backup(&instance.config_path())?);
+    }
+
+    let result = (|| {
+        for change in &plan.changes {
+            if let Change::DisableMod(jar) = change {
+                let disabled = instance.minecraft_dir().join(DISABLED_MODS_DIR);
+                fs::create_dir_all(&disabled)?;
+                let target = disabled.join(jar.file_name().unwrap_or_default());
+                // Renaming would replace a jar disabled earlier
+                if target.exists() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AlreadyExists,
+                        format!("{} already exists", target.display()),
+                    ));
+                }
+                fs::rename(jar, &target)?;
+                applied.moved.push((jar.clone(), target));
+            }
+        }
+        if applied.backup.is_some() {
+            fs::write(instance.config_path(), config.to_string())?;
+        }
+        Ok(())
+    })();
+    if let Err(why) = result {
+        // The error that made it stop is the one worth reporting
+        let _ = undo(instance, &applied);
+        return Err(why);
+    }
+    instance.config = config;
+    Ok(applied)
+}
+
+/// Puts back what [`apply`] did: moves the mods back and restores `instance.cfg` from the
+/// backup.
+pub fn undo(instance: &mut Instance, applied: &Applied) -> io::Result<()> {
+    for (from, to) in applied.moved.iter().rev() {
+        fs::rename(to, from)?;
+    }
+    if let Some(backup) = &applied.backup {
+        fs::copy(backup, instance.config_path())?;
+        let content = fs::read_to_string(instance.config_path())?;
+        instance.config = InstanceConfig::parse(&content);
+    }
+    Ok(())
+}
+
+/// Folders that Java installations are usually found in: the launcher's own `java` folder
+/// and the default install locations of the common distributions.
+fn java_roots(instance: &Instance) -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    // <launcher>/instances/<instance>
+    if let Some(launcher) = instance.dir.parent().and_then(Path::parent) {
+        roots.push(launcher.join("java"));
+    }
+    roots.extend(
+        [
+            "/usr/lib/jvm",
+            "/Library/Java/JavaVirtualMachines",
+            "C:/Program Files/Java",
+            "C:/Program Files/Eclipse Adoptium",
+            "C:/Program Files/Microsoft",
+            "C:/Program Files/Zulu",
+        ]
+        .iter()
+        .map(PathBuf::from),
+    );
+    roots
+}
+
+/// Looks for a Java installation with the major version `major` in `roots`, earlier roots
+/// first, and returns its `java` executable (`javaw.exe` on Windows).
+pub fn find_java(major: u32, roots: &[PathBuf]) -> Option<PathBuf> {
+    roots.iter().find_map(|root| {
+        let mut homes: Vec<PathBuf> = fs::read_dir(root)
+            .ok()?
+            .filter_map(|e| e.ok())
+            .flat_map(|e| [e.path().join("Contents").join("Home"), e.path()])
+            .filter(|home| java_major(home) == Some(major))
+            .collect();
+        // The last one by name is usually the newest update.
+        homes.sort();
+        let home = homes.pop()?;
+        ["javaw.exe", "java.exe", "java"]
+            .iter()
+            .map(|exe| home.join("bin").join(exe))
+            .find(|exe| exe.exists())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use tempfile::TempDir;
+    use zip::{write::FileOptions, ZipWriter};
+
+    use super::*;
+    use crate::instance::instance_diagnosis;
+
+    /// A launcher folder with the instance `test`, with `config` as its `instance.cfg`.
+    fn launcher(config: &str, launcher_config: Option<&str>) -> (TempDir, PathBuf) {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("instances").join("test");
+        fs::create_dir_all(dir.join(".minecraft").join("mods")).unwrap();
+        fs::write(dir.join("instance.cfg"), config).unwrap();
+        if let Some(launcher_config) = launcher_config {
+            fs::write(root.path().join("multimc.cfg"), launcher_config).unwrap();
+        }
+        (root, dir)
+    }
+
+    fn add_jar(dir: &Path, name: &str, id: Option<&str>) {
+        let content = match id {
+            Some(id) => {
+                let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+                zip.start_file("fabric.mod.json", FileOptions::default()).unwrap();
+                write!(zip, r#"{{"id": "{}"}}"#, id).unwrap();
+                zip.finish().unwrap().into_inner()
+            }
+            None => b"not a zip".to_vec(),
+        };
+        fs::write(dir.join(".minecraft").join("mods").join(name), content).unwrap();
+    }
+
+    fn diagnosis(action: Action) -> Diagnosis {
+        instance_diagnosis("test", "!", String::new(), Some(action))
+    }
+
+    fn disabled(dir: &Path, name: &str) -> Change {
+        Change::DisableMod(dir.join(".minecraft").join("mods").join(name))
+    }
+
+    #[test]
+    fn keeps_the_global_jvm_arguments() {
+        let (_root, dir) = launcher("name=test\n", Some("JvmArgs=-Xss4m -Dfoo=bar\n"));
+        let instance = Instance::open(&dir).unwrap();
+        let global = plan(&instance, &[diagnosis(Action::SetJvmArg("-Dfml.earlyprogresswindow=false".to_string()))]).unwrap();
+        assert_eq!(
+            global.changes,
+            [
+                Change::SetConfig { key: "OverrideJavaArgs", value: "true".to_string() },
+                Change::SetConfig { key: "JvmArgs", value: "-Xss4m -Dfoo=bar -Dfml.earlyprogresswindow=false".to_string() },
+            ]
+        );
+
+        let (_root, dir) = launcher("OverrideJavaArgs=true\nJvmArgs=-Dmine\n", Some("JvmArgs=-Dglobal\n"));
+        let instance = Instance::open(&dir).unwrap();
+        let plan = plan(&instance, &[diagnosis(Action::SetJvmArg("-Dnew".to_string()))]).unwrap();
+        assert!(plan.changes.contains(&Change::SetConfig { key: "JvmArgs", value: "-Dmine -Dnew".to_string() }));
+    }
+
+    #[test]
+    fn leaves_jvm_arguments_to_the_user_without_the_launcher_config() {
+        let (_root, dir) = launcher("name=test\n", None);
+        let instance = Instance::open(&dir).unwrap();
+        let action = Action::SetJvmArg("-Dnew".to_string());
+        let plan = plan(&instance, &[diagnosis(action.clone())]).unwrap();
+        assert_eq!(plan.changes, []);
+        assert_eq!(plan.manual, [action]);
+    }
+
+    #[test]
+    fn removes_mods_by_id() {
+        let (_root, dir) = launcher("name=test\n", None);
+        add_jar(&dir, "ok-1.0.jar", Some("ok"));
+        add_jar(&dir, "okhttp-4.9.jar", Some("okhttp"));
+        add_jar(&dir, "lookup.jar", None);
+        add_jar(&dir, "broken-ok-addon.jar", None);
+        add_jar(&dir, "ok-2.0.jar", None);
+        let instance = Instance::open(&dir).unwrap();
+
+        let plan = plan(&instance, &[diagnosis(Action::RemoveMod("ok".to_string()))]).unwrap();
+        assert_eq!(plan.changes, [disabled(&dir, "ok-1.0.jar"), disabled(&dir, "ok-2.0.jar")]);
+    }
+
+    #[test]
+    fn applies_and_undoes_changes() {
+        let (_root, dir) = launcher("name=test\nMaxMemAlloc=1024\n", None);
+        add_jar(&dir, "shadersmod.jar", None);
+        let mut instance = Instance::open(&dir).unwrap();
+        let plan = plan(
+            &instance,
+            &[diagnosis(Action::SetMaxMemory(4096)), diagnosis(Action::RemoveMod("shadersmod".to_string()))],
+        )
+        .unwrap();
+
+        let applied = apply(&mut instance, &plan).unwrap();
+        let backup = applied.backup.clone().unwrap();
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "name=test\nMaxMemAlloc=1024\n");
+        assert_eq!(instance.config.get("MaxMemAlloc"), Some("4096"));
+        assert!(fs::read_to_string(dir.join("instance.cfg")).unwrap().contains("MaxMemAlloc=4096"));
+        let moved_to = dir.join(".minecraft").join(DISABLED_MODS_DIR).join("shadersmod.jar");
+        assert_eq!(applied.moved, [(dir.join(".minecraft/mods/shadersmod.jar"), moved_to.clone())]);
+        assert!(moved_to.exists());
+
+        undo(&mut instance, &applied).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("instance.cfg")).unwrap(), "name=test\nMaxMemAlloc=1024\n");
+        assert_eq!(instance.config.get("MaxMemAlloc"), Some("1024"));
+        assert!(dir.join(".minecraft/mods/shadersmod.jar").exists());
+        assert!(!moved_to.exists());
+    }
+
+    #[test]
+    fn leaves_the_instance_as_it_was_when_a_change_fails() {
+        let (_root, dir) = launcher("name=test\n", None);
+        add_jar(&dir, "bad-a.jar", None);
+        add_jar(&dir, "bad-b.jar", None);
+        let disabled_dir = dir.join(".minecraft").join(DISABLED_MODS_DIR);
+        fs::create_dir_all(&disabled_dir).unwrap();
+        fs::write(disabled_dir.join("bad-b.jar"), "disabled earlier").unwrap();
+        let mut instance = Instance::open(&dir).unwrap();
+        let plan = plan(
+            &instance,
+            &[diagnosis(Action::SetMaxMemory(4096)), diagnosis(Action::RemoveMod("bad".to_string()))],
+        )
+        .unwrap();
+        assert_eq!(plan.changes.len(), 4);
+
+        assert_eq!(apply(&mut instance, &plan).unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+        assert!(dir.join(".minecraft/mods/bad-a.jar").exists());
+        assert!(!disabled_dir.join("bad-a.jar").exists());
+        assert_eq!(fs::read_to_string(disabled_dir.join("bad-b.jar")).unwrap(), "disabled earlier");
+        assert_eq!(fs::read_to_string(dir.join("instance.cfg")).unwrap(), "name=test\n");
+        assert_eq!(instance.config.get("MaxMemAlloc"), None);
+    }
+}
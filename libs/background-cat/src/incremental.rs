@@ -0,0 +1,166 @@
+//! Analyzing a log while it's being written, e.g. by a launcher running the game.
+
+use std::collections::VecDeque;
+
+use log::error;
+
+use crate::{
+    analyzer::{resolve, Finding},
+    classify, Analyzer, Diagnosis, LogKind,
+};
+
+/// Lines the rules see at once, enough for triggers spanning several lines like the
+/// launcher's "Minecraft folder is:" followed by the path.
+const WINDOW_LINES: usize = 16;
+/// Lines at the start of a log that decide what kind of log it is. Rules for every kind run
+/// until it's known.
+const CLASSIFY_LINES: usize = 50;
+
+/// Runs an [`Analyzer`] on a log line by line and reports each finding once, as soon as the
+/// lines triggering it were pushed, without going over the whole log for every line.
+///
+/// Rules only see the last few lines while the log is pushed. Findings whose triggers are
+/// further apart, like a missing mod and the exception it causes, and the exit code
+/// explanation are reported by [`IncrementalAnalyzer::finish`], which runs the rules on the
+/// whole log once. Findings aren't taken back when a more confident one conflicting with them
+/// turns up later.
+#[derive(Debug, Clone)]
+pub struct IncrementalAnalyzer {
+    analyzer: Analyzer,
+    log: String,
+    lines: usize,
+    window: VecDeque<String>,
+    kind: LogKind,
+    reported: Vec<Finding>,
+}
+
+impl Default for IncrementalAnalyzer {
+    fn default() -> Self {
+        IncrementalAnalyzer::new(Analyzer::new())
+    }
+}
+
+impl IncrementalAnalyzer {
+    /// Runs the rules of `analyzer`, with its policy and locale.
+    pub fn new(analyzer: Analyzer) -> Self {
+        IncrementalAnalyzer {
+            analyzer,
+            log: String::new(),
+            lines: 0,
+            window: VecDeque::with_capacity(WINDOW_LINES),
+            kind: LogKind::Unknown,
+            reported: Vec::new(),
+        }
+    }
+
+    /// Number of lines pushed so far.
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    /// Adds the next line of the log, with or without its line break, and returns what it
+    /// revealed that wasn't reported before. Evidence refers to lines of the whole log.
+    pub fn push_line(&mut self, line: &str) -> Vec<Diagnosis> {
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        self.log.push_str(line);
+        self.log.push('\n');
+        self.lines += 1;
+        if self.lines <= CLASSIFY_LINES {
+            self.kind = classify(&self.log).kind;
+        }
+        if self.window.len() == WINDOW_LINES {
+            self.window.pop_front();
+        }
+        self.window.push_back(line.to_string());
+
+        let mut text = String::new();
+        for line in &self.window {
+            text.push_str(line);
+            text.push('\n');
+        }
+        let reported = &self.reported;
+        let (mut found, errors) = self
+            .analyzer
+            .findings(&text, &[self.kind], |rule| reported.iter().any(|f| f.rule == rule));
+        for why in errors {
+            error!("{}", why);
+        }
+        let skipped = self.lines - self.window.len();
+        for finding in &mut found {
+            for evidence in &mut finding.diagnosis.evidence {
+                evidence.line += skipped;
+            }
+        }
+        self.report(found)
+    }
+
+    /// Adds several lines at once, see [`IncrementalAnalyzer::push_line`].
+    pub fn push_lines<'a>(&mut self, lines: impl IntoIterator<Item = &'a str>) -> Vec<Diagnosis> {
+        lines.into_iter().flat_map(|line| self.push_line(line)).collect()
+    }
+
+    /// Ends the log and returns the findings that weren't reported while it was pushed.
+    pub fn finish(self) -> Vec<Diagnosis> {
+        let (diagnoses, errors) = self.analyzer.diagnose_kinds(&self.log, &[classify(&self.log).kind]);
+        for why in errors {
+            error!("{}", why);
+        }
+        diagnoses
+            .into_iter()
+            .filter(|d| !self.reported.iter().any(|f| f.diagnosis.id == d.id))
+            .collect()
+    }
+
+    /// Keeps the findings that are new and not overruled by the ones reported before.
+    fn report(&mut self, found: Vec<Finding>) -> Vec<Diagnosis> {
+        let new: Vec<Finding> = found
+            .into_iter()
+            .filter(|f| !self.reported.iter().any(|r| r.diagnosis.id == f.diagnosis.id))
+            .collect();
+        if new.is_empty() {
+            return Vec::new();
+        }
+        let kept: Vec<Diagnosis> = resolve(self.reported.iter().cloned().chain(new.iter().cloned()).collect())
+            .into_iter()
+            .filter(|d| new.iter().any(|f| f.diagnosis.id == d.id))
+            .collect();
+        self.reported.extend(new.into_iter().filter(|f| kept.iter().any(|d| d.id == f.diagnosis.id)));
+        kept
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ResponseId;
+
+    #[test]
+    fn finds_triggers_spanning_lines_once() {
+        let mut analyzer = IncrementalAnalyzer::default();
+        let mut found = analyzer.push_lines(["MultiMC version: 0.6.16", "Minecraft folder is:"]);
+        assert!(found.is_empty());
+
+        found.extend(analyzer.push_line("C:/Program Files/MultiMC/instances/x/.minecraft\n"));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, ResponseId::ProgramFiles.as_str());
+        assert_eq!(found[0].evidence[0].line, 3);
+
+        for _ in 0..WINDOW_LINES {
+            assert!(analyzer.push_line("more output").is_empty());
+        }
+        assert!(analyzer.finish().iter().all(|d| d.id != ResponseId::ProgramFiles.as_str()));
+    }
+
+    #[test]
+    fn numbers_evidence_in_the_whole_log() {
+        let mut analyzer = IncrementalAnalyzer::default();
+        let mut found = Vec::new();
+        for i in 0..100 {
+            found.extend(analyzer.push_line(&format!("[12:00:00] [main/INFO]: line {}", i)));
+        }
+        found.extend(analyzer.push_line("java.lang.OutOfMemoryError: Java heap space"));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].evidence[0].line, 101);
+        assert!(analyzer.push_line("java.lang.OutOfMemoryError: Java heap space").is_empty());
+    }
+}
@@ -0,0 +1,384 @@
+//! Turning files users give us into text the rules can read.
+
+use std::{
+    borrow::Cow,
+    fmt,
+    io::{self, Cursor, Read},
+};
+
+use flate2::read::GzDecoder;
+use zip::{result::ZipError, ZipArchive};
+
+/// Decodes a log. Logs are usually UTF-8, but ones saved through PowerShell are UTF-16 with a
+/// byte order mark, and logs of systems with other code pages can have invalid UTF-8, which is
+/// replaced instead of failing.
+pub fn decode(bytes: &[u8]) -> Cow<'_, str> {
+    if let Some(rest) = bytes.strip_prefix(b"\xef\xbb\xbf") {
+        return String::from_utf8_lossy(rest);
+    }
+    let utf16 = |rest: &[u8], from_bytes: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = rest.chunks_exact(2).map(|c| from_bytes([c[0], c[1]])).collect();
+        Cow::Owned(String::from_utf16_lossy(&units))
+    };
+    match bytes {
+        [0xff, 0xfe, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xfe, 0xff, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        _ => String::from_utf8_lossy(bytes),
+    }
+}
+
+/// A log taken out of what a user gave us.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Extracted {
+    /// Name of the input, followed by the path in the archive for logs from archives, e.g.
+    /// `logs.zip:logs/latest.log`.
+    pub name: String,
+    pub text: String,
+}
+
+/// How much [`extract`] is willing to unpack, so a small archive can't make us decompress
+/// gigabytes or go through millions of entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Size of each log after decompressing it.
+    pub max_log_size: u64,
+    /// Size of all logs taken from an archive together.
+    pub max_total_size: u64,
+    /// Entries of a zip archive, including the ones that aren't logs.
+    pub max_entries: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_log_size: 16 * 1024 * 1024,
+            max_total_size: 64 * 1024 * 1024,
+            max_entries: 10_000,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    Gzip(io::Error),
+    Zip(ZipError),
+    /// A log, or all logs of an archive together, would be bigger than the limit.
+    TooLarge { name: String, limit: u64 },
+    TooManyEntries { limit: usize },
+    /// An archive without anything that looks like a log.
+    NoLogs,
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Gzip(why) => write!(f, "corrupt gzip file: {}", why),
+            InputError::Zip(why) => why.fmt(f),
+            InputError::TooLarge { name, limit } => {
+                write!(f, "`{}` is larger than {} bytes when unpacked", name, limit)
+            }
+            InputError::TooManyEntries { limit } => write!(f, "the archive has more than {} files", limit),
+            InputError::NoLogs => f.write_str("the archive contains no logs"),
+        }
+    }
+}
+
+impl std::error::Error for InputError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InputError::Gzip(why) => Some(why),
+            InputError::Zip(why) => Some(why),
+            _ => None,
+        }
+    }
+}
+
+impl From<ZipError> for InputError {
+    fn from(why: ZipError) -> Self {
+        InputError::Zip(why)
+    }
+}
+
+/// Reads `reader` to the end, or returns `None` as soon as it's more than `limit` bytes. Sizes
+/// in archive headers aren't trusted, so a zip or gzip bomb stops at the limit too.
+pub(crate) fn read_limited(reader: impl Read, limit: u64) -> io::Result<Option<Vec<u8>>> {
+    let mut content = Vec::new();
+    reader.take(limit + 1).read_to_end(&mut content)?;
+    Ok((content.len() as u64 <= limit).then_some(content))
+}
+
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const EMPTY_ZIP_MAGIC: &[u8] = b"PK\x05\x06";
+
+/// The logs in the file `name`: the file itself for plain logs, the decompressed log for
+/// gzip files like `2022-05-01-1.log.gz`, and the logs and crash reports in zip files, like
+/// a zipped `logs` or instance folder. Files are recognized by their content, not their name.
+/// Archives inside archives aren't opened.
+pub fn extract(name: &str, bytes: &[u8], limits: &Limits) -> Result<Vec<Extracted>, InputError> {
+    if bytes.starts_with(ZIP_MAGIC) || bytes.starts_with(EMPTY_ZIP_MAGIC) {
+        return extract_zip(name, bytes, limits);
+    }
+    let text = if bytes.starts_with(GZIP_MAGIC) {
+        decode(&gunzip(name, bytes, limits.max_log_size)?).into_owned()
+    } else {
+        decode(bytes).into_owned()
+    };
+    Ok(vec![Extracted {
+        name: name.to_string(),
+        text,
+    }])
+}
+
+fn gunzip(name: &str, bytes: &[u8], limit: u64) -> Result<Vec<u8>, InputError> {
+    read_limited(GzDecoder::new(bytes), limit)
+        .map_err(InputError::Gzip)?
+        .ok_or_else(|| InputError::TooLarge {
+            name: name.to_string(),
+            limit,
+        })
+}
+
+fn extract_zip(name: &str, bytes: &[u8], limits: &Limits) -> Result<Vec<Extracted>, InputError> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+    if archive.len() > limits.max_entries {
+        return Err(InputError::TooManyEntries {
+            limit: limits.max_entries,
+        });
+    }
+    let mut entries: Vec<String> = archive.file_names().filter(|e| is_log(e)).map(str::to_string).collect();
+    // A zipped instance has a rotated log for every launch, which are old news next to the
+    // logs of the last launch.
+    if entries.iter().any(|e| !is_gzipped(e)) {
+        entries.retain(|e| !is_gzipped(e));
+    }
+    // The directory is in no particular order
+    entries.sort();
+    let mut logs = Vec::new();
+    let mut total = 0;
+    for entry in entries {
+        let entry_name = format!("{}:{}", name, entry);
+        let too_large = || InputError::TooLarge {
+            name: entry_name.clone(),
+            limit: limits.max_log_size,
+        };
+        let file = archive.by_name(&entry)?;
+        let mut content = read_limited(file, limits.max_log_size)
+            .map_err(|why| InputError::Zip(why.into()))?
+            .ok_or_else(too_large)?;
+        if content.starts_with(GZIP_MAGIC) {
+            content = gunzip(&entry_name, &content, limits.max_log_size)?;
+        }
+        total += content.len() as u64;
+        if total > limits.max_total_size {
+            return Err(InputError::TooLarge {
+                name: name.to_string(),
+                limit: limits.max_total_size,
+            });
+        }
+        logs.push(Extracted {
+            name: entry_name,
+            text: decode(&content).into_owned(),
+        });
+    }
+    if logs.is_empty() {
+        return Err(InputError::NoLogs);
+    }
+    Ok(logs)
+}
+
+/// Logs, rotated logs and `hs_err_pid` files anywhere, and crash reports. Other text files
+/// in a zipped instance, like configs, aren't worth looking at.
+fn is_log(path: &str) -> bool {
+    if path.ends_with('/') {
+        return false;
+    }
+    let path = path.to_ascii_lowercase();
+    let file_name = path.rsplit('/').next().unwrap_or(&path);
+    path.ends_with(".log")
+        || is_gzipped(&path)
+        || (file_name.ends_with(".txt") && (path.contains("crash-reports/") || file_name.starts_with("crash-")))
+}
+
+fn is_gzipped(path: &str) -> bool {
+    path.to_ascii_lowercase().ends_with(".log.gz")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::GzEncoder, Compression};
+    use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+    use super::*;
+
+    const MIB: usize = 1024 * 1024;
+
+    /// A zip file of `entries`, stored without compression so big entries are quick to make.
+    fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+        for (name, content) in entries {
+            if name.ends_with('/') {
+                zip.add_directory(*name, options).unwrap();
+            } else {
+                zip.start_file(*name, options).unwrap();
+                zip.write_all(content).unwrap();
+            }
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn gzip(content: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn names(logs: &[Extracted]) -> Vec<&str> {
+        logs.iter().map(|l| l.name.as_str()).collect()
+    }
+
+    #[test]
+    fn stops_gzip_files_inflating_past_16_mib_by_default() {
+        let fits = gzip(&vec![b'a'; 16 * MIB]);
+        let logs = extract("2022-05-01-1.log.gz", &fits, &Limits::default()).unwrap();
+        assert_eq!(logs[0].text.len(), 16 * MIB);
+
+        let bomb = gzip(&vec![b'a'; 16 * MIB + 1]);
+        assert!(bomb.len() < 64 * 1024);
+        match extract("2022-05-01-1.log.gz", &bomb, &Limits::default()) {
+            Err(InputError::TooLarge { name, limit }) => {
+                assert_eq!(name, "2022-05-01-1.log.gz");
+                assert_eq!(limit, 16 * MIB as u64);
+            }
+            other => panic!("expected the log to be too large, got {:?}", other.map(|l| names(&l).join(", "))),
+        }
+    }
+
+    #[test]
+    fn takes_plain_and_gzipped_logs_as_they_are() {
+        let log = b"[12:00:00] [main/INFO]: hi\n";
+        let plain = extract("latest.log", log, &Limits::default()).unwrap();
+        let gzipped = extract("2022-05-01-1.log.gz", &gzip(log), &Limits::default()).unwrap();
+        assert_eq!(names(&plain), ["latest.log"]);
+        assert_eq!(names(&gzipped), ["2022-05-01-1.log.gz"]);
+        assert_eq!(plain[0].text, gzipped[0].text);
+    }
+
+    #[test]
+    fn limits_the_size_of_each_log() {
+        let limits = Limits::default();
+        let fits = vec![b'a'; 16 * MIB];
+        let logs = extract("logs.zip", &zip(&[("latest.log", &fits)]), &limits).unwrap();
+        assert_eq!(logs[0].text.len(), 16 * MIB);
+
+        let too_large = vec![b'a'; 16 * MIB + 1];
+        match extract("logs.zip", &zip(&[("latest.log", &too_large)]), &limits) {
+            Err(InputError::TooLarge { name, limit }) => {
+                assert_eq!(name, "logs.zip:latest.log");
+                assert_eq!(limit, 16 * MIB as u64);
+            }
+            other => panic!("expected the log to be too large, got {:?}", other.map(|l| names(&l).join(", "))),
+        }
+    }
+
+    #[test]
+    fn limits_the_size_of_all_logs_together() {
+        let log = vec![b'a'; 13 * MIB];
+        let entries: Vec<(String, &[u8])> = (1..=5).map(|n| (format!("{}.log", n), &log[..])).collect();
+        let entries: Vec<(&str, &[u8])> = entries.iter().map(|(name, log)| (name.as_str(), *log)).collect();
+
+        let fits = extract("logs.zip", &zip(&entries[..4]), &Limits::default()).unwrap();
+        assert_eq!(fits.len(), 4);
+        match extract("logs.zip", &zip(&entries), &Limits::default()) {
+            Err(InputError::TooLarge { name, limit }) => {
+                assert_eq!(name, "logs.zip");
+                assert_eq!(limit, 64 * MIB as u64);
+            }
+            other => panic!("expected the logs to be too large, got {:?}", other.map(|l| names(&l).join(", "))),
+        }
+    }
+
+    #[test]
+    fn limits_the_number_of_entries() {
+        let files: Vec<String> = (0..10_001).map(|n| format!("config/{}.txt", n)).collect();
+        let mut entries: Vec<(&str, &[u8])> = files.iter().map(|file| (file.as_str(), &b""[..])).collect();
+        assert!(matches!(
+            extract("instance.zip", &zip(&entries), &Limits::default()),
+            Err(InputError::TooManyEntries { limit: 10_000 })
+        ));
+
+        entries.pop();
+        entries[0] = ("logs/latest.log", b"hi");
+        let logs = extract("instance.zip", &zip(&entries), &Limits::default()).unwrap();
+        assert_eq!(names(&logs), ["instance.zip:logs/latest.log"]);
+    }
+
+    #[test]
+    fn stops_gzip_files_that_inflate_past_the_limit() {
+        let limits = Limits {
+            max_log_size: 1024,
+            ..Limits::default()
+        };
+        let bomb = gzip(&vec![0; MIB]);
+        assert!(bomb.len() < 1024 * 8);
+        assert!(matches!(
+            extract("2022-05-01-1.log.gz", &bomb, &limits),
+            Err(InputError::TooLarge { limit: 1024, .. })
+        ));
+        // Gzipped logs in a zip stop at the limit too, even when the zip entry is small
+        match extract("logs.zip", &zip(&[("2022-05-01-1.log.gz", &bomb)]), &limits) {
+            Err(InputError::TooLarge { name, .. }) => assert_eq!(name, "logs.zip:2022-05-01-1.log.gz"),
+            other => panic!("expected the log to be too large, got {:?}", other.map(|l| names(&l).join(", "))),
+        }
+    }
+
+    #[test]
+    fn doesnt_open_nested_zips() {
+        let inner = zip(&[("latest.log", b"hi")]);
+        let outer = zip(&[("logs.zip", &inner), ("nested/latest.log", b"hello")]);
+        let logs = extract("outer.zip", &outer, &Limits::default()).unwrap();
+        assert_eq!(names(&logs), ["outer.zip:nested/latest.log"]);
+
+        let only_nested = zip(&[("logs.zip", &inner)]);
+        assert!(matches!(
+            extract("outer.zip", &only_nested, &Limits::default()),
+            Err(InputError::NoLogs)
+        ));
+    }
+
+    #[test]
+    fn picks_the_logs_out_of_a_zipped_instance() {
+        let rotated = gzip(b"an old launch");
+        let instance = zip(&[
+            ("instance.cfg", b"name=Test"),
+            ("mmc-pack.json", b"{}"),
+            (".minecraft/", b""),
+            (".minecraft/options.txt", b"fov:0.0"),
+            (".minecraft/config/forge.cfg", b"general {}"),
+            (".minecraft/mods/", b""),
+            (".minecraft/logs/latest.log", b"the last launch"),
+            (".minecraft/logs/2022-05-01-1.log.gz", &rotated),
+            (".minecraft/crash-reports/crash-2022-05-02_12.00.00-client.txt", b"---- Minecraft Crash Report ----"),
+            (".minecraft/hs_err_pid1234.log", b"# A fatal error has been detected"),
+        ]);
+        let logs = extract("Test.zip", &instance, &Limits::default()).unwrap();
+        assert_eq!(
+            names(&logs),
+            [
+                "Test.zip:.minecraft/crash-reports/crash-2022-05-02_12.00.00-client.txt",
+                "Test.zip:.minecraft/hs_err_pid1234.log",
+                "Test.zip:.minecraft/logs/latest.log",
+            ]
+        );
+        assert_eq!(logs[2].text, "the last launch");
+
+        // Without the last launch's logs, the rotated ones are all there is
+        let logs_folder = zip(&[("logs/2022-05-01-1.log.gz", &rotated), ("logs/2022-05-01-2.log.gz", &rotated)]);
+        let logs = extract("logs.zip", &logs_folder, &Limits::default()).unwrap();
+        assert_eq!(names(&logs), ["logs.zip:logs/2022-05-01-1.log.gz", "logs.zip:logs/2022-05-01-2.log.gz"]);
+        assert_eq!(logs[0].text, "an old launch");
+    }
+}
@@ -0,0 +1,630 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    input,
+    mods::{self, ModsDir},
+    Action, Diagnosis, Evidence, Locale, ResponseId, RuleSet,
+};
+
+/// A MultiMC instance folder, with its `instance.cfg` and `mmc-pack.json`.
+#[derive(Debug, Clone)]
+pub struct Instance {
+    pub dir: PathBuf,
+    pub config: InstanceConfig,
+    /// Missing for instances that were never launched with a MultiMC 5 version.
+    pub pack: Option<Pack>,
+}
+
+impl Instance {
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Instance> {
+        let dir = dir.into();
+        let config = InstanceConfig::parse(&fs::read_to_string(dir.join(CONFIG_FILE))?);
+        let pack = match fs::read_to_string(dir.join(PACK_FILE)) {
+            Ok(content) => Some(
+                serde_json::from_str(&content)
+                    .map_err(|why| io::Error::new(io::ErrorKind::InvalidData, why))?,
+            ),
+            Err(why) if why.kind() == io::ErrorKind::NotFound => None,
+            Err(why) => return Err(why),
+        };
+        Ok(Instance { dir, config, pack })
+    }
+
+    /// The game folder, `.minecraft` or, in older instances, `minecraft`.
+    pub fn minecraft_dir(&self) -> PathBuf {
+        let dot = self.dir.join(".minecraft");
+        if !dot.exists() && self.dir.join("minecraft").exists() {
+            return self.dir.join("minecraft");
+        }
+        dot
+    }
+
+    pub fn mods_dir(&self) -> PathBuf {
+        self.minecraft_dir().join("mods")
+    }
+
+    /// Where the game writes the log of the running or last launch.
+    pub fn latest_log_path(&self) -> PathBuf {
+        self.minecraft_dir().join("logs").join("latest.log")
+    }
+
+    /// The game's log of the last launch.
+    pub fn latest_log(&self) -> io::Result<String> {
+        let bytes = fs::read(self.latest_log_path())?;
+        Ok(input::decode(&bytes).into_owned())
+    }
+
+    /// The name shown in the launcher, or the folder name if it has none.
+    pub fn name(&self) -> String {
+        match self.config.get("name") {
+            Some(name) => name.to_string(),
+            None => self.dir.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+        }
+    }
+
+    /// The most recently written log in the `logs` folder, usually `latest.log`.
+    pub fn newest_log(&self) -> io::Result<Option<PathBuf>> {
+        newest_file(&self.minecraft_dir().join("logs"), "log")
+    }
+
+    /// The most recent crash report in the `crash-reports` folder.
+    pub fn newest_crash_report(&self) -> io::Result<Option<PathBuf>> {
+        newest_file(&self.minecraft_dir().join("crash-reports"), "txt")
+    }
+
+    /// Jar files in the mods folder, sorted by name.
+    pub fn mod_jars(&self) -> io::Result<Vec<PathBuf>> {
+        let mut jars = Vec::new();
+        for entry in fs::read_dir(self.mods_dir())? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("jar")) {
+                jars.push(path);
+            }
+        }
+        jars.sort();
+        Ok(jars)
+    }
+
+    pub fn config_path(&self) -> PathBuf {
+        self.dir.join(CONFIG_FILE)
+    }
+
+    /// The global settings of the launcher the instance is in, which apply unless the
+    /// instance overrides them. `None` if the instance isn't in a launcher's `instances`
+    /// folder.
+    pub fn launcher_config(&self) -> io::Result<Option<InstanceConfig>> {
+        // <launcher>/instances/<instance>
+        let Some(launcher) = self.dir.parent().and_then(Path::parent) else {
+            return Ok(None);
+        };
+        for name in LAUNCHER_CONFIG_FILES {
+            match fs::read_to_string(launcher.join(name)) {
+                Ok(content) => return Ok(Some(InstanceConfig::parse(&content))),
+                Err(why) if why.kind() == io::ErrorKind::NotFound => continue,
+                Err(why) => return Err(why),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Looks for problems in the instance's files, without needing a log: components that
+    /// need another Minecraft version, mods for another loader or Minecraft version, missing
+    /// dependencies and Java settings that don't fit the Minecraft version.
+    pub fn inspect(&self) -> io::Result<Vec<Diagnosis>> {
+        let mut diagnoses = Vec::new();
+        let loader = self.pack.as_ref().and_then(Pack::loader);
+        if let Some(pack) = &self.pack {
+            diagnoses.extend(component_mismatches(pack));
+        }
+        let mods = match mods::read_mods_dir(&self.mods_dir()) {
+            Ok(mods) => mods,
+            Err(why) if why.kind() == io::ErrorKind::NotFound => Default::default(),
+            Err(why) => return Err(why),
+        };
+        if let Some(loader) = loader {
+            diagnoses.extend(wrong_loader_mods(&mods, loader));
+        }
+        let minecraft = self.pack.as_ref().and_then(|p| p.version_of(MINECRAFT));
+        diagnoses.extend(mods::check(&mods.jars, loader, minecraft));
+        diagnoses.extend(self.java_overrides());
+        Ok(diagnoses)
+    }
+
+    /// Java overrides in `instance.cfg` that don't fit the instance.
+    fn java_overrides(&self) -> Vec<Diagnosis> {
+        let mut diagnoses = Vec::new();
+        let config = self.config.to_string();
+        let required = self
+            .pack
+            .as_ref()
+            .and_then(|p| p.version_of(MINECRAFT))
+            .and_then(required_java);
+
+        if self.config.is_set("OverrideJavaLocation") {
+            let path = self.config.get("JavaPath").unwrap_or_default();
+            // <home>/bin/java
+            let major = Path::new(path).parent().and_then(Path::parent).and_then(java_major);
+            let response = match (required, major) {
+                (Some(21), Some(m)) if m < 21 => Some(ResponseId::UseJava21),
+                (Some(17), Some(m)) if m < 17 => Some(ResponseId::UseJava17),
+                (Some(16), Some(m)) if m < 16 => Some(ResponseId::UseJava16),
+                // Vanilla and Fabric run on newer Java, Forge and LiteLoader for these versions don't
+                (Some(8), Some(m)) if m > 8 && self.needs_java_8() => Some(ResponseId::UseJava8),
+                _ => None,
+            };
+            if let Some(response) = response {
+                let mut diagnosis = catalogued(
+                    response,
+                    "‼",
+                    Some(Action::RequireJava(required.unwrap_or_default())),
+                );
+                diagnosis.evidence.extend(config_evidence(&config, "JavaPath="));
+                diagnoses.push(diagnosis);
+            }
+        }
+
+        if self.config.is_set("OverrideJavaArgs") {
+            let args = self.config.get("JvmArgs").unwrap_or_default();
+            if args.split_whitespace().any(|a| a.starts_with("-Xmx") || a.starts_with("-Xms")) {
+                let mut diagnosis = catalogued(ResponseId::JvmArgsMemory, "❗", None);
+                diagnosis.evidence.extend(config_evidence(&config, "JvmArgs="));
+                diagnoses.push(diagnosis);
+            }
+            if required.is_some_and(|r| r >= 17) && args.contains("-XX:+UseConcMarkSweepGC") {
+                let mut diagnosis = catalogued(ResponseId::JvmArgsRemovedGc, "‼", None);
+                diagnosis.evidence.extend(config_evidence(&config, "JvmArgs="));
+                diagnoses.push(diagnosis);
+            }
+        }
+        diagnoses
+    }
+
+    /// Whether the instance has a loader that only runs on Java 8 for Minecraft 1.16 and older.
+    fn needs_java_8(&self) -> bool {
+        self.pack
+            .as_ref()
+            .is_some_and(|p| p.components.iter().any(|c| JAVA_8_LOADERS.contains(&c.uid.as_str())))
+    }
+}
+
+/// Mods for another loader than `loader`, going by their metadata. For jars whose metadata
+/// can't be read, the file name is used: jars for several loaders usually name all of them,
+/// and are left alone.
+fn wrong_loader_mods(mods: &ModsDir, loader: ModLoader) -> Vec<Diagnosis> {
+    // Jars are removed by the id of their mod, or by their file name if it can't be read
+    let by_metadata = mods.jars.iter().map(|jar| {
+        let id = jar.mods.iter().find(|m| !m.bundled).map_or_else(|| jar.file_name(), |m| m.id.clone());
+        (jar.file_name(), id, jar.loaders())
+    });
+    let by_name = mods.unreadable.iter().map(|(path, _)| {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let loaders = name
+            .to_lowercase()
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter_map(ModLoader::from_token)
+            .collect();
+        (name.clone(), name, loaders)
+    });
+
+    let mut diagnoses = Vec::new();
+    for (name, id, loaders) in by_metadata.chain(by_name) {
+        if loaders.is_empty() || loaders.iter().any(|l| loader.loads_mods_for().contains(l)) {
+            continue;
+        }
+        let message = ResponseId::WrongLoaderMod.format(
+            Locale::default(),
+            &[("mod", &name), ("mod_loader", loaders[0].name()), ("loader", loader.name())],
+        );
+        diagnoses.push(instance_diagnosis(
+            ResponseId::WrongLoaderMod.as_str(),
+            "❗",
+            message,
+            Some(Action::RemoveMod(id)),
+        ));
+    }
+    diagnoses
+}
+
+const CONFIG_FILE: &str = "instance.cfg";
+/// Global settings of MultiMC and its forks, which use the same format as `instance.cfg`.
+const LAUNCHER_CONFIG_FILES: [&str; 3] = ["multimc.cfg", "prismlauncher.cfg", "polymc.cfg"];
+const PACK_FILE: &str = "mmc-pack.json";
+
+/// The `key=value` lines of an `instance.cfg` or a launcher's config. Lines are kept as they are, so saving a
+/// config only changes the keys that were set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstanceConfig {
+    lines: Vec<String>,
+}
+
+impl InstanceConfig {
+    pub fn parse(content: &str) -> InstanceConfig {
+        InstanceConfig {
+            lines: content.lines().map(str::to_string).collect(),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.lines
+            .iter()
+            .find_map(|line| Some(line.split_once('=').filter(|(k, _)| k.trim() == key)?.1.trim()))
+    }
+
+    /// Whether a boolean setting like `OverrideJavaArgs` is on.
+    pub fn is_set(&self, key: &str) -> bool {
+        self.get(key).is_some_and(|v| v.eq_ignore_ascii_case("true"))
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> &mut Self {
+        let line = format!("{}={}", key, value);
+        match self
+            .lines
+            .iter_mut()
+            .find(|l| l.split_once('=').is_some_and(|(k, _)| k.trim() == key))
+        {
+            Some(existing) => *existing = line,
+            None => self.lines.push(line),
+        }
+        self
+    }
+}
+
+impl std::fmt::Display for InstanceConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// The components of an instance from `mmc-pack.json`, like Minecraft and the mod loader.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Pack {
+    #[serde(default)]
+    pub components: Vec<Component>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Component {
+    pub uid: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub cached_name: Option<String>,
+    /// Versions of other components this one needs.
+    #[serde(default)]
+    pub cached_requires: Vec<Requirement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Requirement {
+    pub uid: String,
+    /// The exact version needed, if any.
+    #[serde(default)]
+    pub equals: Option<String>,
+}
+
+impl Pack {
+    /// The version of the component with `uid`, e.g. `net.minecraft`.
+    pub fn version_of(&self, uid: &str) -> Option<&str> {
+        self.components
+            .iter()
+            .find(|c| c.uid == uid)
+            .and_then(|c| c.version.as_deref())
+    }
+
+    pub fn minecraft_version(&self) -> Option<&str> {
+        self.version_of(MINECRAFT)
+    }
+}
+
+/// Makes a copy of `path` next to it, named like `instance.cfg.bak`, `instance.cfg.bak1`, ...
+/// so earlier backups are kept. Returns the path of the copy.
+pub fn backup(path: &Path) -> io::Result<PathBuf> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no file name"))?
+        .to_string_lossy();
+    let mut n = 0;
+    loop {
+        let suffix = if n == 0 { String::new() } else { n.to_string() };
+        let target = path.with_file_name(format!("{}.bak{}", name, suffix));
+        if !target.exists() {
+            fs::copy(path, &target)?;
+            return Ok(target);
+        }
+        n += 1;
+    }
+}
+
+/// The most recently modified file in `dir` with the extension `extension`. A missing folder
+/// has none.
+fn newest_file(dir: &Path, extension: &str) -> io::Result<Option<PathBuf>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(why) if why.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(why) => return Err(why),
+    };
+    let mut newest = None;
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        if !path.extension().is_some_and(|e| e.eq_ignore_ascii_case(extension)) {
+            continue;
+        }
+        let modified = entry.metadata()?.modified()?;
+        if newest.as_ref().is_none_or(|(time, _)| modified > *time) {
+            newest = Some((modified, path));
+        }
+    }
+    Ok(newest.map(|(_, path)| path))
+}
+
+const MINECRAFT: &str = "net.minecraft";
+/// Loaders that don't run on Java 9 and newer for Minecraft 1.16 and older.
+const JAVA_8_LOADERS: [&str; 2] = ["net.minecraftforge", "com.mumfrey.liteloader"];
+
+/// Mod loaders that can be installed as a component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModLoader {
+    Forge,
+    NeoForge,
+    Fabric,
+    Quilt,
+}
+
+impl ModLoader {
+    const COMPONENTS: [(&'static str, ModLoader); 4] = [
+        ("net.minecraftforge", ModLoader::Forge),
+        ("net.neoforged", ModLoader::NeoForge),
+        ("net.fabricmc.fabric-loader", ModLoader::Fabric),
+        ("org.quiltmc.quilt-loader", ModLoader::Quilt),
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ModLoader::Forge => "Forge",
+            ModLoader::NeoForge => "NeoForge",
+            ModLoader::Fabric => "Fabric",
+            ModLoader::Quilt => "Quilt",
+        }
+    }
+
+    /// Loaders whose mods this loader can load, including itself.
+    pub fn loads_mods_for(&self) -> &'static [ModLoader] {
+        match self {
+            ModLoader::Forge => &[ModLoader::Forge],
+            // NeoForge started out as a fork of Forge 1.20.1, and still loads most of its mods
+            ModLoader::NeoForge => &[ModLoader::NeoForge, ModLoader::Forge],
+            ModLoader::Fabric => &[ModLoader::Fabric],
+            ModLoader::Quilt => &[ModLoader::Quilt, ModLoader::Fabric],
+        }
+    }
+
+    fn from_token(token: &str) -> Option<ModLoader> {
+        match token {
+            "forge" => Some(ModLoader::Forge),
+            "neoforge" => Some(ModLoader::NeoForge),
+            "fabric" => Some(ModLoader::Fabric),
+            "quilt" => Some(ModLoader::Quilt),
+            _ => None,
+        }
+    }
+}
+
+impl Pack {
+    /// The mod loader installed in the instance, if any.
+    pub fn loader(&self) -> Option<ModLoader> {
+        ModLoader::COMPONENTS
+            .iter()
+            .find(|(uid, _)| self.components.iter().any(|c| c.uid == *uid))
+            .map(|(_, loader)| *loader)
+    }
+}
+
+/// Components that need another version of a component than the instance has, like a
+/// Forge version for another Minecraft version.
+fn component_mismatches(pack: &Pack) -> Vec<Diagnosis> {
+    let mut diagnoses = Vec::new();
+    for component in &pack.components {
+        for requirement in &component.cached_requires {
+            let (Some(needed), Some(actual)) = (&requirement.equals, pack.version_of(&requirement.uid)) else {
+                continue;
+            };
+            if needed == actual {
+                continue;
+            }
+            let name = |c: &Component| c.cached_name.clone().unwrap_or_else(|| c.uid.clone());
+            let required_name = pack
+                .components
+                .iter()
+                .find(|c| c.uid == requirement.uid)
+                .map_or_else(|| requirement.uid.clone(), name);
+            let message = ResponseId::ComponentMismatch.format(
+                Locale::default(),
+                &[
+                    ("component", &name(component)),
+                    ("version", component.version.as_deref().unwrap_or("?")),
+                    ("required", &required_name),
+                    ("needed", needed),
+                    ("actual", actual),
+                ],
+            );
+            diagnoses.push(instance_diagnosis(ResponseId::ComponentMismatch.as_str(), "‼", message, None));
+        }
+    }
+    diagnoses
+}
+
+/// The major Java version a Minecraft release like "1.20.1" needs to run.
+fn required_java(minecraft: &str) -> Option<u32> {
+    let mut parts = minecraft.split('.').map(|p| p.parse::<u32>());
+    let (Some(Ok(1)), Some(Ok(minor))) = (parts.next(), parts.next()) else {
+        return None;
+    };
+    let patch = parts.next().and_then(|p| p.ok()).unwrap_or(0);
+    Some(match (minor, patch) {
+        (21.., _) | (20, 5..) => 21,
+        (18.., _) => 17,
+        (17, _) => 16,
+        _ => 8,
+    })
+}
+
+/// The major Java version of the installation in `home`, from its `release` file.
+pub(crate) fn java_major(home: &Path) -> Option<u32> {
+    let release = fs::read_to_string(home.join("release")).ok()?;
+    let version = release
+        .lines()
+        .find_map(|l| l.strip_prefix("JAVA_VERSION="))?
+        .trim_matches('"');
+    let mut parts = version.split(['.', '_', '+', '-']);
+    match parts.next()? {
+        // 1.8.0_372 is Java 8
+        "1" => parts.next()?.parse().ok(),
+        major => major.parse().ok(),
+    }
+}
+
+fn config_evidence(config: &str, needle: &str) -> Option<Evidence> {
+    let mut evidence = Evidence::find(config, needle)?;
+    evidence.source = Some(CONFIG_FILE.to_string());
+    Some(evidence)
+}
+
+pub(crate) fn catalogued(response: ResponseId, severity: &'static str, action: Option<Action>) -> Diagnosis {
+    let message = response.text(Locale::default()).to_string();
+    instance_diagnosis(response.as_str(), severity, message, action)
+}
+
+pub(crate) fn instance_diagnosis(id: &'static str, severity: &'static str, message: String, action: Option<Action>) -> Diagnosis {
+    Diagnosis {
+        id,
+        rule_set: RuleSet::Mistakes,
+        severity,
+        message,
+        confidence: 1.0,
+        blocking: false,
+        evidence: Vec::new(),
+        actions: action.into_iter().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn component(uid: &str, version: &str) -> Component {
+        Component {
+            uid: uid.to_string(),
+            version: Some(version.to_string()),
+            cached_name: None,
+            cached_requires: Vec::new(),
+        }
+    }
+
+    /// An instance of `minecraft` with `loader`, set to use a Java `java` installed in `dir`.
+    fn instance(dir: &TempDir, minecraft: &str, loader: Option<&str>, java: u32) -> Instance {
+        let home = dir.path().join(format!("jdk-{}", java));
+        fs::create_dir_all(home.join("bin")).unwrap();
+        let version = if java == 8 { "1.8.0_372".to_string() } else { format!("{}.0.2", java) };
+        fs::write(home.join("release"), format!("JAVA_VERSION=\"{}\"\n", version)).unwrap();
+        let config = format!("OverrideJavaLocation=true\nJavaPath={}\n", home.join("bin/java").display());
+
+        let mut components = vec![component(MINECRAFT, minecraft)];
+        components.extend(loader.map(|uid| component(uid, "1.0")));
+        Instance {
+            dir: dir.path().join("instance"),
+            config: InstanceConfig::parse(&config),
+            pack: Some(Pack { components }),
+        }
+    }
+
+    #[test]
+    fn knows_the_java_of_minecraft_versions() {
+        let cases = [
+            ("1.7.10", Some(8)),
+            ("1.12.2", Some(8)),
+            ("1.16.5", Some(8)),
+            ("1.17", Some(16)),
+            ("1.17.1", Some(16)),
+            ("1.18", Some(17)),
+            ("1.20.4", Some(17)),
+            ("1.20.5", Some(21)),
+            ("1.21.1", Some(21)),
+            ("24w14a", None),
+            ("b1.7.3", None),
+        ];
+        for (minecraft, java) in cases {
+            assert_eq!(required_java(minecraft), java, "{}", minecraft);
+        }
+    }
+
+    #[test]
+    fn finds_java_overrides_that_dont_fit() {
+        const FORGE: Option<&str> = Some("net.minecraftforge");
+        const FABRIC: Option<&str> = Some("net.fabricmc.fabric-loader");
+        const LITELOADER: Option<&str> = Some("com.mumfrey.liteloader");
+        let cases = [
+            ("1.21.1", None, 17, Some(ResponseId::UseJava21)),
+            ("1.21.1", None, 21, None),
+            ("1.20.4", FABRIC, 17, None),
+            ("1.18.2", FORGE, 16, Some(ResponseId::UseJava17)),
+            ("1.17.1", FABRIC, 8, Some(ResponseId::UseJava16)),
+            ("1.17.1", FORGE, 16, None),
+            ("1.16.5", None, 17, None),
+            ("1.16.5", FABRIC, 17, None),
+            ("1.16.5", FORGE, 17, Some(ResponseId::UseJava8)),
+            ("1.12.2", LITELOADER, 11, Some(ResponseId::UseJava8)),
+            ("1.12.2", FORGE, 8, None),
+        ];
+        for (minecraft, loader, java, expected) in cases {
+            let dir = TempDir::new().unwrap();
+            let found = instance(&dir, minecraft, loader, java).java_overrides();
+            let found: Vec<&str> = found.iter().map(|d| d.id).collect();
+            let expected: Vec<&str> = expected.iter().map(|r| r.as_str()).collect();
+            assert_eq!(found, expected, "Minecraft {} with {:?} on Java {}", minecraft, loader, java);
+        }
+    }
+
+    #[test]
+    fn ignores_java_without_the_override() {
+        let dir = TempDir::new().unwrap();
+        let mut instance = instance(&dir, "1.18.2", None, 8);
+        instance.config.set("OverrideJavaLocation", "false");
+        assert!(instance.java_overrides().is_empty());
+    }
+
+    #[test]
+    fn finds_components_for_other_versions() {
+        let mut forge = component("net.minecraftforge", "14.23.5.2859");
+        forge.cached_name = Some("Forge".to_string());
+        forge.cached_requires = vec![Requirement {
+            uid: MINECRAFT.to_string(),
+            equals: Some("1.12.2".to_string()),
+        }];
+        let mut minecraft = component(MINECRAFT, "1.12.1");
+        minecraft.cached_name = Some("Minecraft".to_string());
+        let pack = Pack {
+            components: vec![minecraft, forge],
+        };
+
+        let diagnoses = component_mismatches(&pack);
+        assert_eq!(diagnoses.len(), 1);
+        assert_eq!(diagnoses[0].id, "component-mismatch");
+        assert_eq!(
+            diagnoses[0].message,
+            "Forge 14.23.5.2859 is made for Minecraft 1.12.2, but the instance has Minecraft 1.12.1. \
+             Change the version of one of them in the instance's version settings."
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Serialize, Serializer};
+
+/// The launcher that wrote a log, from its "<Launcher> version: <version>" header.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LauncherInfo {
+    pub name: String,
+    pub version: Version,
+}
+
+/// Launchers known to write MultiMC-style logs.
+const LAUNCHERS: &str = "MultiMC|PolyMC|ManyMC|Prism Launcher|PollyMC|Fjord Launcher|UltimMC";
+
+pub fn detect_launcher(log: &str) -> Option<LauncherInfo> {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(&format!(r"(?m)^({}) version: (\S+)", LAUNCHERS)).unwrap();
+    }
+    let captures = RE.captures(log)?;
+    Some(LauncherInfo {
+        name: captures[1].to_string(),
+        version: captures[2].parse().ok()?,
+    })
+}
+
+/// A launcher version like "0.7.0-3" or "8.2". Only the numeric parts are compared, so
+/// "0.7.0-3" is 0.7.0.3 and "1.0-custom" is 1.0.
+#[derive(Debug, Clone)]
+pub struct Version {
+    raw: String,
+    parts: Vec<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseVersionError(String);
+
+impl fmt::Display for ParseVersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid version `{}`", self.0)
+    }
+}
+
+impl std::error::Error for ParseVersionError {}
+
+impl FromStr for Version {
+    type Err = ParseVersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<u64> = s
+            .split(['.', '-', '+', '_'])
+            .map_while(|p| p.parse().ok())
+            .collect();
+        if parts.is_empty() {
+            return Err(ParseVersionError(s.to_string()));
+        }
+        Ok(Version {
+            raw: s.to_string(),
+            parts,
+        })
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.parts.len().max(other.parts.len());
+        let pad = |parts: &[u64], i: usize| parts.get(i).copied().unwrap_or(0);
+        (0..len)
+            .map(|i| pad(&self.parts, i).cmp(&pad(&other.parts, i)))
+            .find(|o| o.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Version {}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl Serialize for Version {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.raw)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+/// A set of versions, written as comma separated constraints like ">=7.0, <8".
+/// `A..B` is short for `>=A, <B`, and `*` matches every version.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VersionRange {
+    constraints: Vec<(Op, Version)>,
+}
+
+impl VersionRange {
+    /// The range containing every version.
+    pub fn any() -> Self {
+        Self::default()
+    }
+
+    pub fn contains(&self, version: &Version) -> bool {
+        self.constraints.iter().all(|(op, bound)| match op {
+            Op::Less => version < bound,
+            Op::LessOrEqual => version <= bound,
+            Op::Equal => version == bound,
+            Op::GreaterOrEqual => version >= bound,
+            Op::Greater => version > bound,
+        })
+    }
+}
+
+impl FromStr for VersionRange {
+    type Err = ParseVersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut constraints = Vec::new();
+        for constraint in s.split(',').map(str::trim) {
+            if constraint.is_empty() || constraint == "*" {
+                continue;
+            }
+            if let Some((from, to)) = constraint.split_once("..") {
+                constraints.push((Op::GreaterOrEqual, from.trim().parse()?));
+                constraints.push((Op::Less, to.trim().parse()?));
+                continue;
+            }
+            let (op, version) = [
+                (">=", Op::GreaterOrEqual),
+                ("<=", Op::LessOrEqual),
+                (">", Op::Greater),
+                ("<", Op::Less),
+                ("=", Op::Equal),
+            ]
+            .iter()
+            .find_map(|(prefix, op)| Some((*op, constraint.strip_prefix(prefix)?)))
+            .unwrap_or((Op::Equal, constraint));
+            constraints.push((op, version.trim().parse()?));
+        }
+        Ok(VersionRange { constraints })
+    }
+}
+
+impl fmt::Display for VersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.constraints.is_empty() {
+            return f.write_str("*");
+        }
+        let constraints: Vec<String> = self
+            .constraints
+            .iter()
+            .map(|(op, version)| {
+                let op = match op {
+                    Op::Less => "<",
+                    Op::LessOrEqual => "<=",
+                    Op::Equal => "=",
+                    Op::GreaterOrEqual => ">=",
+                    Op::Greater => ">",
+                };
+                format!("{}{}", op, version)
+            })
+            .collect();
+        f.write_str(&constraints.join(", "))
+    }
+}
+
+/// How to treat logs from a launcher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Support {
+    /// Analyze the log as usual.
+    Supported,
+    /// Point out that the launcher isn't supported, but still show the other findings.
+    Warn,
+    /// Only point out that the launcher isn't supported.
+    Refuse,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSupportError(String);
+
+impl fmt::Display for ParseSupportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid support level `{}`, expected `supported`, `warn` or `refuse`",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseSupportError {}
+
+impl FromStr for Support {
+    type Err = ParseSupportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "supported" => Ok(Support::Supported),
+            "warn" => Ok(Support::Warn),
+            "refuse" => Ok(Support::Refuse),
+            _ => Err(ParseSupportError(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Support {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Support::Supported => "supported",
+            Support::Warn => "warn",
+            Support::Refuse => "refuse",
+        })
+    }
+}
+
+/// Support level of a launcher within a range of versions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LauncherPolicy {
+    pub launcher: String,
+    pub versions: VersionRange,
+    pub support: Support,
+}
+
+/// Decides which launchers are supported. Launchers without a matching entry get the
+/// default support level.
+///
+/// The default policy supports MultiMC and refuses every other launcher.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OriginPolicy {
+    entries: Vec<LauncherPolicy>,
+    default: Support,
+}
+
+impl Default for OriginPolicy {
+    fn default() -> Self {
+        let mut policy = OriginPolicy::new(Support::Refuse);
+        policy.set("MultiMC", VersionRange::any(), Support::Supported);
+        policy
+    }
+}
+
+impl OriginPolicy {
+    /// A policy without entries, treating every launcher as `default`.
+    pub fn new(default: Support) -> Self {
+        OriginPolicy {
+            entries: Vec::new(),
+            default,
+        }
+    }
+
+    /// Sets the support level for versions of a launcher. Later entries take precedence
+    /// over earlier ones. Launcher names are compared case-insensitively.
+    pub fn set(
+        &mut self,
+        launcher: impl Into<String>,
+        versions: VersionRange,
+        support: Support,
+    ) -> &mut Self {
+        self.entries.push(LauncherPolicy {
+            launcher: launcher.into(),
+            versions,
+            support,
+        });
+        self
+    }
+
+    pub fn entries(&self) -> &[LauncherPolicy] {
+        &self.entries
+    }
+
+    pub fn default_support(&self) -> Support {
+        self.default
+    }
+
+    pub fn support_for(&self, launcher: &LauncherInfo) -> Support {
+        self.entries
+            .iter()
+            .rev()
+            .find(|e| {
+                e.launcher.eq_ignore_ascii_case(&launcher.name)
+                    && e.versions.contains(&launcher.version)
+            })
+            .map_or(self.default, |e| e.support)
+    }
+}
@@ -1,244 +1,368 @@
 #![deny(dead_code)]
 
+pub mod actions;
+pub mod analyzer;
+pub mod classify;
+pub mod evidence;
+pub mod exit_codes;
+pub mod facts;
+pub mod fix;
+pub mod incremental;
+pub mod input;
+pub mod instance;
+pub mod launcher;
+pub mod mods;
+pub mod render;
 pub mod responses;
-use responses::RESPONSES;
+pub mod root;
+pub mod session;
+pub use actions::Action;
+pub use analyzer::{Analyzer, Detector, Hit, Report, Rule, RuleError, RuleSet};
+pub use classify::{classify, Classification, LogKind};
+pub use evidence::Evidence;
+pub use facts::{facts, Facts, Loader};
+pub use incremental::IncrementalAnalyzer;
+pub use launcher::{detect_launcher, LauncherInfo, LauncherPolicy, OriginPolicy, Support, VersionRange};
+pub use render::Renderer;
+pub use responses::{Locale, ResponseId, UnknownResponse};
+pub use session::{Session, SessionReport};
 
 use lazy_static::lazy_static;
 use regex::Regex;
+use serde::Serialize;
+
+/// A finding of a rule, with the response to show for it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnosis {
+    /// Id of the response, see [`ResponseId`]. Rules with their own wording may use other ids.
+    pub id: &'static str,
+    /// Set of the rule that made the finding.
+    pub rule_set: RuleSet,
+    pub severity: &'static str,
+    /// The response, in the markup described in [`render`].
+    pub message: String,
+    /// How likely the finding is right, between 0 and 1.
+    pub confidence: f32,
+    /// Whether the finding is about the launcher itself (e.g. a pirated build) and confident
+    /// enough that nothing else is worth looking at.
+    pub blocking: bool,
+    /// Lines that made the rule match. Not every rule reports them.
+    pub evidence: Vec<Evidence>,
+    /// Steps that fix the problem, if it can be fixed by following steps.
+    pub actions: Vec<Action>,
+}
 
 pub fn common_mistakes(input: &str) -> Vec<(&str, String)> {
-    PARSERS.iter().flat_map(|m| m(input)).collect()
-}
-
-pub(crate) type Check = fn(&str) -> Option<(&str, String)>;
-
-pub(crate) const PARSERS: [Check; 18] = [
-    multimc_in_program_files,
-    macos_too_new_java,
-    multimc_in_onedrive_managed_folder,
-    forge_too_new_java,
-    one_seventeen_plus_java_too_old,
-    two_one_plus_java_too_old,
-    m1_failed_to_find_service_port,
-    pixel_format_not_accelerated_win10,
-    intel_graphics_icd_dll,
-    id_range_exceeded,
-    out_of_memory_error,
-    shadermod_optifine_conflict,
-    fabric_api_missing,
-    java_architecture,
-    detect_temp_directories,
-    using_system_glfw,
-    using_system_openal,
-    reboot_required,
+    Analyzer::new()
+        .rule_sets(&[RuleSet::Mistakes])
+        .analyze(input)
+        .diagnoses
+        .into_iter()
+        .map(|d| (d.severity, d.message))
+        .collect()
+}
+
+pub fn common_origins(input: &str) -> Vec<(&str, String)> {
+    Analyzer::new()
+        .rule_sets(&[RuleSet::Origins])
+        .analyze(input)
+        .diagnoses
+        .into_iter()
+        .map(|d| (d.severity, d.message))
+        .collect()
+}
+
+/// Runs all built-in rules on `input`, treating launchers as the default [`OriginPolicy`]
+/// says. Use an [`Analyzer`] to change the rules or the policy.
+pub fn analyze(input: &str) -> Report {
+    Analyzer::new().analyze(input)
+}
+
+/// Runs origin and mistake rules together, treating launchers as the default
+/// [`OriginPolicy`] says. See [`Analyzer::analyze`].
+pub fn diagnose(input: &str) -> Vec<Diagnosis> {
+    Analyzer::new().analyze(input).diagnoses
+}
+
+/// Like [`diagnose`], with a custom policy for launchers.
+pub fn diagnose_with_policy(input: &str, policy: &OriginPolicy) -> Vec<Diagnosis> {
+    Analyzer::new().policy(policy.clone()).analyze(input).diagnoses
+}
+
+/// Messages printed by the launcher itself, or its log header.
+const LAUNCHER: &[LogKind] = &[LogKind::LauncherLog];
+/// Output of the game, which the launcher log contains as well.
+const GAME: &[LogKind] = &[
+    LogKind::LauncherLog,
+    LogKind::LatestLog,
+    LogKind::DebugLog,
+    LogKind::CrashReport,
+];
+const ANY: &[LogKind] = &[
+    LogKind::LauncherLog,
+    LogKind::LatestLog,
+    LogKind::DebugLog,
+    LogKind::CrashReport,
+    LogKind::HsErr,
 ];
 
-fn multimc_in_program_files(log: &str) -> Option<(&str, String)> {
-    const TRIGGER: &str = "Minecraft folder is:\nC:/Program Files";
-    if log.contains(TRIGGER) {
-        Some(("‼", RESPONSES.get("program-files")?.to_string()))
-    } else {
-        None
+pub(crate) const PARSERS: [Rule; 18] = [
+    Rule::new("multimc-in-program-files", multimc_in_program_files, LAUNCHER),
+    Rule::new("macos-too-new-java", macos_too_new_java, LAUNCHER)
+        .confidence(0.8)
+        .conflicts(&[ResponseId::UseJava17, ResponseId::UseJava21]),
+    Rule::new("multimc-in-onedrive-managed-folder", multimc_in_onedrive_managed_folder, LAUNCHER)
+        .confidence(0.6),
+    Rule::new("forge-too-new-java", forge_too_new_java, GAME)
+        .confidence(0.9)
+        .conflicts(&[ResponseId::UseJava17, ResponseId::UseJava21]),
+    Rule::new("one-seventeen-plus-java-too-old", one_seventeen_plus_java_too_old, GAME)
+        .conflicts(&[ResponseId::UseJava8, ResponseId::MacosJavaTooNew]),
+    Rule::new("two-one-plus-java-too-old", two_one_plus_java_too_old, GAME)
+        .supersedes(&[ResponseId::UseJava17])
+        .conflicts(&[ResponseId::UseJava8, ResponseId::MacosJavaTooNew]),
+    Rule::new("m1-failed-to-find-service-port", m1_failed_to_find_service_port, GAME),
+    Rule::new(
+        "pixel-format-not-accelerated-win10",
+        pixel_format_not_accelerated_win10,
+        &[LogKind::LauncherLog, LogKind::CrashReport],
+    )
+        .confidence(0.8),
+    Rule::new("intel-graphics-icd-dll", intel_graphics_icd_dll, &[LogKind::LauncherLog, LogKind::HsErr])
+        .confidence(0.9),
+    Rule::new("id-range-exceeded", id_range_exceeded, GAME),
+    Rule::new("out-of-memory-error", out_of_memory_error, ANY).confidence(0.9),
+    Rule::new("shadermod-optifine-conflict", shadermod_optifine_conflict, GAME),
+    Rule::new("fabric-api-missing", fabric_api_missing, GAME).confidence(0.9),
+    Rule::new("java-architecture", java_architecture, LAUNCHER),
+    Rule::new("detect-temp-directories", detect_temp_directories, LAUNCHER).confidence(0.9),
+    Rule::new("using-system-glfw", using_system_glfw, LAUNCHER).confidence(0.5),
+    Rule::new("using-system-openal", using_system_openal, LAUNCHER).confidence(0.5),
+    Rule::new("reboot-required", reboot_required, LAUNCHER).confidence(0.9),
+];
+
+/// The paths after the "Minecraft folder is:" headers of a launcher log, with where they start.
+fn minecraft_folders(log: &str) -> impl Iterator<Item = (usize, &str)> {
+    const HEADER: &str = "Minecraft folder is:\n";
+    log.match_indices(HEADER).map(move |(at, _)| {
+        let start = at + HEADER.len();
+        let end = log[start..].find('\n').map_or(log.len(), |i| start + i);
+        (start, log[start..end].trim_end_matches('\r'))
+    })
+}
+
+/// Problems with installing the launcher in `path`, like Program Files or a temporary folder.
+/// `path` is written with forward slashes, like in the "Minecraft folder is:" header.
+pub fn install_location_problems(path: &str) -> Vec<ResponseId> {
+    let mut problems = Vec::new();
+    if in_program_files(path) {
+        problems.push(ResponseId::ProgramFiles);
+    }
+    if in_onedrive(path) {
+        problems.push(ResponseId::MultimcInOnedrive);
     }
+    if path.contains("Rar$") {
+        problems.push(ResponseId::WinrarTemp);
+    } else if in_temp_folder(path) {
+        problems.push(ResponseId::TempFolder);
+    }
+    problems
 }
 
-fn macos_too_new_java(log: &str) -> Option<(&str, String)> {
-    const TRIGGER: &str = r#"Terminating app due to uncaught exception 'NSInternalInconsistencyException', reason: 'NSWindow drag regions should only be invalidated on the Main Thread!'"#;
-    if log.contains(TRIGGER) {
-        Some(("‼", RESPONSES.get("macos-java-too-new")?.to_string()))
-    } else {
-        None
+fn in_program_files(path: &str) -> bool {
+    path.starts_with("C:/Program Files")
+}
+
+fn in_onedrive(path: &str) -> bool {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^C:/.+/.+/OneDrive").unwrap();
+    }
+    RE.is_match(path)
+}
+
+fn in_temp_folder(path: &str) -> bool {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^[A-Z]:/([^/]+/)*Temp").unwrap();
     }
+    RE.is_match(path)
 }
 
-fn id_range_exceeded(log: &str) -> Option<(&str, String)> {
+fn multimc_in_program_files(log: &str) -> Option<Hit> {
+    let (at, _) = minecraft_folders(log).find(|(_, path)| in_program_files(path))?;
+    Some(Hit::new("‼", ResponseId::ProgramFiles).with_evidence([Evidence::at(log, at)]))
+}
+
+fn macos_too_new_java(log: &str) -> Option<Hit> {
+    const TRIGGER: &str = r#"Terminating app due to uncaught exception 'NSInternalInconsistencyException', reason: 'NSWindow drag regions should only be invalidated on the Main Thread!'"#;
+    let evidence = Evidence::find(log, TRIGGER)?;
+    Some(Hit::new("‼", ResponseId::MacosJavaTooNew).with_evidence([evidence]))
+}
+
+fn id_range_exceeded(log: &str) -> Option<Hit> {
     const TRIGGER: &str =
         "java.lang.RuntimeException: Invalid id 4096 - maximum id range exceeded.";
-    if log.contains(TRIGGER) {
-        Some(("‼", RESPONSES.get("id-limit")?.to_string()))
-    } else {
-        None
-    }
+    let evidence = Evidence::find(log, TRIGGER)?;
+    Some(Hit::new("‼", ResponseId::IdLimit).with_evidence([evidence]))
 }
 
-fn out_of_memory_error(log: &str) -> Option<(&str, String)> {
+fn out_of_memory_error(log: &str) -> Option<Hit> {
     const TRIGGER: &str = "java.lang.OutOfMemoryError";
-    if log.contains(TRIGGER) {
-        Some(("‼", RESPONSES.get("out-of-memory")?.to_string()))
-    } else {
-        None
-    }
+    let evidence = Evidence::find(log, TRIGGER)?;
+    let suggested = facts::max_memory(log)
+        .map_or(actions::DEFAULT_MAX_MEMORY, |current| {
+            current.saturating_mul(2).max(actions::DEFAULT_MAX_MEMORY)
+        });
+    Some(
+        Hit::new("‼", ResponseId::OutOfMemory)
+            .with_evidence([evidence])
+            .with_actions([Action::SetMaxMemory(suggested)]),
+    )
 }
 
-fn shadermod_optifine_conflict(log: &str) -> Option<(&str, String)> {
+fn shadermod_optifine_conflict(log: &str) -> Option<Hit> {
     const TRIGGER: &str = "java.lang.RuntimeException: Shaders Mod detected. Please remove it, OptiFine has built-in support for shaders.";
-    if log.contains(TRIGGER) {
-        Some(("‼", RESPONSES.get("optifine-and-shadermod")?.to_string()))
-    } else {
-        None
-    }
+    let evidence = Evidence::find(log, TRIGGER)?;
+    Some(Hit::new("‼", ResponseId::OptifineAndShadermod).with_evidence([evidence]))
 }
 
-fn fabric_api_missing(log: &str) -> Option<(&str, String)> {
+fn fabric_api_missing(log: &str) -> Option<Hit> {
     const EXCEPTION: &str =
         "net.fabricmc.loader.discovery.ModResolutionException: Could not find required mod:";
     const FABRIC: &str = "requires {fabric @";
 
-    if log.contains(EXCEPTION) && log.contains(FABRIC) {
-        Some(("‼", RESPONSES.get("missing-fabric-api")?.to_string()))
+    if log.contains(EXCEPTION) {
+        let evidence = Evidence::find(log, FABRIC)?;
+        Some(Hit::new("‼", ResponseId::MissingFabricApi).with_evidence([evidence]))
     } else {
         None
     }
 }
 
-fn multimc_in_onedrive_managed_folder(log: &str) -> Option<(&str, String)> {
-    lazy_static! {
-        static ref RE: Regex = Regex::new(r"Minecraft folder is:\nC:/.+/.+/OneDrive").unwrap();
-    }
-    if RE.is_match(log) {
-        Some(("❗", RESPONSES.get("multimc-in-onedrive")?.to_string()))
-    } else {
-        None
-    }
+fn multimc_in_onedrive_managed_folder(log: &str) -> Option<Hit> {
+    let (at, _) = minecraft_folders(log).find(|(_, path)| in_onedrive(path))?;
+    Some(Hit::new("❗", ResponseId::MultimcInOnedrive).with_evidence([Evidence::at(log, at)]))
 }
 
-fn forge_too_new_java(log: &str) -> Option<(&str, String)> {
+fn forge_too_new_java(log: &str) -> Option<Hit> {
     const URLCLASSLOADER_CAST: &str = "java.lang.ClassCastException: class jdk.internal.loader.ClassLoaders$AppClassLoader cannot be cast to class java.net.URLClassLoader";
-    if log.contains(URLCLASSLOADER_CAST) {
-        Some(("‼", RESPONSES.get("use-java-8")?.to_string()))
-    } else {
-        None
-    }
+    let evidence = Evidence::find(log, URLCLASSLOADER_CAST)?;
+    Some(Hit::new("‼", ResponseId::UseJava8).with_evidence([evidence]))
 }
 
-fn one_seventeen_plus_java_too_old(log: &str) -> Option<(&str, String)> {
+fn one_seventeen_plus_java_too_old(log: &str) -> Option<Hit> {
     const FABRIC_JAVA_VERSION_ERROR: &str = "fabric requires {java @ [>=16]}";
     const FABRIC_JAVA_VERSION_ERROR_SEVENTEEN: &str = "fabric requires {java @ [>=17]}";
     const JAVA_16_WARNING: &str = "Minecraft 21w19a and above require the use of Java 16";
     const JAVA_17_WARNING: &str = "Minecraft 1.18 Pre Release 2 and above require the use of Java 17";
 
-    if log.contains(FABRIC_JAVA_VERSION_ERROR)
-        || log.contains(FABRIC_JAVA_VERSION_ERROR_SEVENTEEN)
-        || log.contains(JAVA_16_WARNING)
-        || log.contains(JAVA_17_WARNING)
-    {
-        Some(("‼", RESPONSES.get("use-java-17")?.to_string()))
-    } else {
-        None
-    }
+    let evidence = [
+        FABRIC_JAVA_VERSION_ERROR,
+        FABRIC_JAVA_VERSION_ERROR_SEVENTEEN,
+        JAVA_16_WARNING,
+        JAVA_17_WARNING,
+    ]
+    .iter()
+    .find_map(|trigger| Evidence::find(log, trigger))?;
+    Some(Hit::new("‼", ResponseId::UseJava17).with_evidence([evidence]))
 }
 
-fn two_one_plus_java_too_old(log: &str) -> Option<(&str, String)> {
+fn two_one_plus_java_too_old(log: &str) -> Option<Hit> {
     const JAVA_CHECK_CLASS_FILE_VERSION: &str = "(class file version 65.0)";
     const JAVA_CHECK_CLASS_FILE_VERSION_MMC: &str = "Minecraft 24w14a and above require the use of Java 21";
-    if log.contains(JAVA_CHECK_CLASS_FILE_VERSION)
-        || log.contains(JAVA_CHECK_CLASS_FILE_VERSION_MMC)
-    {
-        Some(("‼", RESPONSES.get("use-java-21")?.to_string()))
-    } else {
-        None
-    }
+    let evidence = [JAVA_CHECK_CLASS_FILE_VERSION, JAVA_CHECK_CLASS_FILE_VERSION_MMC]
+        .iter()
+        .find_map(|trigger| Evidence::find(log, trigger))?;
+    Some(Hit::new("‼", ResponseId::UseJava21).with_evidence([evidence]))
 }
 
-fn m1_failed_to_find_service_port(log: &str) -> Option<(&str, String)> {
+fn m1_failed_to_find_service_port(log: &str) -> Option<Hit> {
     const TRIGGER: &str = "java.lang.IllegalStateException: GLFW error before init: [0x10008]Cocoa: Failed to find service port for display";
-    if log.contains(TRIGGER) {
-        Some((
-            "‼",
-            RESPONSES
-                .get("apple-silicon-incompatible-forge")?
-                .to_string(),
-        ))
-    } else {
-        None
-    }
+    let evidence = Evidence::find(log, TRIGGER)?;
+    Some(Hit::new("‼", ResponseId::AppleSiliconIncompatibleForge).with_evidence([evidence]))
 }
 
-fn pixel_format_not_accelerated_win10(log: &str) -> Option<(&str, String)> {
+fn pixel_format_not_accelerated_win10(log: &str) -> Option<Hit> {
     const LWJGL_EXCEPTION: &str = "org.lwjgl.LWJGLException: Pixel format not accelerated";
     const WIN10: &str = "Operating System: Windows 10";
-    if log.contains(LWJGL_EXCEPTION) && log.contains(WIN10) {
-        Some(("❗", RESPONSES.get("unsupported-intel-gpu")?.to_string()))
+    if log.contains(WIN10) {
+        let evidence = Evidence::find(log, LWJGL_EXCEPTION)?;
+        Some(Hit::new("❗", ResponseId::UnsupportedIntelGpu).with_evidence([evidence]))
     } else {
         None
     }
 }
 
-fn intel_graphics_icd_dll(log: &str) -> Option<(&str, String)> {
+fn intel_graphics_icd_dll(log: &str) -> Option<Hit> {
     lazy_static! {
         static ref RE: Regex =
             Regex::new(r"C  \[(ig[0-9]+icd[0-9]+\.dll)\+(0x[0-9a-f]+)\]").unwrap();
     }
-    if RE.is_match(log) {
-        Some(("❗", RESPONSES.get("unsupported-intel-gpu")?.to_string()))
-    } else {
-        None
-    }
+    let evidence = Evidence::find_re(log, &RE)?;
+    Some(Hit::new("❗", ResponseId::UnsupportedIntelGpu).with_evidence([evidence]))
 }
 
-fn java_architecture(log: &str) -> Option<(&str, String)> {
+fn java_architecture(log: &str) -> Option<Hit> {
     const TRIGGER: &str = "Your Java architecture is not matching your system architecture.";
-    if log.contains(TRIGGER) {
-        Some(("❗", RESPONSES.get("32-bit-java")?.to_string()))
-    } else {
-        None
-    }
+    let evidence = Evidence::find(log, TRIGGER)?;
+    Some(Hit::new("❗", ResponseId::Java32Bit).with_evidence([evidence]))
 }
 
-fn detect_temp_directories(log: &str) -> Option<(&str, String)> {
-    lazy_static! {
-        static ref RE: Regex = Regex::new(r"Minecraft folder is:\n[A-Z]:/([^/]+/)*Temp").unwrap();
-    }
-    if log.contains("Rar$") {
-        Some(("‼", RESPONSES.get("winrar-temp")?.to_string()))
+fn detect_temp_directories(log: &str) -> Option<Hit> {
+    let temp_folder = minecraft_folders(log).find(|(_, path)| in_temp_folder(path));
+    if let Some(evidence) = Evidence::find(log, "Rar$") {
+        Some(Hit::new("‼", ResponseId::WinrarTemp).with_evidence([evidence]))
     }
-    else if RE.is_match(log) && !log.contains("forge_installer") {
-        Some(("‼", RESPONSES.get("temp-folder")?.to_string()))
+    else if let (Some((at, _)), false) = (temp_folder, log.contains("forge_installer")) {
+        Some(Hit::new("‼", ResponseId::TempFolder).with_evidence([Evidence::at(log, at)]))
     }
     else {
         None
     }
 }
 
-fn using_system_openal(log: &str) -> Option<(&str, String)> {
+fn using_system_openal(log: &str) -> Option<Hit> {
     const TRIGGER: &str = "Using system OpenAL.";
-    if log.contains(TRIGGER) {
-        Some(("❗", RESPONSES.get("using-system-openal")?.to_string()))
-    } else {
-        None
-    }
+    let evidence = Evidence::find(log, TRIGGER)?;
+    Some(Hit::new("❗", ResponseId::UsingSystemOpenal).with_evidence([evidence]))
 }
 
-fn using_system_glfw(log: &str) -> Option<(&str, String)> {
+fn using_system_glfw(log: &str) -> Option<Hit> {
     const TRIGGER: &str = "Using system GLFW.";
-    if log.contains(TRIGGER) {
-        Some(("❗", RESPONSES.get("using-system-glfw")?.to_string()))
-    } else {
-        None
-    }
+    let evidence = Evidence::find(log, TRIGGER)?;
+    Some(Hit::new("❗", ResponseId::UsingSystemGlfw).with_evidence([evidence]))
 }
 
-fn reboot_required(log: &str) -> Option<(&str, String)> {
+fn reboot_required(log: &str) -> Option<Hit> {
     const TRIGGER: &str = "Couldn't extract native jar";
-    if log.contains(TRIGGER) {
-        Some(("‼", RESPONSES.get("reboot-required")?.to_string()))
-    } else {
-        None
-    }
+    let evidence = Evidence::find(log, TRIGGER)?;
+    Some(Hit::new("‼", ResponseId::RebootRequired).with_evidence([evidence]))
 }
 
-pub fn common_origins(input: &str) -> Vec<(&str, String)> {
-    ORIGINS.iter().flat_map(|m| m(input)).collect()
-}
+/// Launchers that are forks of MultiMC are handled by the [`OriginPolicy`] instead.
+pub(crate) const ORIGINS: [Rule; 7] = [
+    Rule::new("custom-build", custom_build, LAUNCHER).blocking(),
+    Rule::new("pirated-build", pirated_build, GAME).supersedes(PIRACY_SUPERSEDES).blocking(),
+    Rule::new("cracked-launcher", cracked_launcher, GAME).supersedes(PIRACY_SUPERSEDES).blocking(),
+    Rule::new("patched-authlib", patched_authlib, LAUNCHER).supersedes(PIRACY_SUPERSEDES).blocking(),
+    Rule::new("offline-account", offline_account, GAME)
+        .confidence(0.9)
+        .supersedes(&[ResponseId::ForkedBuild, ResponseId::UnsupportedLauncherWarning])
+        .blocking(),
+    // Real accounts can be called "Player123" too, so this only adds to the other findings
+    Rule::new("offline-username", offline_username, GAME)
+        .confidence(0.6)
+        .supersedes(&[ResponseId::ForkedBuild, ResponseId::UnsupportedLauncherWarning]),
+    Rule::new("m1-wrapper", m1_wrapper, LAUNCHER).blocking(),
+];
 
-pub(crate) const ORIGINS: [Check; 4] = [
-    custom_build,
-    pirated_build,
-    forked_build,
-    m1_wrapper
+/// A pirated launcher explains the fork and the offline account.
+const PIRACY_SUPERSEDES: &[ResponseId] = &[
+    ResponseId::ForkedBuild,
+    ResponseId::UnsupportedLauncherWarning,
+    ResponseId::OfflineAccount,
 ];
 
-fn custom_build(log: &str) -> Option<(&str, String)> {
+fn custom_build(log: &str) -> Option<Hit> {
     lazy_static! {
         static ref RE_OFFICIAL: Regex = Regex::new(r"MultiMC version: 0\.[0-7]\.[0-9]+-[0-9]+").unwrap();
         static ref RE_CUSTOM: Regex = Regex::new(r"MultiMC version: [a-z0-9\.]+-custom").unwrap();
@@ -247,42 +371,221 @@ fn custom_build(log: &str) -> Option<(&str, String)> {
         None
     } else {
         if RE_CUSTOM.is_match(log) {
-            Some(("‼", RESPONSES.get("custom-build")?.to_string()))
+            Some(Hit::new("‼", ResponseId::CustomBuild))
         } else {
             None
         }
     }
 }
 
-fn pirated_build(log: &str) -> Option<(&str, String)> {
+fn pirated_build(log: &str) -> Option<Hit> {
     const PIRATED_BUILD: &str = "UltimMC version: ";
     const AUTH_INJECTOR: &str = "authlib-injector";
 
-    if log.contains(PIRATED_BUILD) || log.contains(AUTH_INJECTOR) {
-        Some(("‼", RESPONSES.get("pirated-build")?.to_string()))
-    } else {
+    let evidence: Vec<Evidence> = [PIRATED_BUILD, AUTH_INJECTOR]
+        .iter()
+        .filter_map(|trigger| Evidence::find(log, trigger))
+        .collect();
+    if evidence.is_empty() {
         None
+    } else {
+        Some(Hit::new("‼", ResponseId::PiratedBuild).with_evidence(evidence))
     }
 }
 
-fn forked_build(log: &str) -> Option<(&str, String)> {
-    const POLYMC_BUILD: &str = "PolyMC version: ";
-    const MANYMC_BUILD: &str = "ManyMC version: ";
-    const PRISM_BUILD: &str = "Prism Launcher version: ";
+fn cracked_launcher(log: &str) -> Option<Hit> {
+    lazy_static! {
+        // TLauncher ships its own skin mod and keeps its files in .tlauncher. The word boundary
+        // keeps ATLauncher, which is legitimate, from matching
+        static ref RE: Regex = Regex::new(r"(?i)\b(?:tlauncher|tlskincape|sklauncher)").unwrap();
+    }
+    let evidence = Evidence::find_re(log, &RE)?;
+    Some(Hit::new("‼", ResponseId::PiratedBuild).with_evidence([evidence]))
+}
 
-    if log.contains(POLYMC_BUILD) || log.contains(MANYMC_BUILD) || log.contains(PRISM_BUILD) {
-        Some(("‼", RESPONSES.get("forked-build")?.to_string()))
+fn patched_authlib(log: &str) -> Option<Hit> {
+    lazy_static! {
+        static ref RE_AUTHLIB: Regex = Regex::new(r"(?m)^[ \t]+\S*authlib\S*\.jar[ \t\r]*$").unwrap();
+        static ref RE_OFFICIAL: Regex =
+            Regex::new(r"com/mojang/authlib/([^/]+)/authlib-([^/]+)\.jar[ \t\r]*$").unwrap();
+    }
+    let evidence: Vec<Evidence> = RE_AUTHLIB
+        .find_iter(log)
+        .filter(|m| {
+            RE_OFFICIAL
+                .captures(m.as_str())
+                .is_none_or(|c| c[1] != c[2])
+        })
+        .map(|m| Evidence::at(log, m.start()))
+        .collect();
+    if evidence.is_empty() {
+        None
     } else {
+        Some(Hit::new("‼", ResponseId::PiratedBuild).with_evidence(evidence))
+    }
+}
+
+fn offline_account(log: &str) -> Option<Hit> {
+    lazy_static! {
+        static ref RE_ZEROED_TOKEN: Regex =
+            Regex::new(r"--accessToken (?:0+|null|FML|-)(?:\s|$)").unwrap();
+        // Offline UUIDs are derived from the name (version 3), online ones are random (version 4)
+        static ref RE_OFFLINE_UUID: Regex = Regex::new(
+            r"--uuid [0-9a-fA-F]{8}-?[0-9a-fA-F]{4}-?3[0-9a-fA-F]{3}-?[0-9a-fA-F]{4}-?[0-9a-fA-F]{12}\b"
+        )
+        .unwrap();
+    }
+    let mut evidence: Vec<Evidence> = [&*RE_ZEROED_TOKEN, &*RE_OFFLINE_UUID]
+        .iter()
+        .filter_map(|re| Evidence::find_re(log, re))
+        .collect();
+    evidence.dedup();
+    if evidence.is_empty() {
         None
+    } else {
+        Some(Hit::new("‼", ResponseId::OfflineAccount).with_evidence(evidence))
+    }
+}
+
+/// Default names of offline accounts in cracked launchers, e.g. "Player123".
+fn offline_username(log: &str) -> Option<Hit> {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"(?m)(?:--username |Setting user: )Player[0-9]{1,4}(?:\s|$)").unwrap();
     }
+    let evidence = Evidence::find_re(log, &RE)?;
+    Some(Hit::new("‼", ResponseId::OfflineAccount).with_evidence([evidence]))
 }
 
-fn m1_wrapper(log: &str) -> Option<(&str, String)> {
+fn m1_wrapper(log: &str) -> Option<Hit> {
     const M1_PYTHON_WRAPPER: &str = "/m1-multimc-hack/mcwrap.py";
 
-    if log.contains(M1_PYTHON_WRAPPER) {
-        Some(("‼", RESPONSES.get("m1-python-wrapper")?.to_string()))
-    } else {
-        None
+    let evidence = Evidence::find(log, M1_PYTHON_WRAPPER)?;
+    Some(Hit::new("‼", ResponseId::M1PythonWrapper).with_evidence([evidence]))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn launcher_log(params: &str) -> String {
+        format!(
+            "MultiMC version: 0.7.0-3275\n\n\
+             Minecraft folder is:\n/home/user/MultiMC/instances/1.16.5/.minecraft\n\n\n\
+             Java path is:\n/usr/lib/jvm/java-8-openjdk/jre/bin/java\n\n\n\
+             Main Class:\n  net.minecraft.client.main.Main\n\n\
+             Params:\n  {}\n\n\
+             Window size: 854 x 480\n\n\
+             Launcher: standard\n",
+            params
+        )
+    }
+
+    /// How MultiMC prints the arguments of a launch with a Microsoft account, the account's
+    /// details censored.
+    const CENSORED_PARAMS: &str = "--username <PROFILE NAME> --version 1.16.5 \
+        --gameDir /home/user/MultiMC/instances/1.16.5/.minecraft --assetsDir /home/user/MultiMC/assets \
+        --assetIndex 1.16 --uuid <PROFILE ID> --accessToken <ACCESS TOKEN> --userType msa --versionType release";
+
+    fn offline_hits(log: &str) -> Vec<Diagnosis> {
+        Analyzer::new()
+            .rule_sets(&[RuleSet::Origins])
+            .analyze(log)
+            .diagnoses
+            .into_iter()
+            .filter(|d| d.id == ResponseId::OfflineAccount.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn censored_params_are_no_offline_account() {
+        assert!(offline_hits(&launcher_log(CENSORED_PARAMS)).is_empty());
+        // Legacy versions are launched without a UUID or token
+        assert!(offline_hits(&launcher_log("Player123 <SESSION ID>")).is_empty());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn params_split_over_lines_are_no_offline_account() {
+        let params = CENSORED_PARAMS.replace(" --accessToken", "\n  --accessToken");
+        assert!(offline_hits(&launcher_log(&params)).is_empty());
+    }
+
+    #[test]
+    fn finds_offline_tokens_and_uuids_in_params() {
+        let params = "--username Steve --version 1.16.5 --gameDir /home/user/.minecraft \
+            --uuid 5627dd98-e6be-3c21-b8a8-e92344183641 --accessToken 0 --userType legacy --versionType release";
+        let log = launcher_log(params);
+        let hits = offline_hits(&log);
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].blocking);
+        assert_eq!(hits[0].evidence[0].line, 15);
+        assert_eq!(hits[0].evidence[0].text, format!("  {}", params));
+    }
+
+    #[test]
+    fn checks_install_locations() {
+        let cases: [(&str, &[ResponseId]); 6] = [
+            ("C:/Program Files/MultiMC", &[ResponseId::ProgramFiles]),
+            ("C:/Program Files (x86)/MultiMC", &[ResponseId::ProgramFiles]),
+            ("C:/Users/steve/OneDrive/MultiMC", &[ResponseId::MultimcInOnedrive]),
+            ("C:/Users/steve/AppData/Local/Temp/MultiMC", &[ResponseId::TempFolder]),
+            ("C:/Users/steve/AppData/Local/Temp/Rar$EXa1234.5678/MultiMC", &[ResponseId::WinrarTemp]),
+            ("D:/Games/MultiMC", &[]),
+        ];
+        for (path, problems) in cases {
+            assert_eq!(install_location_problems(path), problems, "{}", path);
+        }
+    }
+
+    #[test]
+    fn checks_the_minecraft_folder_of_launcher_logs() {
+        let log = launcher_log(CENSORED_PARAMS).replace("/home/user/MultiMC", "C:/Program Files/MultiMC");
+        let report = Analyzer::new().analyze(&log);
+        let program_files = report.diagnoses.iter().find(|d| d.id == "program-files").unwrap();
+        assert_eq!(program_files.evidence[0].line, 4);
+        assert_eq!(program_files.evidence[0].text, "C:/Program Files/MultiMC/instances/1.16.5/.minecraft");
+    }
+
+    #[test]
+    fn finds_cracked_launchers_but_not_atlauncher() {
+        let atlauncher = "[12:00:00] [main/INFO]: Loading mods from C:\\Users\\steve\\AppData\\Roaming\\ATLauncher\\instances\\Vanilla\\mods\n\
+            [12:00:01] [main/INFO]: Setting user: Steve\n";
+        assert!(cracked_launcher(atlauncher).is_none());
+        assert!(!Analyzer::new().analyze(atlauncher).is_blocked());
+        assert!(cracked_launcher("/home/steve/.local/share/ATLauncher/instances/Vanilla/logs\n").is_none());
+
+        let tlauncher = "[12:00:00] [main/INFO]: Loading mods from C:\\Users\\steve\\AppData\\Roaming\\.tlauncher\\mods\n";
+        assert!(cracked_launcher(tlauncher).is_some());
+        assert!(cracked_launcher("Starting TLauncher version 2.86\n").is_some());
+        assert!(cracked_launcher("Loaded mod TLSkinCape\n").is_some());
+    }
+
+    #[test]
+    fn suggests_doubling_the_max_memory() {
+        let suggested = |log: &str| {
+            let report = Analyzer::new().analyze(log);
+            let oom = report.diagnoses.iter().find(|d| d.id == ResponseId::OutOfMemory.as_str());
+            oom.expect("expected an out of memory finding").actions.clone()
+        };
+        let oom = "[12:00:00] [main/ERROR]: java.lang.OutOfMemoryError: Java heap space\n";
+        assert_eq!(suggested(&format!("-Xmx3G\n{}", oom)), [Action::SetMaxMemory(6144)]);
+        assert_eq!(suggested(oom), [Action::SetMaxMemory(actions::DEFAULT_MAX_MEMORY)]);
+        assert_eq!(
+            suggested(&format!("-Xmx{}m\n{}", u64::MAX, oom)),
+            [Action::SetMaxMemory(u64::MAX)]
+        );
+    }
+
+    #[test]
+    fn default_offline_names_dont_block() {
+        let params = CENSORED_PARAMS.replace("<PROFILE NAME>", "Player482");
+        let report = Analyzer::new().analyze(&launcher_log(&params));
+        let hits: Vec<&Diagnosis> = report
+            .diagnoses
+            .iter()
+            .filter(|d| d.id == ResponseId::OfflineAccount.as_str())
+            .collect();
+        assert_eq!(hits.len(), 1);
+        assert!(!hits[0].blocking);
+        assert!(!report.is_blocked());
+    }
+}
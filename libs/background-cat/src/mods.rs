@@ -0,0 +1,1011 @@
+//! Metadata of mod jars: `fabric.mod.json`, `quilt.mod.json`, `META-INF/mods.toml`,
+//! `META-INF/neoforge.mods.toml` and `mcmod.info`, and the dependency checks built on it.
+
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::{self, Cursor},
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+use zip::{result::ZipError, ZipArchive};
+
+use crate::{
+    input::read_limited,
+    instance::{instance_diagnosis, ModLoader},
+    launcher::{Version, VersionRange},
+    Action, Diagnosis, Locale, ResponseId,
+};
+
+/// A mod as declared in a jar's metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModMetadata {
+    pub id: String,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub loader: ModLoader,
+    /// Minecraft versions the mod is made for, if it says.
+    pub minecraft: Option<Versions>,
+    pub dependencies: Vec<Dependency>,
+    /// Other ids the mod can stand in for, like `fabric` for Fabric API.
+    pub provides: Vec<String>,
+    /// Whether the mod comes bundled inside another mod's jar.
+    pub bundled: bool,
+}
+
+impl ModMetadata {
+    /// The name if the mod has one, the id otherwise.
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.id)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    pub id: String,
+    pub versions: Versions,
+    /// Optional dependencies are only checked for their version, if installed.
+    pub required: bool,
+}
+
+/// The versions a mod accepts, in the syntax of its loader: Maven ranges like
+/// `[1.20,1.21)` for Forge, and predicates like `>=0.15 <0.16` or `1.20.x` for Fabric and
+/// Quilt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Versions {
+    raw: String,
+    /// Any of the ranges has to match. `None` if the syntax isn't understood.
+    ranges: Option<Vec<VersionRange>>,
+}
+
+impl Versions {
+    pub fn any() -> Versions {
+        Versions {
+            raw: "*".to_string(),
+            ranges: Some(Vec::new()),
+        }
+    }
+
+    /// Parses a Maven version range. A plain version is only a recommendation in Maven, so
+    /// it accepts every version.
+    pub fn maven(raw: &str) -> Versions {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return Versions::any();
+        }
+        let mut ranges = Some(Vec::new());
+        if raw.starts_with(['[', '(']) {
+            ranges = raw
+                .split_inclusive([']', ')'])
+                .map(|interval| maven_interval(interval.trim_start_matches([',', ' '])))
+                .collect();
+        }
+        Versions {
+            raw: raw.to_string(),
+            ranges,
+        }
+    }
+
+    /// Parses Fabric and Quilt version predicates, any of which has to match.
+    pub fn predicates<'a>(raw: impl IntoIterator<Item = &'a str>) -> Versions {
+        let raw: Vec<&str> = raw.into_iter().map(str::trim).collect();
+        Versions {
+            ranges: raw.iter().map(|p| predicate(p)).collect(),
+            raw: raw.join(" || "),
+        }
+    }
+
+    /// Whether `version` is one of the versions. `None` if either isn't understood.
+    pub fn contains(&self, version: &str) -> Option<bool> {
+        let ranges = self.ranges.as_ref()?;
+        if ranges.is_empty() {
+            return Some(true);
+        }
+        let version: Version = version.parse().ok()?;
+        Some(ranges.iter().any(|r| r.contains(&version)))
+    }
+}
+
+impl fmt::Display for Versions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+/// One interval of a Maven range, like `[1.0,2.0)`, `[1.0]` or `(,1.5]`.
+fn maven_interval(interval: &str) -> Option<VersionRange> {
+    let inner = interval.get(1..interval.len().checked_sub(1)?)?;
+    let lower_inclusive = interval.starts_with('[');
+    let upper_inclusive = interval.ends_with(']');
+    let constraints = match inner.split_once(',') {
+        None => format!("={}", inner.trim()),
+        Some((lower, upper)) => {
+            let mut constraints = Vec::new();
+            if !lower.trim().is_empty() {
+                constraints.push(format!(
+                    "{}{}",
+                    if lower_inclusive { ">=" } else { ">" },
+                    lower.trim()
+                ));
+            }
+            if !upper.trim().is_empty() {
+                constraints.push(format!(
+                    "{}{}",
+                    if upper_inclusive { "<=" } else { "<" },
+                    upper.trim()
+                ));
+            }
+            constraints.join(", ")
+        }
+    };
+    constraints.parse().ok()
+}
+
+/// A Fabric version predicate: space separated constraints like `>=1.2`, `~1.2.3`, `^1.2`,
+/// `1.20.x` or `*`.
+fn predicate(predicate: &str) -> Option<VersionRange> {
+    let mut constraints = Vec::new();
+    for constraint in predicate.split_whitespace() {
+        // "<1.21-" is below every 1.21 pre-release, which is the same for us
+        let constraint = constraint.trim_end_matches('-');
+        if let Some(version) = constraint.strip_prefix('~') {
+            constraints.push(format!(">={}", version));
+            constraints.push(format!("<{}", bump(version, 1)?));
+        } else if let Some(version) = constraint.strip_prefix('^') {
+            constraints.push(format!(">={}", version));
+            constraints.push(format!("<{}", bump(version, 0)?));
+        } else if constraint.starts_with(['<', '>', '=']) {
+            constraints.push(constraint.to_string());
+        } else if let Some(fixed) = wildcard_prefix(constraint) {
+            if !fixed.is_empty() {
+                constraints.push(format!(">={}", fixed.join(".")));
+                constraints.push(format!("<{}", bump(&fixed.join("."), fixed.len() - 1)?));
+            }
+        } else {
+            constraints.push(format!("={}", constraint));
+        }
+    }
+    constraints.join(", ").parse().ok()
+}
+
+/// The parts before the wildcard of a version like `1.20.x`, or `None` without one.
+fn wildcard_prefix(version: &str) -> Option<Vec<&str>> {
+    let parts: Vec<&str> = version.split('.').collect();
+    let wildcard = parts.iter().position(|p| matches!(*p, "x" | "X" | "*"))?;
+    Some(parts[..wildcard].to_vec())
+}
+
+/// The version after the numeric part at `index` is raised by one, e.g. 1.2.3 bumped at 1
+/// is 1.3.
+fn bump(version: &str, index: usize) -> Option<String> {
+    let mut parts: Vec<u64> = version
+        .split(['.', '-', '+'])
+        .map_while(|p| p.parse().ok())
+        .collect();
+    parts.truncate(index + 1);
+    while parts.len() <= index {
+        parts.push(0);
+    }
+    parts[index] += 1;
+    let parts: Vec<String> = parts.iter().map(u64::to_string).collect();
+    Some(parts.join("."))
+}
+
+#[derive(Debug)]
+pub enum ModError {
+    Io(io::Error),
+    Zip(ZipError),
+    /// A metadata file or bundled jar is bigger than it's read with.
+    TooLarge { name: String, limit: u64 },
+    /// A metadata file that isn't valid JSON or TOML, or lacks the mod id.
+    Invalid {
+        file: &'static str,
+        why: String,
+    },
+    /// The jar has none of the metadata files, like a library or a resource pack.
+    NoMetadata,
+}
+
+impl fmt::Display for ModError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModError::Io(why) => why.fmt(f),
+            ModError::Zip(why) => why.fmt(f),
+            ModError::TooLarge { name, limit } => write!(f, "`{}` is larger than {} bytes", name, limit),
+            ModError::Invalid { file, why } => write!(f, "invalid `{}`: {}", file, why),
+            ModError::NoMetadata => f.write_str("no mod metadata"),
+        }
+    }
+}
+
+impl std::error::Error for ModError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ModError::Io(why) => Some(why),
+            ModError::Zip(why) => Some(why),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ModError {
+    fn from(why: io::Error) -> Self {
+        ModError::Io(why)
+    }
+}
+
+impl From<ZipError> for ModError {
+    fn from(why: ZipError) -> Self {
+        ModError::Zip(why)
+    }
+}
+
+/// A mod jar and the mods it declares, including the ones bundled inside it. Jars made for
+/// several loaders declare their mods once per loader.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModJar {
+    pub path: PathBuf,
+    pub mods: Vec<ModMetadata>,
+}
+
+/// Metadata files are small, anything bigger is probably not what we're looking for.
+const METADATA_LIMIT: u64 = 1024 * 1024;
+/// Bundled jars are read into memory, so they're limited too.
+const BUNDLED_JAR_LIMIT: u64 = 64 * 1024 * 1024;
+
+impl ModJar {
+    pub fn read(path: impl Into<PathBuf>) -> Result<ModJar, ModError> {
+        let path = path.into();
+        let data = fs::read(&path)?;
+        let mods = read_mods(&data, false)?;
+        if mods.is_empty() {
+            return Err(ModError::NoMetadata);
+        }
+        Ok(ModJar { path, mods })
+    }
+
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// The loaders the jar has metadata for.
+    pub fn loaders(&self) -> Vec<ModLoader> {
+        let mut loaders = Vec::new();
+        for m in self.mods.iter().filter(|m| !m.bundled) {
+            if !loaders.contains(&m.loader) {
+                loaders.push(m.loader);
+            }
+        }
+        loaders
+    }
+
+    /// The mods the jar adds when loaded by `loader`, using the metadata for the loader
+    /// itself if there is some. Without a loader, the first metadata found is used.
+    pub fn mods_for(&self, loader: Option<ModLoader>) -> Vec<&ModMetadata> {
+        let loaders = self.loaders();
+        let chosen = match loader {
+            Some(loader) => loader
+                .loads_mods_for()
+                .iter()
+                .find(|l| loaders.contains(l))
+                .copied(),
+            None => loaders.first().copied(),
+        };
+        self.mods
+            .iter()
+            .filter(|m| Some(m.loader) == chosen)
+            .collect()
+    }
+}
+
+/// The jars of a mods folder, sorted by name.
+#[derive(Debug, Default)]
+pub struct ModsDir {
+    pub jars: Vec<ModJar>,
+    /// Jars that couldn't be read and why, so they can be reported without stopping the others.
+    pub unreadable: Vec<(PathBuf, ModError)>,
+}
+
+pub fn read_mods_dir(dir: &Path) -> io::Result<ModsDir> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("jar"))
+        {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    let mut jars = Vec::new();
+    let mut unreadable = Vec::new();
+    for path in paths {
+        match ModJar::read(&path) {
+            Ok(jar) => jars.push(jar),
+            Err(why) => unreadable.push((path, why)),
+        }
+    }
+    Ok(ModsDir { jars, unreadable })
+}
+
+/// Reads the entry `name` of `zip` if it's there, failing if it's bigger than `limit`. The
+/// size in the directory isn't trusted, so a zip bomb stops at the limit too.
+fn read_entry(zip: &mut ZipArchive<Cursor<&[u8]>>, name: &str, limit: u64) -> Result<Option<Vec<u8>>, ModError> {
+    let entry = match zip.by_name(name) {
+        Ok(entry) => entry,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(why) => return Err(ModError::Zip(why)),
+    };
+    match read_limited(entry, limit)? {
+        Some(bytes) => Ok(Some(bytes)),
+        None => Err(ModError::TooLarge {
+            name: name.to_string(),
+            limit,
+        }),
+    }
+}
+
+/// The mods declared in the jar `data`, and in the jars bundled in it.
+fn read_mods(data: &[u8], bundled: bool) -> Result<Vec<ModMetadata>, ModError> {
+    let mut zip = ZipArchive::new(Cursor::new(data))?;
+    let mut text = |name: &str| -> Result<Option<String>, ModError> {
+        Ok(read_entry(&mut zip, name, METADATA_LIMIT)?.map(|bytes| String::from_utf8_lossy(&bytes).into_owned()))
+    };
+
+    let mut mods = Vec::new();
+    if let Some(json) = text("fabric.mod.json")? {
+        mods.push(fabric(&json, bundled)?);
+    }
+    if let Some(json) = text("quilt.mod.json")? {
+        mods.push(quilt(&json, bundled)?);
+    }
+    if let Some(toml) = text("META-INF/neoforge.mods.toml")? {
+        mods.extend(forge(
+            &toml,
+            ModLoader::NeoForge,
+            &text("META-INF/MANIFEST.MF")?,
+            bundled,
+        )?);
+    } else if let Some(toml) = text("META-INF/mods.toml")? {
+        mods.extend(forge(
+            &toml,
+            ModLoader::Forge,
+            &text("META-INF/MANIFEST.MF")?,
+            bundled,
+        )?);
+    }
+    if let Some(json) = text("mcmod.info")? {
+        // Some mods ship an empty or broken mcmod.info next to their real metadata
+        match mcmod_info(&json, bundled) {
+            Ok(legacy) => mods.extend(legacy),
+            Err(_) if !mods.is_empty() => {}
+            Err(why) => return Err(why),
+        }
+    }
+
+    if !bundled {
+        // Fabric puts bundled jars in META-INF/jars, Forge and NeoForge in META-INF/jarjar
+        let nested: Vec<String> = zip
+            .file_names()
+            .filter(|name| name.starts_with("META-INF/jars/") || name.starts_with("META-INF/jarjar/"))
+            .filter(|name| name.ends_with(".jar"))
+            .map(str::to_string)
+            .collect();
+        for name in nested {
+            if let Some(jar) = read_entry(&mut zip, &name, BUNDLED_JAR_LIMIT)? {
+                // A bundled library without metadata is fine
+                mods.extend(read_mods(&jar, true).unwrap_or_default());
+            }
+        }
+    }
+    Ok(mods)
+}
+
+fn invalid(file: &'static str, why: impl ToString) -> ModError {
+    ModError::Invalid {
+        file,
+        why: why.to_string(),
+    }
+}
+
+fn string(value: &Value, key: &str) -> Option<String> {
+    value.get(key)?.as_str().map(str::to_string)
+}
+
+/// Versions given as a string or an array of strings.
+fn json_predicates(value: &Value) -> Option<Versions> {
+    match value {
+        Value::String(s) => Some(Versions::predicates([s.as_str()])),
+        Value::Array(items) => {
+            let items: Option<Vec<&str>> = items.iter().map(Value::as_str).collect();
+            Some(Versions::predicates(items?))
+        }
+        _ => None,
+    }
+}
+
+fn fabric(json: &str, bundled: bool) -> Result<ModMetadata, ModError> {
+    const FILE: &str = "fabric.mod.json";
+    let value: Value = serde_json::from_str(json).map_err(|why| invalid(FILE, why))?;
+    let id = string(&value, "id").ok_or_else(|| invalid(FILE, "no `id`"))?;
+
+    let mut minecraft = None;
+    let mut dependencies = Vec::new();
+    for (key, required) in [("depends", true), ("recommends", false)] {
+        let Some(Value::Object(depends)) = value.get(key) else {
+            continue;
+        };
+        for (dependency, versions) in depends {
+            let versions = json_predicates(versions).unwrap_or_else(Versions::any);
+            if dependency == "minecraft" {
+                minecraft = Some(versions);
+            } else {
+                dependencies.push(Dependency {
+                    id: dependency.clone(),
+                    versions,
+                    required,
+                });
+            }
+        }
+    }
+    let provides = match value.get("provides") {
+        Some(Value::Array(ids)) => ids
+            .iter()
+            .filter_map(|i| i.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    };
+    Ok(ModMetadata {
+        id,
+        name: string(&value, "name"),
+        version: string(&value, "version"),
+        loader: ModLoader::Fabric,
+        minecraft,
+        dependencies,
+        provides,
+        bundled,
+    })
+}
+
+fn quilt(json: &str, bundled: bool) -> Result<ModMetadata, ModError> {
+    const FILE: &str = "quilt.mod.json";
+    let value: Value = serde_json::from_str(json).map_err(|why| invalid(FILE, why))?;
+    let loader = value
+        .get("quilt_loader")
+        .ok_or_else(|| invalid(FILE, "no `quilt_loader`"))?;
+    let id = string(loader, "id").ok_or_else(|| invalid(FILE, "no `id`"))?;
+
+    let mut minecraft = None;
+    let mut dependencies = Vec::new();
+    let depends = loader
+        .get("depends")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    for dependency in depends {
+        // Either just an id, or an object with the id, versions and whether it's optional
+        let (dependency_id, versions, required) = match &dependency {
+            Value::String(id) => (id.clone(), Versions::any(), true),
+            Value::Object(_) => {
+                let Some(id) = string(&dependency, "id") else {
+                    continue;
+                };
+                let versions = dependency.get("versions").map_or_else(Versions::any, |v| {
+                    json_predicates(v).unwrap_or(Versions {
+                        raw: v.to_string(),
+                        ranges: None,
+                    })
+                });
+                let optional = dependency
+                    .get("optional")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false);
+                (id, versions, !optional)
+            }
+            _ => continue,
+        };
+        if dependency_id == "minecraft" {
+            minecraft = Some(versions);
+        } else {
+            dependencies.push(Dependency {
+                id: dependency_id,
+                versions,
+                required,
+            });
+        }
+    }
+    let provides = match loader.get("provides") {
+        Some(Value::Array(ids)) => ids
+            .iter()
+            .filter_map(|p| p.as_str().map(str::to_string).or_else(|| string(p, "id")))
+            .collect(),
+        _ => Vec::new(),
+    };
+    let metadata = loader.get("metadata").cloned().unwrap_or_default();
+    Ok(ModMetadata {
+        id,
+        name: string(&metadata, "name"),
+        version: string(loader, "version"),
+        loader: ModLoader::Quilt,
+        minecraft,
+        dependencies,
+        provides,
+        bundled,
+    })
+}
+
+/// The mods of a `mods.toml`. `${file.jarVersion}` versions are taken from the jar's
+/// manifest, like Forge does.
+fn forge(
+    content: &str,
+    loader: ModLoader,
+    manifest: &Option<String>,
+    bundled: bool,
+) -> Result<Vec<ModMetadata>, ModError> {
+    const FILE: &str = "mods.toml";
+    let jar_version = manifest.as_deref().and_then(|m| {
+        m.lines()
+            .find_map(|l| l.strip_prefix("Implementation-Version:"))
+            .map(|v| v.trim().to_string())
+    });
+    let value: toml::Value = toml::from_str(content).map_err(|why| invalid(FILE, why))?;
+    let tables = |value: Option<&toml::Value>| -> Vec<toml::Value> {
+        value.and_then(toml::Value::as_array).cloned().unwrap_or_default()
+    };
+    let toml_string = |table: &toml::Value, key: &str| table.get(key).and_then(toml::Value::as_str).map(str::to_string);
+
+    let mut mods = Vec::new();
+    for table in tables(value.get("mods")) {
+        let Some(id) = toml_string(&table, "modId") else {
+            continue;
+        };
+        let version = toml_string(&table, "version").map(|v| match (v.as_str(), &jar_version) {
+            ("${file.jarVersion}", Some(jar_version)) => jar_version.clone(),
+            _ => v,
+        });
+        let mut metadata = ModMetadata {
+            id: id.clone(),
+            name: toml_string(&table, "displayName"),
+            version,
+            loader,
+            minecraft: None,
+            dependencies: Vec::new(),
+            provides: Vec::new(),
+            bundled,
+        };
+        let dependencies = tables(value.get("dependencies").and_then(|d| d.get(&id)));
+        for dependency in dependencies {
+            let Some(dependency_id) = toml_string(&dependency, "modId") else {
+                continue;
+            };
+            let kind = toml_string(&dependency, "type");
+            // Forge used `mandatory`, NeoForge uses `type`
+            let required = match &kind {
+                Some(kind) => kind.eq_ignore_ascii_case("required"),
+                None => dependency.get("mandatory").and_then(toml::Value::as_bool).unwrap_or(true),
+            };
+            let incompatible = kind.as_deref().is_some_and(|k| {
+                k.eq_ignore_ascii_case("incompatible") || k.eq_ignore_ascii_case("discouraged")
+            });
+            if incompatible {
+                continue;
+            }
+            let versions = toml_string(&dependency, "versionRange")
+                .map_or_else(Versions::any, |range| Versions::maven(&range));
+            match dependency_id.as_str() {
+                "minecraft" => metadata.minecraft = Some(versions),
+                // Mods for NeoForge 1.20.1 to 1.20.4 still used mods.toml
+                "neoforge" => metadata.loader = ModLoader::NeoForge,
+                _ => metadata.dependencies.push(Dependency {
+                    id: dependency_id,
+                    versions,
+                    required,
+                }),
+            }
+        }
+        mods.push(metadata);
+    }
+    Ok(mods)
+}
+
+/// The mods of an `mcmod.info`, used by Forge before 1.13. Either a list of mods or an
+/// object with a `modList`.
+fn mcmod_info(json: &str, bundled: bool) -> Result<Vec<ModMetadata>, ModError> {
+    const FILE: &str = "mcmod.info";
+    let value: Value = serde_json::from_str(json).map_err(|why| invalid(FILE, why))?;
+    let list = match &value {
+        Value::Array(list) => list,
+        Value::Object(_) => value
+            .get("modList")
+            .and_then(Value::as_array)
+            .ok_or_else(|| invalid(FILE, "no `modList`"))?,
+        _ => return Err(invalid(FILE, "not a list of mods")),
+    };
+
+    let mut mods = Vec::new();
+    for entry in list {
+        let Some(id) = string(entry, "modid") else {
+            continue;
+        };
+        // Usually a single version like "1.12.2" and often a placeholder, so only ranges
+        // are used
+        let minecraft = string(entry, "mcversion")
+            .filter(|v| v.starts_with(['[', '(']))
+            .map(|v| Versions::maven(&v));
+        let dependencies = entry
+            .get("requiredMods")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(|required| {
+                // "modid@[1.0,)"
+                let (id, versions) = required.split_once('@').unwrap_or((required, ""));
+                Dependency {
+                    id: id.to_string(),
+                    versions: Versions::maven(versions),
+                    required: true,
+                }
+            })
+            .filter(|d| d.id != "Forge" && d.id != "forge")
+            .collect();
+        mods.push(ModMetadata {
+            id,
+            name: string(entry, "name"),
+            version: string(entry, "version"),
+            loader: ModLoader::Forge,
+            minecraft,
+            dependencies,
+            provides: Vec::new(),
+            bundled,
+        });
+    }
+    Ok(mods)
+}
+
+/// Dependencies that are part of the game or the loader rather than mods.
+const PLATFORM_IDS: &[&str] = &[
+    "minecraft",
+    "java",
+    "forge",
+    "neoforge",
+    "fml",
+    "javafml",
+    "fabricloader",
+    "fabric-loader",
+    "quilt_loader",
+    "quilt_base",
+];
+
+/// Checks the mods of `jars` against each other and the instance: required mods that
+/// aren't installed, installed versions that don't fit, and mods made for another
+/// Minecraft version.
+pub fn check(
+    jars: &[ModJar],
+    loader: Option<ModLoader>,
+    minecraft: Option<&str>,
+) -> Vec<Diagnosis> {
+    let mods: Vec<(&ModJar, &ModMetadata)> = jars
+        .iter()
+        .flat_map(|jar| jar.mods_for(loader).into_iter().map(move |m| (jar, m)))
+        .collect();
+    let mut installed: HashMap<&str, Option<&str>> = HashMap::new();
+    for (_, m) in &mods {
+        installed.insert(&m.id, m.version.as_deref());
+        for provided in &m.provides {
+            installed.entry(provided).or_insert(m.version.as_deref());
+        }
+    }
+
+    let mut diagnoses = Vec::new();
+    for (_, m) in mods.iter().filter(|(_, m)| !m.bundled) {
+        if let (Some(versions), Some(minecraft)) = (&m.minecraft, minecraft) {
+            if versions.contains(minecraft) == Some(false) {
+                diagnoses.push(formatted(
+                    ResponseId::ModForOtherMinecraft,
+                    "‼",
+                    &[
+                        ("mod", m.display_name()),
+                        ("versions", &versions.to_string()),
+                        ("minecraft", minecraft),
+                    ],
+                    Action::RemoveMod(m.id.clone()),
+                ));
+            }
+        }
+        for dependency in &m.dependencies {
+            if PLATFORM_IDS.contains(&dependency.id.as_str()) {
+                continue;
+            }
+            match installed.get(dependency.id.as_str()) {
+                None if !dependency.required => {}
+                None if matches!(dependency.id.as_str(), "fabric" | "fabric-api") => {
+                    let diagnosis = formatted(
+                        ResponseId::MissingFabricApi,
+                        "‼",
+                        &[],
+                        Action::InstallMod("fabric-api".to_string()),
+                    );
+                    if !diagnoses.contains(&diagnosis) {
+                        diagnoses.push(diagnosis);
+                    }
+                }
+                None => diagnoses.push(formatted(
+                    ResponseId::MissingDependency,
+                    "‼",
+                    &[("mod", m.display_name()), ("dependency", &dependency.id)],
+                    Action::InstallMod(dependency.id.clone()),
+                )),
+                Some(Some(version)) if dependency.versions.contains(version) == Some(false) => {
+                    diagnoses.push(formatted(
+                        ResponseId::IncompatibleDependency,
+                        "‼",
+                        &[
+                            ("mod", m.display_name()),
+                            ("dependency", &dependency.id),
+                            ("versions", &dependency.versions.to_string()),
+                            ("version", version),
+                        ],
+                        Action::InstallMod(dependency.id.clone()),
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+    }
+    diagnoses
+}
+
+fn formatted(
+    response: ResponseId,
+    severity: &'static str,
+    args: &[(&str, &str)],
+    action: Action,
+) -> Diagnosis {
+    let message = response.format(Locale::default(), args);
+    instance_diagnosis(response.as_str(), severity, message, Some(action))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use zip::{write::FileOptions, ZipWriter};
+
+    use super::*;
+
+    fn jar(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            zip.start_file(*name, FileOptions::default()).unwrap();
+            zip.write_all(content).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    const FABRIC_MOD: &str = r#"{
+        "schemaVersion": 1,
+        "id": "sodium",
+        "name": "Sodium",
+        "version": "0.5.3",
+        "depends": { "minecraft": ["1.20.1", "1.20.2"], "fabricloader": ">=0.14", "fabric-api": "*" },
+        "recommends": { "indium": ">=1.0.20 <1.1" },
+        "provides": ["rubidium"]
+    }"#;
+
+    const MODS_TOML: &str = r#"
+        modLoader = "javafml"
+        loaderVersion = "[47,)"
+        license = "MIT"
+
+        [[mods]]
+        modId = "create"
+        version = "${file.jarVersion}"
+        displayName = "Create"
+        description = '''
+        Building tools and aesthetic technology
+        '''
+
+        [[dependencies.create]]
+        modId = "minecraft"
+        mandatory = true
+        versionRange = "[1.20.1,1.20.2)"
+        ordering = "NONE"
+        side = "BOTH"
+
+        [[dependencies.create]]
+        modId = "flywheel"
+        mandatory = true
+        versionRange = "[0.6.10,0.6.11)"
+
+        [[dependencies.create]]
+        modId = "jei"
+        mandatory = false
+        versionRange = "[15,)"
+    "#;
+
+    fn versions(raw: &str) -> Versions {
+        if raw.starts_with(['[', '(']) {
+            Versions::maven(raw)
+        } else {
+            Versions::predicates([raw])
+        }
+    }
+
+    #[test]
+    fn matches_version_ranges() {
+        let cases = [
+            ("[1.0,2.0)", "1.0", Some(true)),
+            ("[1.0,2.0)", "1.9.9", Some(true)),
+            ("[1.0,2.0)", "2.0", Some(false)),
+            ("(1.0,2.0]", "1.0", Some(false)),
+            ("(1.0,2.0]", "2.0", Some(true)),
+            ("[1.20.1]", "1.20.1", Some(true)),
+            ("[1.20.1]", "1.20.2", Some(false)),
+            ("(,1.5]", "1.2", Some(true)),
+            ("[1.0,)", "99", Some(true)),
+            ("[1.0,1.2),[1.5,)", "1.3", Some(false)),
+            ("[1.0,1.2),[1.5,)", "1.6", Some(true)),
+            (">=0.14 <0.15", "0.14.21", Some(true)),
+            (">=0.14 <0.15", "0.15", Some(false)),
+            (">=0.14 <0.15", "0.13.3", Some(false)),
+            ("~1.2.3", "1.2.9", Some(true)),
+            ("~1.2.3", "1.3.0", Some(false)),
+            ("^1.2", "1.9", Some(true)),
+            ("^1.2", "2.0", Some(false)),
+            ("1.20.x", "1.20.4", Some(true)),
+            ("1.20.x", "1.21", Some(false)),
+            ("*", "1.2.3", Some(true)),
+            ("1.19.2", "1.19.2", Some(true)),
+            ("1.19.2", "1.19.3", Some(false)),
+            (">=1.0", "not a version", None),
+        ];
+        for (range, version, expected) in cases {
+            assert_eq!(versions(range).contains(version), expected, "{} in {}", version, range);
+        }
+        // A plain Maven version is only a recommendation
+        assert_eq!(Versions::maven("1.0").contains("5.0"), Some(true));
+        assert_eq!(Versions::maven("").contains("5.0"), Some(true));
+    }
+
+    #[test]
+    fn reads_fabric_mod_json() {
+        let mods = read_mods(&jar(&[("fabric.mod.json", FABRIC_MOD.as_bytes())]), false).unwrap();
+        assert_eq!(mods.len(), 1);
+        let sodium = &mods[0];
+        assert_eq!(sodium.id, "sodium");
+        assert_eq!(sodium.display_name(), "Sodium");
+        assert_eq!(sodium.version.as_deref(), Some("0.5.3"));
+        assert_eq!(sodium.loader, ModLoader::Fabric);
+        assert_eq!(sodium.provides, ["rubidium"]);
+        let minecraft = sodium.minecraft.as_ref().unwrap();
+        assert_eq!(minecraft.contains("1.20.2"), Some(true));
+        assert_eq!(minecraft.contains("1.20.4"), Some(false));
+
+        let dependencies: Vec<(&str, bool)> = sodium.dependencies.iter().map(|d| (d.id.as_str(), d.required)).collect();
+        assert!(dependencies.contains(&("fabric-api", true)));
+        assert!(dependencies.contains(&("fabricloader", true)));
+        assert!(dependencies.contains(&("indium", false)));
+    }
+
+    #[test]
+    fn reads_mods_toml_with_the_manifest_version() {
+        let data = jar(&[
+            ("META-INF/mods.toml", MODS_TOML.as_bytes()),
+            ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\nImplementation-Version: 0.5.1.f\r\n"),
+        ]);
+        let mods = read_mods(&data, false).unwrap();
+        assert_eq!(mods.len(), 1);
+        let create = &mods[0];
+        assert_eq!(create.id, "create");
+        assert_eq!(create.display_name(), "Create");
+        assert_eq!(create.version.as_deref(), Some("0.5.1.f"));
+        assert_eq!(create.loader, ModLoader::Forge);
+        assert_eq!(create.minecraft.as_ref().unwrap().contains("1.20.1"), Some(true));
+        assert_eq!(create.minecraft.as_ref().unwrap().contains("1.20.2"), Some(false));
+        let dependencies: Vec<(&str, bool)> = create.dependencies.iter().map(|d| (d.id.as_str(), d.required)).collect();
+        assert_eq!(dependencies, [("flywheel", true), ("jei", false)]);
+    }
+
+    #[test]
+    fn reads_neoforge_metadata_and_bundled_jars() {
+        let toml = r#"
+            [[mods]]
+            modId = "library"
+            version = "2.0"
+            [[dependencies.library]]
+            modId = "neoforge"
+            type = "required"
+            versionRange = "[20.4,)"
+            [[dependencies.library]]
+            modId = "oldthing"
+            type = "incompatible"
+        "#;
+        let bundled = jar(&[("fabric.mod.json", br#"{"id": "inner", "version": "1.0"}"#)]);
+        let data = jar(&[("META-INF/neoforge.mods.toml", toml.as_bytes()), ("META-INF/jarjar/inner.jar", &bundled)]);
+        let mods = read_mods(&data, false).unwrap();
+        assert_eq!(mods.len(), 2);
+        assert_eq!((mods[0].id.as_str(), mods[0].loader, mods[0].bundled), ("library", ModLoader::NeoForge, false));
+        assert!(mods[0].dependencies.is_empty());
+        assert_eq!((mods[1].id.as_str(), mods[1].bundled), ("inner", true));
+    }
+
+    #[test]
+    fn reports_broken_metadata() {
+        assert!(matches!(
+            read_mods(&jar(&[("META-INF/mods.toml", b"[[mods]\nmodId = ")]), false),
+            Err(ModError::Invalid { .. })
+        ));
+        assert!(matches!(
+            read_mods(&jar(&[("fabric.mod.json", b"{\"name\": \"no id\"}")]), false),
+            Err(ModError::Invalid { .. })
+        ));
+        assert!(matches!(read_mods(b"not a zip", false), Err(ModError::Zip(_))));
+        assert_eq!(read_mods(&jar(&[("assets/x.png", b"")]), false).unwrap(), []);
+    }
+
+    fn fabric_jar(file: &str, json: &str) -> ModJar {
+        ModJar {
+            path: PathBuf::from(file),
+            mods: vec![fabric(json, false).unwrap()],
+        }
+    }
+
+    fn ids(diagnoses: &[Diagnosis]) -> Vec<&str> {
+        diagnoses.iter().map(|d| d.id).collect()
+    }
+
+    #[test]
+    fn finds_nothing_when_everything_fits() {
+        let jars = [
+            fabric_jar("sodium.jar", FABRIC_MOD),
+            fabric_jar("fabric-api.jar", r#"{"id": "fabric-api", "version": "0.90.0", "provides": ["fabric"]}"#),
+        ];
+        assert_eq!(check(&jars, Some(ModLoader::Fabric), Some("1.20.1")), []);
+    }
+
+    #[test]
+    fn finds_mods_for_other_minecraft_versions() {
+        let jars = [
+            fabric_jar("sodium.jar", FABRIC_MOD),
+            fabric_jar("fabric-api.jar", r#"{"id": "fabric-api", "version": "0.90.0"}"#),
+        ];
+        let diagnoses = check(&jars, Some(ModLoader::Fabric), Some("1.19.2"));
+        assert_eq!(ids(&diagnoses), [ResponseId::ModForOtherMinecraft.as_str()]);
+        assert!(diagnoses[0].message.contains("Sodium"));
+        assert_eq!(diagnoses[0].actions, [Action::RemoveMod("sodium".to_string())]);
+    }
+
+    #[test]
+    fn finds_missing_fabric_api_once() {
+        let jars = [
+            fabric_jar("sodium.jar", FABRIC_MOD),
+            fabric_jar("lithium.jar", r#"{"id": "lithium", "depends": {"fabric": "*"}}"#),
+        ];
+        let diagnoses = check(&jars, Some(ModLoader::Fabric), Some("1.20.1"));
+        assert_eq!(ids(&diagnoses), [ResponseId::MissingFabricApi.as_str()]);
+        assert_eq!(diagnoses[0].actions, [Action::InstallMod("fabric-api".to_string())]);
+    }
+
+    #[test]
+    fn finds_missing_and_incompatible_dependencies() {
+        let jars = [
+            fabric_jar("addon.jar", r#"{"id": "addon", "name": "Addon", "depends": {"core": ">=2.0", "lib": "*"}, "recommends": {"extra": "*"}}"#),
+            fabric_jar("core.jar", r#"{"id": "core", "version": "1.5"}"#),
+        ];
+        let diagnoses = check(&jars, Some(ModLoader::Fabric), None);
+        assert_eq!(
+            ids(&diagnoses),
+            [ResponseId::IncompatibleDependency.as_str(), ResponseId::MissingDependency.as_str()]
+        );
+        assert!(diagnoses[0].message.contains(">=2.0"));
+        assert_eq!(diagnoses[0].actions, [Action::InstallMod("core".to_string())]);
+        assert_eq!(diagnoses[1].actions, [Action::InstallMod("lib".to_string())]);
+    }
+}
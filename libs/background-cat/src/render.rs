@@ -0,0 +1,336 @@
+//! Responses, notes and other messages are written in a small markup that each frontend
+//! renders its own way:
+//!
+//! - `[text](https://...)` is a link, and so is a bare `https://...` URL,
+//! - `` `code` `` is code, like a JVM argument or a path,
+//! - a newline starts a new line.
+//!
+//! Everything else is plain text.
+
+/// A piece of a message in the markup described in the [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Span<'a> {
+    Text(&'a str),
+    Code(&'a str),
+    Link { text: &'a str, url: &'a str },
+    LineBreak,
+}
+
+/// Splits `markup` into spans. Unclosed code or links are kept as text.
+pub fn parse(markup: &str) -> Vec<Span<'_>> {
+    let mut spans = Vec::new();
+    let mut text_start = 0;
+    let mut i = 0;
+    while i < markup.len() {
+        let rest = &markup[i..];
+        let found = if rest.starts_with('\n') {
+            Some((Span::LineBreak, 1))
+        } else if let Some(code) = rest.strip_prefix('`') {
+            code.find('`').map(|end| (Span::Code(&code[..end]), end + 2))
+        } else if rest.starts_with('[') {
+            link(rest)
+        } else if rest.starts_with("https://") || rest.starts_with("http://") {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            // A sentence ending right after a URL isn't part of it.
+            let url = rest[..end].trim_end_matches(['.', ',', ')', '!', '?', ':', ';']);
+            Some((Span::Link { text: url, url }, url.len()))
+        } else {
+            None
+        };
+        match found {
+            Some((span, len)) => {
+                if text_start < i {
+                    spans.push(Span::Text(&markup[text_start..i]));
+                }
+                spans.push(span);
+                i += len;
+                text_start = i;
+            }
+            None => i += rest.chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    if text_start < markup.len() {
+        spans.push(Span::Text(&markup[text_start..]));
+    }
+    spans
+}
+
+/// A `[text](url)` link at the start of `s`, and its length.
+fn link(s: &str) -> Option<(Span<'_>, usize)> {
+    let text_end = s.find("](")?;
+    let url_end = text_end + 2 + s[text_end + 2..].find(')')?;
+    let text = &s[1..text_end];
+    let url = &s[text_end + 2..url_end];
+    if text.contains(['\n', '[']) || url.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((Span::Link { text, url }, url_end + 1))
+}
+
+/// Turns markup into the format of a frontend.
+pub trait Renderer {
+    fn text(&self, out: &mut String, text: &str);
+    fn code(&self, out: &mut String, code: &str);
+    fn link(&self, out: &mut String, text: &str, url: &str);
+    fn line_break(&self, out: &mut String) {
+        out.push('\n');
+    }
+
+    fn render(&self, markup: &str) -> String {
+        let mut out = String::with_capacity(markup.len());
+        for span in parse(markup) {
+            match span {
+                Span::Text(text) => self.text(&mut out, text),
+                Span::Code(code) => self.code(&mut out, code),
+                Span::Link { text, url } => self.link(&mut out, text, url),
+                Span::LineBreak => self.line_break(&mut out),
+            }
+        }
+        out
+    }
+}
+
+/// Discord flavoured markdown, which the markup is modelled after.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Discord;
+
+impl Renderer for Discord {
+    fn text(&self, out: &mut String, text: &str) {
+        out.push_str(text);
+    }
+
+    fn code(&self, out: &mut String, code: &str) {
+        out.push('`');
+        out.push_str(code);
+        out.push('`');
+    }
+
+    fn link(&self, out: &mut String, text: &str, url: &str) {
+        if text == url {
+            out.push_str(url);
+        } else {
+            out.push_str(&format!("[{}]({})", text, url));
+        }
+    }
+}
+
+/// Text for terminals, with colored code and clickable links (OSC 8) unless `color` is off.
+#[derive(Debug, Clone, Copy)]
+pub struct Terminal {
+    pub color: bool,
+}
+
+impl Renderer for Terminal {
+    fn text(&self, out: &mut String, text: &str) {
+        out.push_str(text);
+    }
+
+    fn code(&self, out: &mut String, code: &str) {
+        if self.color {
+            out.push_str(&format!("\x1b[36m{}\x1b[0m", code));
+        } else {
+            out.push_str(code);
+        }
+    }
+
+    fn link(&self, out: &mut String, text: &str, url: &str) {
+        if !self.color {
+            Plain.link(out, text, url);
+        } else {
+            out.push_str(&format!("\x1b]8;;{}\x1b\\\x1b[4;34m{}\x1b[0m\x1b]8;;\x1b\\", url, text));
+        }
+    }
+}
+
+/// An HTML fragment, safe to embed in a page.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Html;
+
+/// Escapes `text` for use in HTML text and attribute values.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl Renderer for Html {
+    fn text(&self, out: &mut String, text: &str) {
+        out.push_str(&escape_html(text));
+    }
+
+    fn code(&self, out: &mut String, code: &str) {
+        out.push_str(&format!("<code>{}</code>", escape_html(code)));
+    }
+
+    /// Only web links become `<a>` elements, so a `javascript:` link in a message can't run
+    /// anything. Other links are written as text.
+    fn link(&self, out: &mut String, text: &str, url: &str) {
+        let scheme = url.split_once(':').map_or("", |(scheme, _)| scheme);
+        if !scheme.eq_ignore_ascii_case("https") && !scheme.eq_ignore_ascii_case("http") {
+            let mut plain = String::new();
+            Plain.link(&mut plain, text, url);
+            out.push_str(&escape_html(&plain));
+            return;
+        }
+        out.push_str(&format!(
+            "<a href=\"{}\">{}</a>",
+            escape_html(url),
+            escape_html(text)
+        ));
+    }
+
+    fn line_break(&self, out: &mut String) {
+        out.push_str("<br>\n");
+    }
+}
+
+/// Text without any formatting. Links are written as "text (url)".
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Plain;
+
+impl Renderer for Plain {
+    fn text(&self, out: &mut String, text: &str) {
+        out.push_str(text);
+    }
+
+    fn code(&self, out: &mut String, code: &str) {
+        out.push_str(code);
+    }
+
+    fn link(&self, out: &mut String, text: &str, url: &str) {
+        if text == url {
+            out.push_str(url);
+        } else {
+            out.push_str(&format!("{} ({})", text, url));
+        }
+    }
+}
+
+/// Markdown, as on GitHub. Text is escaped so it can't start lists or headings.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Markdown;
+
+impl Renderer for Markdown {
+    fn text(&self, out: &mut String, text: &str) {
+        for c in text.chars() {
+            if matches!(c, '\\' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '`') {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+    }
+
+    fn code(&self, out: &mut String, code: &str) {
+        // A code span can contain backticks if it's delimited by more of them
+        let fence = if code.contains('`') { "`` " } else { "`" };
+        out.push_str(fence);
+        out.push_str(code);
+        out.push_str(&fence.chars().rev().collect::<String>());
+    }
+
+    fn link(&self, out: &mut String, text: &str, url: &str) {
+        if text == url {
+            out.push_str(&format!("<{}>", url));
+        } else {
+            out.push('[');
+            self.text(out, text);
+            out.push_str(&format!("]({})", url));
+        }
+    }
+
+    fn line_break(&self, out: &mut String) {
+        // Two trailing spaces keep the next line in the same paragraph or list item
+        out.push_str("  \n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_links_code_and_line_breaks() {
+        assert_eq!(
+            parse("Run `java -version`.\n[See the wiki](https://example.org/wiki) or https://example.org."),
+            [
+                Span::Text("Run "),
+                Span::Code("java -version"),
+                Span::Text("."),
+                Span::LineBreak,
+                Span::Link {
+                    text: "See the wiki",
+                    url: "https://example.org/wiki"
+                },
+                Span::Text(" or "),
+                Span::Link {
+                    text: "https://example.org",
+                    url: "https://example.org"
+                },
+                Span::Text("."),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_unclosed_markup_as_text() {
+        assert_eq!(parse("a `b"), [Span::Text("a `b")]);
+        assert_eq!(
+            parse("[text](https://example.org"),
+            [
+                Span::Text("[text]("),
+                Span::Link {
+                    text: "https://example.org",
+                    url: "https://example.org"
+                },
+            ]
+        );
+        assert_eq!(parse("[text] (x)"), [Span::Text("[text] (x)")]);
+        assert_eq!(parse("[a\nb](x)"), [Span::Text("[a"), Span::LineBreak, Span::Text("b](x)")]);
+    }
+
+    #[test]
+    fn parses_markup_inside_other_markup_as_its_content() {
+        // Code isn't parsed any further
+        assert_eq!(parse("`[a](https://x.org)`"), [Span::Code("[a](https://x.org)")]);
+        // Link texts aren't either, and can't contain other links
+        assert_eq!(
+            parse("[`a`](https://x.org)"),
+            [Span::Link {
+                text: "`a`",
+                url: "https://x.org"
+            }]
+        );
+        assert_eq!(
+            parse("[a [b](https://x.org)"),
+            [
+                Span::Text("[a "),
+                Span::Link {
+                    text: "b",
+                    url: "https://x.org"
+                },
+            ]
+        );
+        assert_eq!(parse("[a](x) `b"), [Span::Link { text: "a", url: "x" }, Span::Text(" `b")]);
+    }
+
+    #[test]
+    fn only_links_to_the_web_in_html() {
+        assert_eq!(
+            Html.render("[wiki](https://example.org/?a=1&b=2)"),
+            "<a href=\"https://example.org/?a=1&amp;b=2\">wiki</a>"
+        );
+        assert_eq!(Html.render("[click](javascript:alert(1))"), "click (javascript:alert(1))");
+        assert_eq!(Html.render("[x](JavaScript:alert`1`)"), "x (JavaScript:alert`1`)");
+        assert_eq!(Html.render("[a](data:text/html,<b>)"), "a (data:text/html,&lt;b&gt;)");
+        assert_eq!(Html.render("[a](/relative)"), "a (/relative)");
+    }
+}
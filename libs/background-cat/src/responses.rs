@@ -1,153 +1,409 @@
+use std::{fmt, str::FromStr};
+
 use indoc::indoc;
-use lazy_static::lazy_static;
-use std::collections::HashMap;
-
-lazy_static! {
-    pub static ref RESPONSES: HashMap<&'static str, &'static str> = HashMap::from([
-        (
-            "program-files",
-            indoc! {"
-                Your MultiMC installation is in Program Files, where MultiMC doesn't have permission to write.\n\
-                You should move it somewhere else, like your Desktop."
-            }
-        ),
-        (
-            "macos-java-too-new",
-            indoc! {
-            "You are using too new a Java version. Please follow the steps on this wiki page to install 8u241: https://github.com/MultiMC/MultiMC5/wiki/Java-on-macOS"
-            }
-        ),
-        (
-            "id-limit",
-            indoc! {
-                    "You've exceeded the hardcoded ID Limit. Remove some mods, or install [JustEnoughIDs](https://www.curseforge.com/minecraft/mc-mods/jeid)"
-            }
-        ),
-        (
-            "out-of-memory",
-            indoc! {
-                "You've run out of memory. You should allocate more, although the exact value depends on how many mods you have installed. \
-                [Click this link for a guide.](https://raw.githubusercontent.com/MultiMC/background-cat/master/apps/discord-cat/src/images/set_ram.png)"
-            }
-        ),
-        (
-            "optifine-and-shadermod",
-            indoc! {
-                "You've installed Shaders Mod alongside OptiFine. OptiFine has built-in shader support, so you should remove Shaders Mod"
-            }
-        ),
-        (
-            "missing-fabric-api",
-            indoc! {
-                "You are missing Fabric API, which is required by a mod. \
-                [Download the needed version here](https://www.curseforge.com/minecraft/mc-mods/fabric-api)"
-            }
-        ),
-        (
-            "multimc-in-onedrive",
-            indoc! {
-                "MultiMC is possibly located in a folder managed by OneDrive, if you already have OneDrive disabled \
-                you can ignore this warning.\n\
-                OneDrive can mess with your game files while the game is running, and this often leads to crashes.\n\
-                You should move the MultiMC folder to a different folder."
-            }
-        ),
-        (
-            "use-java-8",
-            indoc! {
-                "The version of Minecraft you are playing does not support using modern versions of Java.\n\
-                [Please check our wiki for more information.](https://github.com/MultiMC/Launcher/wiki/Using-the-right-Java#minecraft-116-and-older)"
-            }
-        ),
-        (
-            "use-java-17",
-            indoc! {
-                "You are playing a version of Minecraft that requires Java 17 or newer, but are using an older Java version.\n\
-                [Please check our wiki for more information.](https://github.com/MultiMC/Launcher/wiki/Using-the-right-Java#minecraft-117-and-newer)"
-            }
-        ),
-        (
-            "use-java-21",
-            indoc! {
-                "You are playing a version of Minecraft that requires Java 21 or newer, but are using an older Java version.\n\
-                [Please check our wiki for more information.](https://github.com/MultiMC/Launcher/wiki/Using-the-right-Java#minecraft-210-and-newer)"
-            }
-        ),
-        (
-            "apple-silicon-incompatible-forge",
-            indoc! {
-                "You seem to be using an Apple M1 Mac with an incompatible version of Forge. Add the following to your launch arguments as a workaround: `-Dfml.earlyprogresswindow=false`"
-            }
-        ),
-        (
-            "unsupported-intel-gpu",
-            indoc! {
-                "You seem to be using an Intel GPU that is not supported on Windows 10. \
-                You will need to install an older version of Java, [see here for help](https://github.com/MultiMC/MultiMC5/wiki/Unsupported-Intel-GPUs)"
-            }
-        ),
-        (
-            "32-bit-java",
-            indoc! {
-                "You're using 32-bit Java. [See here for help installing the correct version.](https://github.com/MultiMC/MultiMC5/wiki/Using-the-right-Java)"
-            }
-        ),
-        (
-            "temp-folder",
-            indoc! {"
-                Your MultiMC installation is in a temporary folder. Your operating system will remove it.\n\
-                To prevent data loss, you should move it or extract it somewhere else, like the C: top directory."
-            }
-        ),
-        (
-            "winrar-temp",
-            indoc! {"
-                You did not extract MultiMC to a real folder and are running it from WinRar. Windows will remove it.\n\
-                To prevent data loss, you should extract it somewhere, like the C: top directory."
-            }
-        ),
-        (
-            "custom-build",
-            indoc! {
-                "You're using a self build version of MultiMC. We only support builds distributed from our website or packages from our wiki. [See here for official builds.](https://multimc.org)\n\
-                If you are building MultiMC as developer to add features, you might want to request access to #dev-talk to discuss the feature."
-            }
-        ),
-        (
-            "pirated-build",
-            indoc! {
-                "You're using a pirated Launcher. We only support builds distributed from our website or packages from our wiki that only work with a Mojang and Microsoft Account. [See here for official builds.](https://multimc.org)"
-            }
-        ),
-        (
-            "forked-build",
-            indoc! {
-                "You're using a forked build of MultiMC. We only support builds distributed from our website or packages from our wiki. [See here for official builds.](https://multimc.org)"
-            }
-        ),
-        (
-            "m1-python-wrapper",
-            indoc! {
-                "You're using MultiMC with the M1 python wrapper. Please do not use this to make MultiMC work on M1. Instead run MultiMC via the Rosetta 2 compatibility layer and with the usage of a x86_64 Java binary."
+use serde::{Serialize, Serializer};
+
+/// Languages responses are available in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Locale {
+    #[default]
+    En,
+}
+
+impl Locale {
+    pub const ALL: &'static [Locale] = &[Locale::En];
+}
+
+/// Defines [`ResponseId`] with a text for every locale, in the markup described in
+/// [`crate::render`]. Leaving out a locale for a response is a compile error, since the match
+/// in [`ResponseId::text`] wouldn't be exhaustive.
+macro_rules! responses {
+    ($($variant:ident = $id:literal { $($locale:ident: $text:expr),+ $(,)? })*) => {
+        /// A response the bot can give. Rules refer to these instead of strings, so a typo
+        /// doesn't silently disable a rule.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum ResponseId {
+            $($variant,)*
+        }
+
+        impl ResponseId {
+            pub const ALL: &'static [ResponseId] = &[$(ResponseId::$variant,)*];
+
+            /// The id as used in reports, e.g. "program-files".
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $(ResponseId::$variant => $id,)*
+                }
             }
-        ),
-        (
-            "using-system-openal",
-            indoc! {
-                "You seem to be using your systems OpenAL installation. This can cause the instance to crash if not properly setup. In case of a crash, make sure this isn't the cause of it."
+
+            pub fn text(self, locale: Locale) -> &'static str {
+                match (self, locale) {
+                    $($((ResponseId::$variant, Locale::$locale) => $text,)+)*
+                }
             }
-        ),
-        (
-            "using-system-glfw",
-            indoc! {
-                "You seem to be using your systems GLFW installation. This can cause the instance to crash if not properly setup. In case of a crash, make sure this isn't the cause of it."
+
+            /// The text with `{name}` placeholders replaced by the values in `args`, for
+            /// responses about a specific mod or version.
+            pub fn format(self, locale: Locale, args: &[(&str, &str)]) -> String {
+                args.iter().fold(self.text(locale).to_string(), |text, (name, value)| {
+                    text.replace(&format!("{{{}}}", name), value)
+                })
             }
-        ),
-        (
-            "reboot-required",
-            indoc! {
-                "Another process appears to be locking your native library JARs. To solve this, please reboot your computer."
+        }
+    };
+}
+
+responses! {
+    ProgramFiles = "program-files" {
+        En: indoc! {"
+            Your MultiMC installation is in Program Files, where MultiMC doesn't have permission to write.\n\
+            You should move it somewhere else, like your Desktop."
+        },
+    }
+    MacosJavaTooNew = "macos-java-too-new" {
+        En: indoc! {
+        "You are using too new a Java version. Please follow the steps on this wiki page to install 8u241: https://github.com/MultiMC/MultiMC5/wiki/Java-on-macOS"
+        },
+    }
+    IdLimit = "id-limit" {
+        En: indoc! {
+                "You've exceeded the hardcoded ID Limit. Remove some mods, or install [JustEnoughIDs](https://www.curseforge.com/minecraft/mc-mods/jeid)"
+        },
+    }
+    OutOfMemory = "out-of-memory" {
+        En: indoc! {
+            "You've run out of memory. You should allocate more, although the exact value depends on how many mods you have installed. \
+            [Click this link for a guide.](https://raw.githubusercontent.com/MultiMC/background-cat/master/apps/discord-cat/src/images/set_ram.png)"
+        },
+    }
+    OptifineAndShadermod = "optifine-and-shadermod" {
+        En: indoc! {
+            "You've installed Shaders Mod alongside OptiFine. OptiFine has built-in shader support, so you should remove Shaders Mod"
+        },
+    }
+    MissingFabricApi = "missing-fabric-api" {
+        En: indoc! {
+            "You are missing Fabric API, which is required by a mod. \
+            [Download the needed version here](https://www.curseforge.com/minecraft/mc-mods/fabric-api)"
+        },
+    }
+    MultimcInOnedrive = "multimc-in-onedrive" {
+        En: indoc! {
+            "MultiMC is possibly located in a folder managed by OneDrive, if you already have OneDrive disabled \
+            you can ignore this warning.\n\
+            OneDrive can mess with your game files while the game is running, and this often leads to crashes.\n\
+            You should move the MultiMC folder to a different folder."
+        },
+    }
+    UseJava8 = "use-java-8" {
+        En: indoc! {
+            "The version of Minecraft you are playing does not support using modern versions of Java.\n\
+            [Please check our wiki for more information.](https://github.com/MultiMC/Launcher/wiki/Using-the-right-Java#minecraft-116-and-older)"
+        },
+    }
+    UseJava16 = "use-java-16" {
+        En: indoc! {
+            "You are playing a version of Minecraft that requires Java 16 or newer, but are using an older Java version.\n\
+            [Please check our wiki for more information.](https://github.com/MultiMC/Launcher/wiki/Using-the-right-Java#minecraft-117-and-newer)"
+        },
+    }
+    UseJava17 = "use-java-17" {
+        En: indoc! {
+            "You are playing a version of Minecraft that requires Java 17 or newer, but are using an older Java version.\n\
+            [Please check our wiki for more information.](https://github.com/MultiMC/Launcher/wiki/Using-the-right-Java#minecraft-117-and-newer)"
+        },
+    }
+    UseJava21 = "use-java-21" {
+        En: indoc! {
+            "You are playing a version of Minecraft that requires Java 21 or newer, but are using an older Java version.\n\
+            [Please check our wiki for more information.](https://github.com/MultiMC/Launcher/wiki/Using-the-right-Java#minecraft-210-and-newer)"
+        },
+    }
+    AppleSiliconIncompatibleForge = "apple-silicon-incompatible-forge" {
+        En: indoc! {
+            "You seem to be using an Apple M1 Mac with an incompatible version of Forge. Add the following to your launch arguments as a workaround: `-Dfml.earlyprogresswindow=false`"
+        },
+    }
+    UnsupportedIntelGpu = "unsupported-intel-gpu" {
+        En: indoc! {
+            "You seem to be using an Intel GPU that is not supported on Windows 10. \
+            You will need to install an older version of Java, [see here for help](https://github.com/MultiMC/MultiMC5/wiki/Unsupported-Intel-GPUs)"
+        },
+    }
+    Java32Bit = "32-bit-java" {
+        En: indoc! {
+            "You're using 32-bit Java. [See here for help installing the correct version.](https://github.com/MultiMC/MultiMC5/wiki/Using-the-right-Java)"
+        },
+    }
+    TempFolder = "temp-folder" {
+        En: indoc! {"
+            Your MultiMC installation is in a temporary folder. Your operating system will remove it.\n\
+            To prevent data loss, you should move it or extract it somewhere else, like the C: top directory."
+        },
+    }
+    WinrarTemp = "winrar-temp" {
+        En: indoc! {"
+            You did not extract MultiMC to a real folder and are running it from WinRar. Windows will remove it.\n\
+            To prevent data loss, you should extract it somewhere, like the C: top directory."
+        },
+    }
+    CustomBuild = "custom-build" {
+        En: indoc! {
+            "You're using a self build version of MultiMC. We only support builds distributed from our website or packages from our wiki. [See here for official builds.](https://multimc.org)\n\
+            If you are building MultiMC as developer to add features, you might want to request access to #dev-talk to discuss the feature."
+        },
+    }
+    PiratedBuild = "pirated-build" {
+        En: indoc! {
+            "You're using a pirated Launcher. We only support builds distributed from our website or packages from our wiki that only work with a Mojang and Microsoft Account. [See here for official builds.](https://multimc.org)"
+        },
+    }
+    OfflineAccount = "offline-account" {
+        En: indoc! {
+            "You seem to be playing with an offline or cracked account. We only help people who own the game and log in with a Microsoft account. [See here for official builds.](https://multimc.org)"
+        },
+    }
+    ForkedBuild = "forked-build" {
+        En: indoc! {
+            "You're using a forked build of MultiMC. We only support builds distributed from our website or packages from our wiki. [See here for official builds.](https://multimc.org)"
+        },
+    }
+    UnsupportedLauncher = "unsupported-launcher" {
+        En: indoc! {
+            "You're using a version of MultiMC that we don't support. Please update to the latest version. [See here for official builds.](https://multimc.org)"
+        },
+    }
+    UnsupportedLauncherWarning = "unsupported-launcher-warning" {
+        En: indoc! {
+            "We don't fully support the launcher you're using. The suggestions below might still help, but the launcher's own support channels may know better."
+        },
+    }
+    M1PythonWrapper = "m1-python-wrapper" {
+        En: indoc! {
+            "You're using MultiMC with the M1 python wrapper. Please do not use this to make MultiMC work on M1. Instead run MultiMC via the Rosetta 2 compatibility layer and with the usage of a x86_64 Java binary."
+        },
+    }
+    JvmArgsMemory = "jvm-args-memory" {
+        En: indoc! {
+            "The JVM arguments of the instance set the memory with `-Xmx` or `-Xms`, which overrides the memory settings. \
+            Remove them and set the memory in the instance settings instead."
+        },
+    }
+    JvmArgsRemovedGc = "jvm-args-removed-gc" {
+        En: indoc! {
+            "The JVM arguments of the instance use `-XX:+UseConcMarkSweepGC`, which Java 14 and newer don't have anymore. \
+            Remove it from the JVM arguments."
+        },
+    }
+    UsingSystemOpenal = "using-system-openal" {
+        En: indoc! {
+            "You seem to be using your systems OpenAL installation. This can cause the instance to crash if not properly setup. In case of a crash, make sure this isn't the cause of it."
+        },
+    }
+    UsingSystemGlfw = "using-system-glfw" {
+        En: indoc! {
+            "You seem to be using your systems GLFW installation. This can cause the instance to crash if not properly setup. In case of a crash, make sure this isn't the cause of it."
+        },
+    }
+    MissingDependency = "missing-dependency" {
+        En: indoc! {
+            "`{mod}` requires `{dependency}`, which isn't installed. Install it, or remove `{mod}`."
+        },
+    }
+    IncompatibleDependency = "incompatible-dependency" {
+        En: indoc! {
+            "`{mod}` requires version `{versions}` of `{dependency}`, but {version} is installed. \
+            Install a version of `{dependency}` that fits, or remove `{mod}`."
+        },
+    }
+    ModForOtherMinecraft = "mod-for-other-minecraft" {
+        En: indoc! {
+            "`{mod}` is made for Minecraft `{versions}`, but the instance uses Minecraft {minecraft}. \
+            Look for a version of it for Minecraft {minecraft}, or remove it."
+        },
+    }
+    WrongLoaderMod = "wrong-loader-mod" {
+        En: indoc! {
+            "`{mod}` seems to be a mod for {mod_loader}, but the instance uses {loader}. \
+            Look for a {loader} version of it, or remove it."
+        },
+    }
+    ComponentMismatch = "component-mismatch" {
+        En: indoc! {
+            "{component} {version} is made for {required} {needed}, but the instance has {required} {actual}. \
+            Change the version of one of them in the instance's version settings."
+        },
+    }
+    RebootRequired = "reboot-required" {
+        En: indoc! {
+            "Another process appears to be locking your native library JARs. To solve this, please reboot your computer."
+        },
+    }
+    LauncherLogErrors = "launcher-log-errors" {
+        En: indoc! {
+            "The launcher logged errors ({count} in total). They may explain problems with downloading or launching the game."
+        },
+    }
+    ExitCode = "exit-code" {
+        En: indoc! {
+            "The game exited with code {code} ({name}). {explanation}"
+        },
+    }
+    CrashExitCode = "crash-exit-code" {
+        En: indoc! {
+            "The game crashed with code {code} ({name}). {explanation}"
+        },
+    }
+    ExitAccessViolation = "exit-access-violation" {
+        En: indoc! {
+            "The game tried to access memory it wasn't allowed to. This is usually caused by outdated or broken graphics drivers, overlays, or a native library crashing."
+        },
+    }
+    ExitStackOverflow = "exit-stack-overflow" {
+        En: indoc! {
+            "The game ran out of stack space. This is usually caused by a mod stuck in infinite recursion."
+        },
+    }
+    ExitDllNotFound = "exit-dll-not-found" {
+        En: indoc! {
+            "A required DLL could not be found. Reinstalling Java or the Visual C++ redistributables usually fixes this."
+        },
+    }
+    ExitEntrypointNotFound = "exit-entrypoint-not-found" {
+        En: indoc! {
+            "A DLL is missing a required function. This usually means a native library or driver is outdated."
+        },
+    }
+    ExitDllInitFailed = "exit-dll-init-failed" {
+        En: indoc! {
+            "A DLL failed to initialize. Antivirus software or a broken Java installation are common causes."
+        },
+    }
+    ExitStackBufferOverrun = "exit-stack-buffer-overrun" {
+        En: indoc! {
+            "A native component detected memory corruption and terminated the game. Graphics drivers and overlays are common causes."
+        },
+    }
+    ExitIllegalInstruction = "exit-illegal-instruction" {
+        En: indoc! {
+            "The processor was asked to run an instruction it doesn't support. This can happen with Java builds made for a different CPU architecture."
+        },
+    }
+    ExitHeapCorruption = "exit-heap-corruption" {
+        En: indoc! {
+            "Native memory was corrupted. Graphics drivers, overlays and native mods are common causes."
+        },
+    }
+    ExitNoMemory = "exit-no-memory" {
+        En: indoc! {
+            "Windows could not give the game enough memory. Close other programs or lower the memory allocation."
+        },
+    }
+    ExitControlC = "exit-control-c" {
+        En: indoc! {
+            "The game was terminated, for example by closing its console window or pressing Ctrl+C."
+        },
+    }
+    ExitKilledFromOutside = "exit-killed-from-outside" {
+        En: indoc! {
+            "The game was killed from outside, for example by the task manager, an antivirus or logging off."
+        },
+    }
+    ExitNotResponding = "exit-not-responding" {
+        En: indoc! {
+            "Windows killed the game because it stopped responding. This is often caused by the game running out of memory or a mod freezing the game."
+        },
+    }
+    ExitAborted = "exit-aborted" {
+        En: indoc! {
+            "Java hit a fatal error and aborted. Look for an `hs_err_pid` file in the instance folder."
+        },
+    }
+    ExitBusError = "exit-bus-error" {
+        En: indoc! {
+            "The game accessed invalid memory. This is usually caused by a native library or a full disk."
+        },
+    }
+    ExitKilled = "exit-killed" {
+        En: indoc! {
+            "The game was killed. On Linux this is usually the out-of-memory killer, so try lowering the memory allocation or closing other programs."
+        },
+    }
+    ExitNativeCrash = "exit-native-crash" {
+        En: indoc! {
+            "The game crashed in native code. This is usually caused by graphics drivers or a native library."
+        },
+    }
+    ExitTerminated = "exit-terminated" {
+        En: indoc! {
+            "The game was asked to terminate by another program or the operating system."
+        },
+    }
+    ExitJavaError = "exit-java-error" {
+        En: indoc! {
+            "Java exited with an error. The cause is usually an exception further up in the log, or Java failing to start with the given arguments."
+        },
+    }
+    ExitGameError = "exit-game-error" {
+        En: indoc! {
+            "The game exited with an error. The cause is usually an exception further up in the log or in the crash report."
+        },
+    }
+}
+
+/// A response id that isn't in the catalogue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownResponse(pub String);
+
+impl fmt::Display for UnknownResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown response `{}`", self.0)
+    }
+}
+
+impl std::error::Error for UnknownResponse {}
+
+impl FromStr for ResponseId {
+    type Err = UnknownResponse;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ResponseId::ALL
+            .iter()
+            .copied()
+            .find(|id| id.as_str() == s)
+            .ok_or_else(|| UnknownResponse(s.to_string()))
+    }
+}
+
+impl fmt::Display for ResponseId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for ResponseId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_response_has_text_in_every_locale() {
+        for id in ResponseId::ALL {
+            for locale in Locale::ALL {
+                assert!(!id.text(*locale).trim().is_empty(), "{} has no {:?} text", id, locale);
             }
-        )
-    ]);
+        }
+    }
+
+    #[test]
+    fn ids_are_unique() {
+        for id in ResponseId::ALL {
+            assert_eq!(id.as_str().parse(), Ok(*id));
+        }
+    }
 }
@@ -0,0 +1,204 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{
+    actions::default_actions,
+    input,
+    install_location_problems,
+    instance::{catalogued, instance_diagnosis},
+    Diagnosis, Evidence, Locale, ResponseId,
+};
+
+/// A MultiMC folder, with its `instances` folder and launcher logs, as users send them zipped
+/// up.
+#[derive(Debug, Clone)]
+pub struct LauncherRoot {
+    pub dir: PathBuf,
+}
+
+impl LauncherRoot {
+    /// Opens `dir`, failing if it has no `instances` folder.
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<LauncherRoot> {
+        let dir = dir.into();
+        if !dir.join("instances").is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} has no instances folder", dir.display()),
+            ));
+        }
+        Ok(LauncherRoot { dir })
+    }
+
+    /// The folders under `instances` that have an `instance.cfg`, sorted by name.
+    pub fn instances(&self) -> io::Result<Vec<PathBuf>> {
+        let mut instances = Vec::new();
+        for entry in fs::read_dir(self.dir.join("instances"))? {
+            let path = entry?.path();
+            if path.join("instance.cfg").is_file() {
+                instances.push(path);
+            }
+        }
+        instances.sort();
+        Ok(instances)
+    }
+
+    /// The log of the last launcher run, like `MultiMC-0.log` or the one of a fork like Prism
+    /// Launcher, in the folder itself or in `logs`.
+    pub fn launcher_log(&self) -> Option<PathBuf> {
+        LAUNCHER_LOGS
+            .iter()
+            .flat_map(|name| [self.dir.join(name), self.dir.join("logs").join(name)])
+            .find(|path| path.is_file())
+    }
+
+    /// Looks for problems with the launcher itself: where it's installed, and errors in its
+    /// own log.
+    pub fn inspect(&self) -> io::Result<Vec<Diagnosis>> {
+        let mut diagnoses = install_location(&self.dir);
+        if let Some(path) = self.launcher_log() {
+            let bytes = fs::read(&path)?;
+            let log = input::decode(&bytes);
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            diagnoses.extend(launcher_log_errors(&log, &name));
+        }
+        Ok(diagnoses)
+    }
+}
+
+/// Problems with installing the launcher in `dir`, like Program Files or a temporary folder.
+pub fn install_location(dir: &Path) -> Vec<Diagnosis> {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    let path = dir.to_string_lossy().replace('\\', "/");
+    // Canonical Windows paths start with \\?\
+    let path = path.trim_start_matches("//?/");
+    install_location_problems(path)
+        .into_iter()
+        .map(|response| {
+            let severity = if response == ResponseId::MultimcInOnedrive { "❗" } else { "‼" };
+            let mut diagnosis = catalogued(response, severity, None);
+            diagnosis.actions = default_actions(response);
+            diagnosis
+        })
+        .collect()
+}
+
+/// Critical and fatal messages in a launcher log, which has lines like
+/// `   12.345 C | Failed to ...`.
+fn launcher_log_errors(log: &str, source: &str) -> Option<Diagnosis> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"(?m)^[ \t]*[0-9.]+ [CF] \| .*$").unwrap();
+    }
+    let errors: Vec<Evidence> = RE
+        .find_iter(log)
+        .take(MAX_LAUNCHER_ERRORS)
+        .map(|m| {
+            let mut evidence = Evidence::at(log, m.start());
+            evidence.source = Some(source.to_string());
+            evidence
+        })
+        .collect();
+    if errors.is_empty() {
+        return None;
+    }
+    let count = RE.find_iter(log).count();
+    let message = ResponseId::LauncherLogErrors.format(Locale::default(), &[("count", &count.to_string())]);
+    let mut diagnosis = instance_diagnosis(ResponseId::LauncherLogErrors.as_str(), "❗", message, None);
+    diagnosis.evidence = errors;
+    Some(diagnosis)
+}
+
+/// Names of the logs of the last run of MultiMC and its forks.
+const LAUNCHER_LOGS: [&str; 7] = [
+    "MultiMC-0.log",
+    "PrismLauncher-0.log",
+    "PolyMC-0.log",
+    "PollyMC-0.log",
+    "FjordLauncher-0.log",
+    "ManyMC-0.log",
+    "UltimMC-0.log",
+];
+
+/// Errors of the launcher log shown as evidence, the rest are only counted.
+const MAX_LAUNCHER_ERRORS: usize = 5;
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const LAUNCHER_LOG: &str = "    0.000 D | MultiMC 5, (c) 2013-2021 MultiMC Contributors\n\
+        \x20   0.001 D | Version                    : 0.7.0-3275\n\
+        \x20   0.250 W | Couldn't load metadata cache\n\
+        \x20  12.345 C | Failed to download https://libraries.minecraft.net/x.jar: Connection refused\n\
+        \x20  12.500 D | Download finished\n\
+        \x20  13.000 F | ASSERT: \"m_instance\" in file launch/LaunchTask.cpp\n";
+
+    /// A MultiMC folder with the instances `Vanilla` and `Modded`, a folder without an
+    /// `instance.cfg`, and a launcher log.
+    fn launcher_root() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for (name, pack) in [("Vanilla", None), ("Modded", Some(r#"{"components": []}"#))] {
+            let instance = dir.path().join("instances").join(name);
+            fs::create_dir_all(&instance).unwrap();
+            fs::write(instance.join("instance.cfg"), format!("name={}\n", name)).unwrap();
+            if let Some(pack) = pack {
+                fs::write(instance.join("mmc-pack.json"), pack).unwrap();
+            }
+        }
+        fs::create_dir_all(dir.path().join("instances/_MMC_TEMP")).unwrap();
+        fs::write(dir.path().join("MultiMC-0.log"), LAUNCHER_LOG).unwrap();
+        dir
+    }
+
+    #[test]
+    fn finds_instances_and_the_launcher_log() {
+        let dir = launcher_root();
+        let root = LauncherRoot::open(dir.path()).unwrap();
+        let instances = root.instances().unwrap();
+        assert_eq!(instances, [dir.path().join("instances/Modded"), dir.path().join("instances/Vanilla")]);
+        assert_eq!(root.launcher_log(), Some(dir.path().join("MultiMC-0.log")));
+
+        let empty = TempDir::new().unwrap();
+        assert_eq!(LauncherRoot::open(empty.path()).unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn reports_errors_in_the_launcher_log() {
+        let dir = launcher_root();
+        let diagnoses = LauncherRoot::open(dir.path()).unwrap().inspect().unwrap();
+        assert_eq!(diagnoses.len(), 1);
+        let diagnosis = &diagnoses[0];
+        assert_eq!(diagnosis.id, "launcher-log-errors");
+        assert!(diagnosis.message.contains("(2 in total)"));
+        let lines: Vec<(Option<&str>, usize)> =
+            diagnosis.evidence.iter().map(|e| (e.source.as_deref(), e.line)).collect();
+        assert_eq!(lines, [(Some("MultiMC-0.log"), 4), (Some("MultiMC-0.log"), 6)]);
+    }
+
+    #[test]
+    fn finds_the_logs_of_forks() {
+        let dir = launcher_root();
+        fs::remove_file(dir.path().join("MultiMC-0.log")).unwrap();
+        let root = LauncherRoot::open(dir.path()).unwrap();
+        assert_eq!(root.launcher_log(), None);
+
+        fs::create_dir(dir.path().join("logs")).unwrap();
+        fs::write(dir.path().join("logs/PrismLauncher-0.log"), LAUNCHER_LOG).unwrap();
+        assert_eq!(root.launcher_log(), Some(dir.path().join("logs/PrismLauncher-0.log")));
+        let diagnoses = root.inspect().unwrap();
+        assert_eq!(diagnoses[0].evidence[0].source.as_deref(), Some("PrismLauncher-0.log"));
+    }
+
+    #[test]
+    fn finds_nothing_wrong_with_a_clean_launcher() {
+        let dir = launcher_root();
+        fs::write(dir.path().join("MultiMC-0.log"), "    0.000 D | MultiMC 5\n").unwrap();
+        assert!(LauncherRoot::open(dir.path()).unwrap().inspect().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,330 @@
+use std::fmt;
+
+use lazy_static::lazy_static;
+use log::error;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::{classify, facts, Analyzer, Classification, Diagnosis, Evidence, Facts, LogKind, OriginPolicy};
+
+/// Several files from one support case, e.g. the launcher log, a crash report and an
+/// `hs_err_pid` file, analyzed together.
+#[derive(Debug, Default)]
+pub struct Session {
+    inputs: Vec<(String, String)>,
+    analyzer: Analyzer,
+}
+
+/// What a [`Session`] found out about a single input.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InputSummary {
+    pub name: String,
+    pub classification: Classification,
+    pub facts: Facts,
+    /// When the game crashed, if the input records it.
+    pub crash_time: Option<CrashTime>,
+}
+
+/// Local time of a crash, to the minute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CrashTime {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionReport {
+    pub inputs: Vec<InputSummary>,
+    /// Remarks about how the inputs relate, e.g. that they come from different launches.
+    pub notes: Vec<String>,
+    pub diagnoses: Vec<Diagnosis>,
+}
+
+/// Crash times further apart than this are considered different crashes.
+const CRASH_TIME_TOLERANCE_MINUTES: i64 = 5;
+
+impl Session {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file to the session. `name` is only used to refer to the input in notes.
+    pub fn add(&mut self, name: impl Into<String>, content: impl Into<String>) -> &mut Self {
+        self.inputs.push((name.into(), content.into()));
+        self
+    }
+
+    /// Sets how launchers are treated, instead of the default [`OriginPolicy`].
+    pub fn policy(&mut self, policy: OriginPolicy) -> &mut Self {
+        self.analyzer.policy(policy);
+        self
+    }
+
+    /// Runs the inputs through `analyzer` instead of one with the built-in rules.
+    pub fn analyzer(&mut self, analyzer: Analyzer) -> &mut Self {
+        self.analyzer = analyzer;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// Runs all rules across the combined inputs, so a rule can match evidence spread over
+    /// several files.
+    pub fn analyze(&self) -> SessionReport {
+        let inputs: Vec<InputSummary> = self
+            .inputs
+            .iter()
+            .map(|(name, content)| {
+                let classification = classify(content);
+                InputSummary {
+                    name: name.clone(),
+                    classification,
+                    facts: facts(content),
+                    crash_time: crash_time(content),
+                }
+            })
+            .collect();
+
+        let kinds: Vec<LogKind> = inputs.iter().map(|i| i.classification.kind).collect();
+        let combined = self
+            .inputs
+            .iter()
+            .map(|(_, content)| content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let (mut diagnoses, errors) = self.analyzer.diagnose_kinds(&combined, &kinds);
+        for why in errors {
+            error!("{}", why);
+        }
+        for evidence in diagnoses.iter_mut().flat_map(|d| d.evidence.iter_mut()) {
+            self.locate(evidence);
+        }
+
+        SessionReport {
+            notes: correlate(&inputs),
+            diagnoses,
+            inputs,
+        }
+    }
+
+    /// Turns a line number in the combined inputs into the input and line it came from.
+    fn locate(&self, evidence: &mut Evidence) {
+        let mut first_line = 1;
+        for (name, content) in &self.inputs {
+            let lines = content.matches('\n').count() + 1;
+            if evidence.line < first_line + lines {
+                evidence.source = Some(name.clone());
+                evidence.line -= first_line - 1;
+                return;
+            }
+            first_line += lines;
+        }
+    }
+}
+
+impl SessionReport {
+    /// Whether one of the inputs is the log from the launcher, which has the most context.
+    pub fn has_launcher_log(&self) -> bool {
+        self.inputs
+            .iter()
+            .any(|i| i.classification.kind == LogKind::LauncherLog)
+    }
+
+    /// Whether a blocking finding, like a pirated launcher, was made.
+    pub fn is_blocked(&self) -> bool {
+        self.diagnoses.iter().any(|d| d.blocking)
+    }
+}
+
+fn correlate(inputs: &[InputSummary]) -> Vec<String> {
+    let mut notes = Vec::new();
+
+    let javas: Vec<(&str, &str)> = inputs
+        .iter()
+        .filter_map(|i| Some((i.name.as_str(), i.facts.java_version.as_deref()?)))
+        .collect();
+    if let Some(&(first_name, first_java)) = javas.first() {
+        if let Some(&(other_name, other_java)) = javas.iter().find(|(_, j)| *j != first_java) {
+            notes.push(format!(
+                "`{}` was made with Java {}, but `{}` with Java {}, so they might not be from the same launch.",
+                first_name, first_java, other_name, other_java
+            ));
+        }
+    }
+
+    let times: Vec<(&str, CrashTime)> = inputs
+        .iter()
+        .filter_map(|i| Some((i.name.as_str(), i.crash_time?)))
+        .collect();
+    if let Some(&(first_name, first_time)) = times.first() {
+        if let Some(&(other_name, other_time)) = times.iter().find(|(_, t)| {
+            (t.minutes() - first_time.minutes()).abs() > CRASH_TIME_TOLERANCE_MINUTES
+        }) {
+            notes.push(format!(
+                "`{}` is from a crash at {}, but `{}` from a crash at {}, so they might be about different crashes.",
+                first_name, first_time, other_name, other_time
+            ));
+        }
+    }
+
+    notes
+}
+
+fn crash_time(log: &str) -> Option<CrashTime> {
+    lazy_static! {
+        // Crash reports: "Time: 2024-03-14 10:00:00" or, in old versions, "Time: 3/14/24 10:00 AM"
+        static ref CRASH_REPORT_ISO: Regex =
+            Regex::new(r"(?m)^Time: ([0-9]{4})-([0-9]{2})-([0-9]{2}) ([0-9]{2}):([0-9]{2})").unwrap();
+        static ref CRASH_REPORT_US: Regex =
+            Regex::new(r"(?m)^Time: ([0-9]{1,2})/([0-9]{1,2})/([0-9]{2}),? ([0-9]{1,2}):([0-9]{2}) ?(AM|PM)?").unwrap();
+        // hs_err_pid: "Time: Thu Mar 14 10:00:00 2024 CET elapsed time: ..."
+        static ref HS_ERR: Regex =
+            Regex::new(r"(?m)^Time: [A-Z][a-z]{2} ([A-Z][a-z]{2}) +([0-9]{1,2}) ([0-9]{2}):([0-9]{2}):[0-9]{2} ([0-9]{4})").unwrap();
+    }
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    if let Some(c) = CRASH_REPORT_ISO.captures(log) {
+        return Some(CrashTime {
+            year: c[1].parse().ok()?,
+            month: c[2].parse().ok()?,
+            day: c[3].parse().ok()?,
+            hour: c[4].parse().ok()?,
+            minute: c[5].parse().ok()?,
+        });
+    }
+    if let Some(c) = CRASH_REPORT_US.captures(log) {
+        let hour: u32 = c[4].parse().ok()?;
+        let hour = match c.get(6).map(|m| m.as_str()) {
+            Some("PM") if hour < 12 => hour + 12,
+            Some("AM") if hour == 12 => 0,
+            _ => hour,
+        };
+        return Some(CrashTime {
+            year: 2000 + c[3].parse::<i32>().ok()?,
+            month: c[1].parse().ok()?,
+            day: c[2].parse().ok()?,
+            hour,
+            minute: c[5].parse().ok()?,
+        });
+    }
+    let c = HS_ERR.captures(log)?;
+    Some(CrashTime {
+        year: c[5].parse().ok()?,
+        month: MONTHS.iter().position(|m| *m == &c[1])? as u32 + 1,
+        day: c[2].parse().ok()?,
+        hour: c[3].parse().ok()?,
+        minute: c[4].parse().ok()?,
+    })
+}
+
+impl CrashTime {
+    /// Minutes since 1970-01-01, ignoring time zones.
+    fn minutes(&self) -> i64 {
+        // Days from civil date, see http://howardhinnant.github.io/date_algorithms.html
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = i64::from(self.month);
+        let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5
+            + i64::from(self.day)
+            - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = era * 146097 + day_of_era - 719468;
+        (days * 24 + i64::from(self.hour)) * 60 + i64::from(self.minute)
+    }
+}
+
+impl fmt::Display for CrashTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAUNCHER_LOG: &str = "MultiMC version: 0.7.0-3275\n\n\
+        Minecraft folder is:\nD:/MultiMC/instances/1.12.2/.minecraft\n\n\n\
+        Java is version 1.8.0_372, using 64-bit architecture.\n\n\
+        [12:00:00] [main/INFO]: Loading Minecraft 1.12.2\n";
+
+    const CRASH_REPORT: &str = "---- Minecraft Crash Report ----\n\
+        Time: 2024-03-14 10:00:00\n\
+        Description: Initializing game\n\
+        \n\
+        java.lang.OutOfMemoryError: Java heap space\n\
+        \n\
+        -- System Details --\n\
+        \tJava Version: 1.8.0_372, Oracle Corporation";
+
+    fn out_of_memory(report: &SessionReport) -> &Evidence {
+        let diagnosis = report.diagnoses.iter().find(|d| d.id == "out-of-memory").unwrap();
+        &diagnosis.evidence[0]
+    }
+
+    #[test]
+    fn locates_evidence_in_the_input_it_came_from() {
+        let mut session = Session::new();
+        session.add("MultiMC.log", LAUNCHER_LOG).add("crash-2024-03-14.txt", CRASH_REPORT);
+        let report = session.analyze();
+        let evidence = out_of_memory(&report);
+        assert_eq!(evidence.source.as_deref(), Some("crash-2024-03-14.txt"));
+        assert_eq!(evidence.line, 5);
+        assert_eq!(evidence.text, "java.lang.OutOfMemoryError: Java heap space");
+    }
+
+    #[test]
+    fn counts_lines_of_inputs_without_a_trailing_newline() {
+        let mut session = Session::new();
+        session
+            .add("MultiMC.log", LAUNCHER_LOG.trim_end())
+            .add("crash-2024-03-14.txt", CRASH_REPORT);
+        let evidence = out_of_memory(&session.analyze()).clone();
+        assert_eq!(evidence.source.as_deref(), Some("crash-2024-03-14.txt"));
+        assert_eq!(evidence.line, 5);
+
+        // And of the first input
+        let mut session = Session::new();
+        session.add("crash-2024-03-14.txt", CRASH_REPORT).add("MultiMC.log", LAUNCHER_LOG);
+        let evidence = out_of_memory(&session.analyze()).clone();
+        assert_eq!(evidence.source.as_deref(), Some("crash-2024-03-14.txt"));
+        assert_eq!(evidence.line, 5);
+    }
+
+    #[test]
+    fn notes_inputs_from_different_launches() {
+        let mut session = Session::new();
+        session.add("MultiMC.log", LAUNCHER_LOG).add("crash-2024-03-14.txt", CRASH_REPORT);
+        assert!(session.analyze().notes.is_empty());
+
+        let other_java = CRASH_REPORT.replace("Java Version: 1.8.0_372", "Java Version: 17.0.2");
+        let other_time = CRASH_REPORT.replace("10:00:00", "10:06:00");
+        let mut session = Session::new();
+        session
+            .add("crash-1.txt", CRASH_REPORT)
+            .add("crash-2.txt", &other_java)
+            .add("crash-3.txt", &other_time);
+        assert_eq!(
+            session.analyze().notes,
+            [
+                "`crash-1.txt` was made with Java 1.8.0_372, but `crash-2.txt` with Java 17.0.2, \
+                 so they might not be from the same launch.",
+                "`crash-1.txt` is from a crash at 2024-03-14 10:00, but `crash-3.txt` from a crash at \
+                 2024-03-14 10:06, so they might be about different crashes.",
+            ]
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Downloading pastes and attachments without trusting the server: every download has
+//! timeouts and a size limit, and only things that look like logs are accepted.
+
+use std::{fmt, time::Duration};
+
+use reqwest::{header, Client, Response, StatusCode, Url};
+
+/// How long and how much a [`Fetcher`] downloads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FetchLimits {
+    /// Time to connect to the server.
+    pub connect_timeout: Duration,
+    /// Time the server may take to send the headers or the next part of the body.
+    pub read_timeout: Duration,
+    /// Time for the whole download, so a server sending a byte now and then can't keep it
+    /// going for hours.
+    pub total_timeout: Duration,
+    /// Size of the body. Logs are text, so even big ones are a few megabytes.
+    pub max_size: u64,
+    /// Attempts after the first one when the server couldn't be reached or had a problem of
+    /// its own.
+    pub retries: u32,
+    /// Wait before the first retry, doubled for each one after that.
+    pub backoff: Duration,
+}
+
+impl Default for FetchLimits {
+    fn default() -> Self {
+        FetchLimits {
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(20),
+            total_timeout: Duration::from_secs(60),
+            max_size: 16 * 1024 * 1024,
+            retries: 2,
+            backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+/// What a download turned out to be, going by its first bytes rather than what the server
+/// claims.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentKind {
+    Text,
+    Gzip,
+    Zip,
+    /// A web page, like the login or error page of a paste site.
+    Html,
+    /// Anything else, like an image.
+    Binary,
+}
+
+impl ContentKind {
+    /// Sniffs the kind of `bytes` from its start.
+    pub fn sniff(bytes: &[u8]) -> ContentKind {
+        let start = &bytes[..bytes.len().min(1024)];
+        if start.starts_with(b"\x1f\x8b") {
+            return ContentKind::Gzip;
+        }
+        if start.starts_with(b"PK\x03\x04") || start.starts_with(b"PK\x05\x06") {
+            return ContentKind::Zip;
+        }
+        // UTF-16 logs are full of NUL bytes
+        if start.starts_with(b"\xff\xfe") || start.starts_with(b"\xfe\xff") {
+            return ContentKind::Text;
+        }
+        if start.contains(&0) {
+            return ContentKind::Binary;
+        }
+        let text = String::from_utf8_lossy(start);
+        let text = text.trim_start_matches('\u{feff}').trim_start().to_ascii_lowercase();
+        if text.starts_with("<!doctype html") || text.starts_with("<html") {
+            return ContentKind::Html;
+        }
+        ContentKind::Text
+    }
+
+    /// Whether it can contain a log.
+    pub fn is_log(self) -> bool {
+        matches!(self, ContentKind::Text | ContentKind::Gzip | ContentKind::Zip)
+    }
+}
+
+/// A downloaded log, or an archive that may contain logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fetched {
+    pub bytes: Vec<u8>,
+    pub kind: ContentKind,
+}
+
+#[derive(Debug)]
+pub enum FetchError {
+    Http(reqwest::Error),
+    /// The site answered with an error, e.g. 404 for a deleted paste.
+    Status(StatusCode),
+    /// The server didn't answer or stopped sending in time.
+    Timeout,
+    TooLarge { limit: u64 },
+    /// It's not a log, e.g. a web page or an image.
+    NotALog(ContentKind),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::Http(why) => why.fmt(f),
+            FetchError::Status(status) => write!(f, "the server answered {}", status),
+            FetchError::Timeout => f.write_str("the server took too long to answer"),
+            FetchError::TooLarge { limit } => write!(f, "it's larger than {} bytes", limit),
+            FetchError::NotALog(ContentKind::Html) => f.write_str("it's a web page, not a log"),
+            FetchError::NotALog(_) => f.write_str("it's not a log"),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FetchError::Http(why) => Some(why),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(why: reqwest::Error) -> Self {
+        if why.is_timeout() {
+            FetchError::Timeout
+        } else {
+            FetchError::Http(why)
+        }
+    }
+}
+
+impl FetchError {
+    /// Whether trying again later might work.
+    fn is_transient(&self) -> bool {
+        match self {
+            FetchError::Http(why) => why.is_connect() || why.is_request() || why.is_body(),
+            FetchError::Status(status) => status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS,
+            FetchError::Timeout => true,
+            FetchError::TooLarge { .. } | FetchError::NotALog(_) => false,
+        }
+    }
+}
+
+/// Downloads logs within [`FetchLimits`].
+#[derive(Debug, Clone)]
+pub struct Fetcher {
+    client: Client,
+    limits: FetchLimits,
+}
+
+impl Fetcher {
+    pub fn new(limits: FetchLimits) -> Result<Self, FetchError> {
+        let client = Client::builder()
+            .connect_timeout(limits.connect_timeout)
+            .user_agent(concat!("background-cat/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+        Ok(Fetcher { client, limits })
+    }
+
+    pub fn limits(&self) -> &FetchLimits {
+        &self.limits
+    }
+
+    /// Downloads `url`, trying again after a while if the server couldn't be reached or had a
+    /// problem of its own.
+    pub async fn fetch(&self, url: &Url) -> Result<Fetched, FetchError> {
+        let mut backoff = self.limits.backoff;
+        let mut attempt = 0;
+        loop {
+            match self.fetch_once(url).await {
+                Err(why) if why.is_transient() && attempt < self.limits.retries => {
+                    attempt += 1;
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn fetch_once(&self, url: &Url) -> Result<Fetched, FetchError> {
+        tokio::time::timeout(self.limits.total_timeout, self.download(url))
+            .await
+            .map_err(|_| FetchError::Timeout)?
+    }
+
+    async fn download(&self, url: &Url) -> Result<Fetched, FetchError> {
+        let request = self.client.get(url.clone()).send();
+        let mut response = self.timed(request).await??;
+        if !response.status().is_success() {
+            return Err(FetchError::Status(response.status()));
+        }
+        check_headers(&response, self.limits.max_size)?;
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = self.timed(response.chunk()).await?? {
+            if (bytes.len() + chunk.len()) as u64 > self.limits.max_size {
+                return Err(FetchError::TooLarge {
+                    limit: self.limits.max_size,
+                });
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        match ContentKind::sniff(&bytes) {
+            kind if kind.is_log() => Ok(Fetched { bytes, kind }),
+            kind => Err(FetchError::NotALog(kind)),
+        }
+    }
+
+    async fn timed<T>(&self, future: impl std::future::Future<Output = T>) -> Result<T, FetchError> {
+        tokio::time::timeout(self.limits.read_timeout, future)
+            .await
+            .map_err(|_| FetchError::Timeout)
+    }
+}
+
+/// Turns down responses that say they're too large or not text before downloading them.
+fn check_headers(response: &Response, max_size: u64) -> Result<(), FetchError> {
+    if response.content_length().is_some_and(|length| length > max_size) {
+        return Err(FetchError::TooLarge { limit: max_size });
+    }
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if ["image/", "video/", "audio/"].iter().any(|t| content_type.starts_with(t)) {
+        return Err(FetchError::NotALog(ContentKind::Binary));
+    }
+    Ok(())
+}
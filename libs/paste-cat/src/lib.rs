@@ -0,0 +1,9 @@
+//! Getting logs from the paste sites people share them on, and putting them there.
+
+pub mod fetch;
+pub mod providers;
+pub mod upload;
+pub use fetch::{ContentKind, FetchError, FetchLimits, Fetched, Fetcher};
+pub use providers::{PasteLink, PasteProvider, Providers};
+pub use upload::{UploadError, Uploader};
+pub use reqwest::Url;
@@ -0,0 +1,339 @@
+//! Paste sites people share logs on, and where to find the raw text of their pastes.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use reqwest::Url;
+
+/// A site logs can be shared on.
+pub trait PasteProvider: Send + Sync {
+    /// Name of the site to show to users, e.g. "paste.ee".
+    fn name(&self) -> &'static str;
+
+    /// Where to download the text of the paste `url` links to, if it links to a paste on
+    /// this site.
+    fn raw_url(&self, url: &Url) -> Option<Url>;
+}
+
+/// The non-empty parts of the path of `url`.
+fn segments(url: &Url) -> Vec<&str> {
+    url.path_segments()
+        .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+pub(crate) fn is_id(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// paste.ee, also known as pastee.dev: `https://paste.ee/p/<id>`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PasteEe;
+
+impl PasteProvider for PasteEe {
+    fn name(&self) -> &'static str {
+        "paste.ee"
+    }
+
+    fn raw_url(&self, url: &Url) -> Option<Url> {
+        let host = match url.host_str()? {
+            "paste.ee" | "api.paste.ee" => "paste.ee",
+            "pastee.dev" => "pastee.dev",
+            _ => return None,
+        };
+        match segments(url)[..] {
+            ["p" | "r" | "d", id, ..] if is_id(id) => format!("https://{}/r/{}", host, id).parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+/// mclo.gs: `https://mclo.gs/<id>`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct McLogs;
+
+impl PasteProvider for McLogs {
+    fn name(&self) -> &'static str {
+        "mclo.gs"
+    }
+
+    fn raw_url(&self, url: &Url) -> Option<Url> {
+        let segments = segments(url);
+        let id = match (url.host_str()?, &segments[..]) {
+            ("mclo.gs", [id]) => id,
+            ("api.mclo.gs", ["1", "raw", id]) => id,
+            _ => return None,
+        };
+        is_id(id)
+            .then(|| format!("https://api.mclo.gs/1/raw/{}", id).parse().ok())
+            .flatten()
+    }
+}
+
+/// pastebin.com: `https://pastebin.com/<id>`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Pastebin;
+
+impl PasteProvider for Pastebin {
+    fn name(&self) -> &'static str {
+        "pastebin"
+    }
+
+    fn raw_url(&self, url: &Url) -> Option<Url> {
+        if !matches!(url.host_str()?, "pastebin.com" | "www.pastebin.com") {
+            return None;
+        }
+        let id = match segments(url)[..] {
+            [id] | ["raw" | "dl", id] => id,
+            _ => return None,
+        };
+        is_id(id)
+            .then(|| format!("https://pastebin.com/raw/{}", id).parse().ok())
+            .flatten()
+    }
+}
+
+/// Sites running hastebin, which link to `https://<host>/<key>` or `https://<host>/<key>.log`
+/// and serve the text at `/raw/<key>`.
+#[derive(Debug, Clone)]
+pub struct Hastebin {
+    /// Hosts, with the port if it isn't the default one.
+    hosts: Vec<String>,
+}
+
+impl Default for Hastebin {
+    fn default() -> Self {
+        Hastebin::new(["hastebin.com", "www.toptal.com", "hst.sh", "hastebin.skyra.pw", "haste.zneix.eu"])
+    }
+}
+
+impl Hastebin {
+    /// Hastebin on `hosts`, e.g. a self-hosted one at `paste.example.org:7777`.
+    pub fn new<S: Into<String>>(hosts: impl IntoIterator<Item = S>) -> Self {
+        Hastebin {
+            hosts: hosts.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl PasteProvider for Hastebin {
+    fn name(&self) -> &'static str {
+        "hastebin"
+    }
+
+    fn raw_url(&self, url: &Url) -> Option<Url> {
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str()?, port),
+            None => url.host_str()?.to_string(),
+        };
+        if !self.hosts.contains(&host) {
+            return None;
+        }
+        let segments = segments(url);
+        // Toptal moved hastebin.com to www.toptal.com/developers/hastebin
+        let (prefix, path) = match &segments[..] {
+            ["developers", "hastebin", path @ ..] => ("/developers/hastebin", path),
+            _ if host == "www.toptal.com" => return None,
+            path => ("", path),
+        };
+        let key = match path {
+            [key] | ["raw" | "share" | "documents", key] => key,
+            _ => return None,
+        };
+        // The extension only picks the highlighting
+        let key = key.split('.').next()?;
+        if !is_id(key) {
+            return None;
+        }
+        let mut raw = url.clone();
+        raw.set_path(&format!("{}/raw/{}", prefix, key));
+        raw.set_query(None);
+        raw.set_fragment(None);
+        Some(raw)
+    }
+}
+
+/// GitHub Gist: `https://gist.github.com/<user>/<id>`. Gists with several files are read
+/// from their first file.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Gist;
+
+impl PasteProvider for Gist {
+    fn name(&self) -> &'static str {
+        "GitHub Gist"
+    }
+
+    fn raw_url(&self, url: &Url) -> Option<Url> {
+        let segments = segments(url);
+        match (url.host_str()?, &segments[..]) {
+            ("gist.github.com", [user, id, ..]) if is_id(id) => {
+                format!("https://gist.githubusercontent.com/{}/{}/raw", user, id).parse().ok()
+            }
+            ("gist.githubusercontent.com", [_, _, "raw", ..]) => Some(url.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Files uploaded to Discord, when linked instead of attached: `https://cdn.discordapp.com/attachments/...`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiscordCdn;
+
+impl PasteProvider for DiscordCdn {
+    fn name(&self) -> &'static str {
+        "Discord"
+    }
+
+    fn raw_url(&self, url: &Url) -> Option<Url> {
+        if !matches!(url.host_str()?, "cdn.discordapp.com" | "media.discordapp.net") {
+            return None;
+        }
+        match segments(url)[..] {
+            ["attachments", _, _, _] => {
+                // The media proxy only serves images, and the query has the signature that
+                // keeps the link working
+                let mut raw = url.clone();
+                raw.set_host(Some("cdn.discordapp.com")).ok()?;
+                raw.set_fragment(None);
+                Some(raw)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A link to a paste on a known site.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasteLink {
+    /// [`PasteProvider::name`] of the site.
+    pub provider: &'static str,
+    /// The link as it was given.
+    pub url: Url,
+    /// Where the text of the paste is.
+    pub raw: Url,
+}
+
+/// The paste sites links are looked up with.
+pub struct Providers {
+    providers: Vec<Box<dyn PasteProvider>>,
+}
+
+impl Default for Providers {
+    /// All the sites of this module.
+    fn default() -> Self {
+        let mut providers = Providers::empty();
+        providers
+            .add(PasteEe)
+            .add(McLogs)
+            .add(Pastebin)
+            .add(Hastebin::default())
+            .add(Gist)
+            .add(DiscordCdn);
+        providers
+    }
+}
+
+impl Providers {
+    pub fn empty() -> Self {
+        Providers { providers: Vec::new() }
+    }
+
+    /// Also looks links up with `provider`, after the ones already there.
+    pub fn add(&mut self, provider: impl PasteProvider + 'static) -> &mut Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+
+    /// Looks up the paste `url` links to.
+    pub fn resolve(&self, url: &Url) -> Option<PasteLink> {
+        self.providers.iter().find_map(|provider| {
+            Some(PasteLink {
+                provider: provider.name(),
+                raw: provider.raw_url(url)?,
+                url: url.clone(),
+            })
+        })
+    }
+
+    /// Links to pastes in `text`, like a chat message, in the order they appear and without
+    /// duplicates.
+    pub fn find_links(&self, text: &str) -> Vec<PasteLink> {
+        lazy_static! {
+            // Discord users wrap links in <> to hide their embeds
+            static ref URL: Regex = Regex::new(r"https?://[^\s<>|]+").unwrap();
+        }
+        let mut links: Vec<PasteLink> = Vec::new();
+        for found in URL.find_iter(text) {
+            let url = found.as_str().trim_end_matches(['.', ',', ')', '!', '?', ':', ';', '*', '_', '`']);
+            let Some(link) = url.parse().ok().and_then(|url| self.resolve(&url)) else {
+                continue;
+            };
+            if !links.iter().any(|l| l.raw == link.raw) {
+                links.push(link);
+            }
+        }
+        links
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw(url: &str) -> Option<String> {
+        Providers::default()
+            .resolve(&url.parse().unwrap())
+            .map(|link| link.raw.to_string())
+    }
+
+    #[test]
+    fn maps_links_to_raw_text() {
+        let cases = [
+            ("https://paste.ee/p/AbC12", "https://paste.ee/r/AbC12"),
+            ("https://api.paste.ee/p/AbC12", "https://paste.ee/r/AbC12"),
+            ("https://pastee.dev/p/AbC12", "https://pastee.dev/r/AbC12"),
+            ("https://mclo.gs/aBc1234", "https://api.mclo.gs/1/raw/aBc1234"),
+            ("https://pastebin.com/XyZ98765", "https://pastebin.com/raw/XyZ98765"),
+            ("https://pastebin.com/raw/XyZ98765", "https://pastebin.com/raw/XyZ98765"),
+            ("https://hst.sh/ukulele.log", "https://hst.sh/raw/ukulele"),
+            ("https://hastebin.com/share/ukulele.js", "https://hastebin.com/raw/ukulele"),
+            (
+                "https://www.toptal.com/developers/hastebin/ukulele.yaml",
+                "https://www.toptal.com/developers/hastebin/raw/ukulele",
+            ),
+            (
+                "https://gist.github.com/someone/0123abcd/4567",
+                "https://gist.githubusercontent.com/someone/0123abcd/raw",
+            ),
+            (
+                "https://media.discordapp.net/attachments/1/2/latest.log?ex=a&hm=b",
+                "https://cdn.discordapp.com/attachments/1/2/latest.log?ex=a&hm=b",
+            ),
+        ];
+        for (link, expected) in cases {
+            assert_eq!(raw(link).as_deref(), Some(expected), "{}", link);
+        }
+    }
+
+    #[test]
+    fn ignores_other_links() {
+        for link in [
+            "https://paste.ee/",
+            "https://pastee.dev/login",
+            "https://pastebin.com/u/someone",
+            "https://github.com/MultiMC/Launcher",
+            "https://www.toptal.com/developers",
+            "https://cdn.discordapp.com/emojis/1.png",
+        ] {
+            assert_eq!(raw(link), None, "{}", link);
+        }
+    }
+
+    #[test]
+    fn finds_links_in_messages() {
+        let links = Providers::default().find_links(
+            "my game crashed <https://paste.ee/p/AbC12>, also https://mclo.gs/aBc1234. and https://paste.ee/p/AbC12",
+        );
+        let raw: Vec<&str> = links.iter().map(|l| l.raw.as_str()).collect();
+        assert_eq!(raw, ["https://paste.ee/r/AbC12", "https://api.mclo.gs/1/raw/aBc1234"]);
+    }
+}
@@ -0,0 +1,292 @@
+//! Uploading logs to paste sites, so people helping can read them in a browser.
+
+use std::{fmt, time::Duration};
+
+use async_trait::async_trait;
+use reqwest::{header, Client, RequestBuilder, Response, StatusCode, Url};
+use serde_json::{json, Value};
+
+use crate::providers::is_id;
+
+/// How long an upload may take, including sending the logs.
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub enum UploadError {
+    Http(reqwest::Error),
+    /// The site didn't accept the credentials.
+    Unauthorized,
+    Status(StatusCode),
+    /// The site refused the upload, with its reason.
+    Rejected(String),
+    /// The site answered with something that isn't what its API documents.
+    InvalidResponse(String),
+}
+
+impl fmt::Display for UploadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UploadError::Http(why) => why.fmt(f),
+            UploadError::Unauthorized => f.write_str("the credentials were refused"),
+            UploadError::Status(status) => write!(f, "the server answered {}", status),
+            UploadError::Rejected(why) => write!(f, "the upload was refused: {}", why),
+            UploadError::InvalidResponse(why) => write!(f, "unexpected answer: {}", why),
+        }
+    }
+}
+
+impl std::error::Error for UploadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UploadError::Http(why) => Some(why),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for UploadError {
+    fn from(why: reqwest::Error) -> Self {
+        UploadError::Http(why)
+    }
+}
+
+/// A site logs can be uploaded to. `logs` are pairs of file name and content.
+#[async_trait]
+pub trait Uploader: Send + Sync {
+    /// Name of the site to show to users, e.g. "paste.ee".
+    fn name(&self) -> &'static str;
+
+    /// Checks that the site can be reached and accepts the credentials, e.g. when starting up,
+    /// so a wrong token doesn't go unnoticed until someone wants to upload a log.
+    async fn validate(&self) -> Result<(), UploadError>;
+
+    /// Uploads `logs` and returns the link to them.
+    async fn upload(&self, description: &str, logs: &[(String, String)]) -> Result<Url, UploadError>;
+}
+
+fn client() -> Result<Client, UploadError> {
+    let client = Client::builder()
+        .timeout(UPLOAD_TIMEOUT)
+        .user_agent(concat!("background-cat/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    Ok(client)
+}
+
+/// Sends `request` and reads the JSON answer, turning error statuses into errors.
+async fn send_json(request: RequestBuilder) -> Result<Value, UploadError> {
+    let response = request.send().await?;
+    let response = check_status(response)?;
+    let text = response.text().await?;
+    serde_json::from_str(&text).map_err(|why| UploadError::InvalidResponse(why.to_string()))
+}
+
+fn check_status(response: Response) -> Result<Response, UploadError> {
+    match response.status() {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Err(UploadError::Unauthorized),
+        status if !status.is_success() => Err(UploadError::Status(status)),
+        _ => Ok(response),
+    }
+}
+
+fn link(value: &Value, key: &str) -> Result<Url, UploadError> {
+    value[key]
+        .as_str()
+        .and_then(|link| link.parse().ok())
+        .ok_or_else(|| UploadError::InvalidResponse(format!("no `{}` in {}", key, value)))
+}
+
+/// Sites without sections get all logs in one text, each under its name.
+fn concatenate(logs: &[(String, String)]) -> String {
+    match logs {
+        [(_, log)] => log.clone(),
+        _ => logs
+            .iter()
+            .map(|(name, log)| format!("===== {} =====\n{}", name, log))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+/// paste.ee, with an API token of an application or user.
+#[derive(Debug, Clone)]
+pub struct PasteEe {
+    api: Url,
+    token: String,
+    expiry: Option<Duration>,
+    client: Client,
+}
+
+impl PasteEe {
+    /// Uploads with `token`. Pastes expire after `expiry`, or are kept until they're deleted.
+    /// Fails if the HTTP client can't be set up.
+    pub fn new(token: impl Into<String>, expiry: Option<Duration>) -> Result<Self, UploadError> {
+        Ok(PasteEe {
+            api: "https://api.paste.ee/".parse().expect("the paste.ee API URL is valid"),
+            token: token.into(),
+            expiry,
+            client: client()?,
+        })
+    }
+
+    /// Uses the API at `api` instead of paste.ee's.
+    pub fn api(mut self, api: Url) -> Self {
+        self.api = api;
+        self
+    }
+
+    fn endpoint(&self, path: &str) -> Result<Url, UploadError> {
+        self.api
+            .join(path)
+            .map_err(|why| UploadError::InvalidResponse(why.to_string()))
+    }
+}
+
+#[async_trait]
+impl Uploader for PasteEe {
+    fn name(&self) -> &'static str {
+        "paste.ee"
+    }
+
+    async fn validate(&self) -> Result<(), UploadError> {
+        let request = self
+            .client
+            .get(self.endpoint("v1/pastes?perpage=1")?)
+            .header("X-Auth-Token", &self.token);
+        send_json(request).await.map(|_| ())
+    }
+
+    async fn upload(&self, description: &str, logs: &[(String, String)]) -> Result<Url, UploadError> {
+        let sections: Vec<Value> = logs
+            .iter()
+            .map(|(name, log)| json!({ "name": name, "contents": log }))
+            .collect();
+        let mut body = json!({ "description": description, "sections": sections });
+        if let Some(expiry) = self.expiry {
+            body["expiration"] = expiry.as_secs().to_string().into();
+        }
+        let request = self
+            .client
+            .post(self.endpoint("v1/pastes")?)
+            .header("X-Auth-Token", &self.token)
+            .json(&body);
+        let response = send_json(request).await?;
+        if response["success"].as_bool() != Some(true) {
+            let why = response["errors"][0]["message"].as_str().unwrap_or("no reason given");
+            return Err(UploadError::Rejected(why.to_string()));
+        }
+        link(&response, "link")
+    }
+}
+
+/// mclo.gs, which needs no account and keeps logs for 90 days after they were last viewed.
+#[derive(Debug, Clone)]
+pub struct McLogs {
+    api: Url,
+    client: Client,
+}
+
+impl McLogs {
+    /// Fails if the HTTP client can't be set up.
+    pub fn new() -> Result<Self, UploadError> {
+        Ok(McLogs {
+            api: "https://api.mclo.gs/".parse().expect("the mclo.gs API URL is valid"),
+            client: client()?,
+        })
+    }
+
+    /// Uses the API at `api` instead of mclo.gs', e.g. for a self-hosted instance.
+    pub fn api(mut self, api: Url) -> Self {
+        self.api = api;
+        self
+    }
+}
+
+#[async_trait]
+impl Uploader for McLogs {
+    fn name(&self) -> &'static str {
+        "mclo.gs"
+    }
+
+    /// There are no credentials, so this only checks that the API is up.
+    async fn validate(&self) -> Result<(), UploadError> {
+        let response = self.client.get(self.api.clone()).send().await?;
+        check_status(response).map(|_| ())
+    }
+
+    async fn upload(&self, _description: &str, logs: &[(String, String)]) -> Result<Url, UploadError> {
+        let url = self
+            .api
+            .join("1/log")
+            .map_err(|why| UploadError::InvalidResponse(why.to_string()))?;
+        let request = self.client.post(url).form(&[("content", concatenate(logs))]);
+        let response = send_json(request).await?;
+        if response["success"].as_bool() != Some(true) {
+            let why = response["error"].as_str().unwrap_or("no reason given");
+            return Err(UploadError::Rejected(why.to_string()));
+        }
+        link(&response, "url")
+    }
+}
+
+/// A self-hosted hastebin server (haste-server and compatible ones). How long documents are
+/// kept is configured on the server.
+#[derive(Debug, Clone)]
+pub struct SelfHosted {
+    url: Url,
+    token: Option<String>,
+    client: Client,
+}
+
+impl SelfHosted {
+    /// The server at `url`, sending `token` as a bearer token if it needs one. Fails if the
+    /// HTTP client can't be set up.
+    pub fn new(mut url: Url, token: Option<String>) -> Result<Self, UploadError> {
+        // Paths are joined onto it, so `https://example.org/paste` must end with a slash
+        if !url.path().ends_with('/') {
+            url.set_path(&format!("{}/", url.path()));
+        }
+        Ok(SelfHosted {
+            url,
+            token,
+            client: client()?,
+        })
+    }
+
+    fn authorized(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.token {
+            Some(token) => request.header(header::AUTHORIZATION, format!("Bearer {}", token)),
+            None => request,
+        }
+    }
+
+    fn endpoint(&self, path: &str) -> Result<Url, UploadError> {
+        self.url
+            .join(path)
+            .map_err(|why| UploadError::InvalidResponse(why.to_string()))
+    }
+}
+
+#[async_trait]
+impl Uploader for SelfHosted {
+    fn name(&self) -> &'static str {
+        "the paste server"
+    }
+
+    async fn validate(&self) -> Result<(), UploadError> {
+        let response = self.authorized(self.client.get(self.url.clone())).send().await?;
+        check_status(response).map(|_| ())
+    }
+
+    async fn upload(&self, _description: &str, logs: &[(String, String)]) -> Result<Url, UploadError> {
+        let request = self.authorized(self.client.post(self.endpoint("documents")?)).body(concatenate(logs));
+        let response = send_json(request).await?;
+        let key = response["key"]
+            .as_str()
+            .ok_or_else(|| UploadError::InvalidResponse(format!("no `key` in {}", response)))?;
+        // Joined onto the server's URL, where a path or an absolute URL would lead elsewhere
+        if !is_id(key) {
+            return Err(UploadError::InvalidResponse(format!("invalid `key` in {}", response)));
+        }
+        self.endpoint(key)
+    }
+}
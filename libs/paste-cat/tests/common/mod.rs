@@ -0,0 +1,148 @@
+//! A local HTTP server answering with canned responses, standing in for paste sites.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+/// What the server answers for a path.
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    /// Leave out `Content-Length`, so the body ends when the connection is closed.
+    pub unknown_length: bool,
+    /// Wait before answering.
+    pub delay: Duration,
+    /// Send the body a byte at a time, waiting this long before each.
+    pub trickle: Option<Duration>,
+}
+
+impl Response {
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+        Response {
+            status: 200,
+            headers: vec![("Content-Type".to_string(), "text/plain; charset=utf-8".to_string())],
+            body: body.into(),
+            unknown_length: false,
+            delay: Duration::ZERO,
+            trickle: None,
+        }
+    }
+
+    pub fn status(status: u16) -> Self {
+        Response {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+            unknown_length: false,
+            delay: Duration::ZERO,
+            trickle: None,
+        }
+    }
+
+    /// Sets the header `name`, replacing the one that was there.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.retain(|(other, _)| !other.eq_ignore_ascii_case(name));
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+pub struct MockServer {
+    /// `host:port` the server listens on.
+    pub host: String,
+    routes: Arc<Mutex<Vec<(String, Response)>>>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    /// Starts a server on a free port. It runs until the test ends.
+    pub fn start() -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = MockServer {
+            host: listener.local_addr().unwrap().to_string(),
+            routes: Arc::default(),
+            requests: Arc::default(),
+        };
+        let routes = Arc::clone(&server.routes);
+        let requests = Arc::clone(&server.requests);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let routes = Arc::clone(&routes);
+                let requests = Arc::clone(&requests);
+                thread::spawn(move || serve(stream, &routes, &requests));
+            }
+        });
+        server
+    }
+
+    /// Answers requests for `path` with `response`. Several responses for a path are given
+    /// one after the other, and the last one is repeated.
+    pub fn route(&self, path: &str, response: Response) -> &Self {
+        self.routes.lock().unwrap().push((path.to_string(), response));
+        self
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.host, path)
+    }
+
+    /// Paths that were requested, in order.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn serve(stream: TcpStream, routes: &Mutex<Vec<(String, Response)>>, requests: &Mutex<Vec<String>>) {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    // Skip the headers
+    let mut line = String::new();
+    while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+        line.clear();
+    }
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/").to_string();
+    requests.lock().unwrap().push(path.clone());
+
+    let response = {
+        let mut routes = routes.lock().unwrap();
+        let matching: Vec<usize> = (0..routes.len()).filter(|&i| routes[i].0 == path).collect();
+        match matching[..] {
+            [] => Response::status(404),
+            [only] => routes[only].1.clone(),
+            [first, ..] => routes.remove(first).1,
+        }
+    };
+    thread::sleep(response.delay);
+    let mut head = format!("HTTP/1.1 {} Mock\r\nConnection: close\r\n", response.status);
+    if !response.unknown_length {
+        head.push_str(&format!("Content-Length: {}\r\n", response.body.len()));
+    }
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    let mut stream = &stream;
+    stream.write_all(head.as_bytes()).ok();
+    match response.trickle {
+        Some(pause) => {
+            for byte in response.body.chunks(1) {
+                thread::sleep(pause);
+                if stream.write_all(byte).and_then(|()| stream.flush()).is_err() {
+                    break;
+                }
+            }
+        }
+        None => {
+            stream.write_all(&response.body).ok();
+        }
+    }
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{MockServer, Response};
+use paste_cat::{providers::Hastebin, ContentKind, FetchError, FetchLimits, Fetcher, Providers, Url};
+use reqwest::StatusCode;
+
+fn providers(server: &MockServer) -> Providers {
+    let mut providers = Providers::default();
+    providers.add(Hastebin::new([server.host.as_str()]));
+    providers
+}
+
+/// Limits that keep the tests fast.
+fn limits() -> FetchLimits {
+    FetchLimits {
+        read_timeout: Duration::from_secs(5),
+        max_size: 1024,
+        retries: 2,
+        backoff: Duration::from_millis(1),
+        ..FetchLimits::default()
+    }
+}
+
+async fn fetch(server: &MockServer, path: &str, limits: FetchLimits) -> Result<paste_cat::Fetched, FetchError> {
+    let url: Url = server.url(path).parse().unwrap();
+    Fetcher::new(limits).unwrap().fetch(&url).await
+}
+
+#[tokio::test]
+async fn fetches_the_raw_text_of_a_link() {
+    let server = MockServer::start();
+    server.route("/raw/ukulele", Response::ok("[12:00:00] [main/INFO]: hi\n"));
+
+    let message = format!("here's my log: <{}>", server.url("/ukulele.log"));
+    let links = providers(&server).find_links(&message);
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].provider, "hastebin");
+
+    let fetched = Fetcher::new(limits()).unwrap().fetch(&links[0].raw).await.unwrap();
+    assert_eq!(fetched.bytes, b"[12:00:00] [main/INFO]: hi\n");
+    assert_eq!(fetched.kind, ContentKind::Text);
+    assert_eq!(server.requests(), ["/raw/ukulele"]);
+}
+
+#[tokio::test]
+async fn reports_missing_pastes_without_retrying() {
+    let server = MockServer::start();
+
+    match fetch(&server, "/raw/gone", limits()).await {
+        Err(FetchError::Status(status)) => assert_eq!(status, StatusCode::NOT_FOUND),
+        other => panic!("expected a 404, got {:?}", other),
+    }
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn retries_server_errors() {
+    let server = MockServer::start();
+    server
+        .route("/log", Response::status(503))
+        .route("/log", Response::status(429))
+        .route("/log", Response::ok("finally\n"));
+
+    let fetched = fetch(&server, "/log", limits()).await.unwrap();
+    assert_eq!(fetched.bytes, b"finally\n");
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn gives_up_after_the_last_retry() {
+    let server = MockServer::start();
+    server.route("/log", Response::status(500));
+
+    match fetch(&server, "/log", limits()).await {
+        Err(FetchError::Status(status)) => assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR),
+        other => panic!("expected a 500, got {:?}", other),
+    }
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn stops_reading_at_the_size_limit() {
+    let server = MockServer::start();
+    let mut big = Response::ok(vec![b'a'; 64 * 1024]);
+    big.unknown_length = true;
+    server.route("/unknown-length", big);
+    server.route("/known-length", Response::ok(vec![b'a'; 2048]));
+
+    for path in ["/unknown-length", "/known-length"] {
+        match fetch(&server, path, limits()).await {
+            Err(FetchError::TooLarge { limit }) => assert_eq!(limit, 1024),
+            other => panic!("expected {} to be too large, got {:?}", path, other),
+        }
+    }
+}
+
+#[tokio::test]
+async fn times_out_on_slow_servers() {
+    let server = MockServer::start();
+    let mut slow = Response::ok("late\n");
+    slow.delay = Duration::from_millis(500);
+    server.route("/slow", slow);
+
+    let limits = FetchLimits {
+        read_timeout: Duration::from_millis(50),
+        retries: 1,
+        ..limits()
+    };
+    match fetch(&server, "/slow", limits).await {
+        Err(FetchError::Timeout) => {}
+        other => panic!("expected a timeout, got {:?}", other),
+    }
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn times_out_on_servers_sending_a_byte_now_and_then() {
+    let server = MockServer::start();
+    let mut trickling = Response::ok("[12:00:00] [main/INFO]: slowly\n");
+    trickling.trickle = Some(Duration::from_millis(20));
+    server.route("/trickle", trickling);
+
+    let limits = FetchLimits {
+        read_timeout: Duration::from_millis(200),
+        total_timeout: Duration::from_millis(300),
+        retries: 0,
+        ..limits()
+    };
+    match fetch(&server, "/trickle", limits).await {
+        Err(FetchError::Timeout) => {}
+        other => panic!("expected a timeout, got {:?}", other),
+    }
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn sniffs_what_was_downloaded() {
+    let server = MockServer::start();
+    server.route("/page", Response::ok("\n<!DOCTYPE html>\n<html><body>Log in</body></html>"));
+    server.route("/image", Response::ok("GIF89a").header("Content-Type", "image/gif"));
+    server.route("/gzip", Response::ok(b"\x1f\x8b\x08\x00".to_vec()));
+
+    assert!(matches!(fetch(&server, "/page", limits()).await, Err(FetchError::NotALog(ContentKind::Html))));
+    assert!(matches!(fetch(&server, "/image", limits()).await, Err(FetchError::NotALog(_))));
+    assert_eq!(fetch(&server, "/gzip", limits()).await.unwrap().kind, ContentKind::Gzip);
+}
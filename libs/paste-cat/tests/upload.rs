@@ -0,0 +1,102 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{MockServer, Response};
+use paste_cat::{
+    upload::{McLogs, PasteEe, SelfHosted},
+    UploadError, Uploader, Url,
+};
+
+fn logs() -> Vec<(String, String)> {
+    vec![("latest.log".to_string(), "[12:00:00] [main/INFO]: hi\n".to_string())]
+}
+
+fn api(server: &MockServer) -> Url {
+    server.url("/").parse().unwrap()
+}
+
+fn json(body: &str) -> Response {
+    Response::ok(body).header("Content-Type", "application/json")
+}
+
+#[tokio::test]
+async fn uploads_to_paste_ee() {
+    let server = MockServer::start();
+    server.route(
+        "/v1/pastes",
+        json(r#"{"success": true, "id": "AbC12", "link": "https://paste.ee/p/AbC12"}"#),
+    );
+
+    let paste_ee = PasteEe::new("token", Some(Duration::from_secs(3600))).unwrap().api(api(&server));
+    let link = paste_ee.upload("a log", &logs()).await.unwrap();
+    assert_eq!(link.as_str(), "https://paste.ee/p/AbC12");
+    assert_eq!(server.requests(), ["/v1/pastes"]);
+}
+
+#[tokio::test]
+async fn reports_refused_tokens() {
+    let server = MockServer::start();
+    server.route("/v1/pastes?perpage=1", Response::status(401));
+
+    let paste_ee = PasteEe::new("wrong", None).unwrap().api(api(&server));
+    assert!(matches!(paste_ee.validate().await, Err(UploadError::Unauthorized)));
+}
+
+#[tokio::test]
+async fn reports_refused_uploads() {
+    let server = MockServer::start();
+    server.route(
+        "/v1/pastes",
+        json(r#"{"success": false, "errors": [{"field": "sections", "message": "The paste is too large."}]}"#),
+    );
+    server.route("/1/log", json(r#"{"success": true, "id": "aBc1234"}"#));
+
+    let paste_ee = PasteEe::new("token", None).unwrap().api(api(&server));
+    match paste_ee.upload("a log", &logs()).await {
+        Err(UploadError::Rejected(why)) => assert_eq!(why, "The paste is too large."),
+        other => panic!("expected the upload to be refused, got {:?}", other),
+    }
+    let mclogs = McLogs::new().unwrap().api(api(&server));
+    assert!(matches!(mclogs.upload("a log", &logs()).await, Err(UploadError::InvalidResponse(_))));
+}
+
+#[tokio::test]
+async fn uploads_to_mclogs() {
+    let server = MockServer::start();
+    server.route(
+        "/1/log",
+        json(r#"{"success": true, "id": "aBc1234", "url": "https://mclo.gs/aBc1234"}"#),
+    );
+
+    let mclogs = McLogs::new().unwrap().api(api(&server));
+    let link = mclogs.upload("a log", &logs()).await.unwrap();
+    assert_eq!(link.as_str(), "https://mclo.gs/aBc1234");
+}
+
+#[tokio::test]
+async fn uploads_to_a_self_hosted_server() {
+    let server = MockServer::start();
+    server.route("/paste/documents", json(r#"{"key": "ukulele"}"#));
+
+    let self_hosted = SelfHosted::new(server.url("/paste").parse().unwrap(), Some("secret".to_string())).unwrap();
+    let link = self_hosted.upload("a log", &logs()).await.unwrap();
+    assert_eq!(link.as_str(), server.url("/paste/ukulele"));
+
+    let down = SelfHosted::new(server.url("/gone/").parse().unwrap(), None).unwrap();
+    assert!(matches!(down.validate().await, Err(UploadError::Status(_))));
+}
+
+#[tokio::test]
+async fn rejects_keys_that_are_not_ids() {
+    let server = MockServer::start();
+    server.route("/absolute/documents", json(r#"{"key": "https://evil.example/x"}"#));
+    server.route("/relative/documents", json(r#"{"key": "../elsewhere"}"#));
+    server.route("/empty/documents", json(r#"{"key": ""}"#));
+
+    for path in ["/absolute", "/relative", "/empty"] {
+        let self_hosted = SelfHosted::new(server.url(path).parse().unwrap(), None).unwrap();
+        let upload = self_hosted.upload("a log", &logs()).await;
+        assert!(matches!(upload, Err(UploadError::InvalidResponse(_))), "{}: {:?}", path, upload);
+    }
+}